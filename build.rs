@@ -2,9 +2,11 @@
 //   SPDX-License-Identifier: BSD-3-Clause
 
 use std::{
+    collections::hash_map::DefaultHasher,
     env,
     error::Error,
     fs,
+    hash::Hasher,
     io,
     io::ErrorKind,
     path::{Path, PathBuf},
@@ -14,15 +16,42 @@ use std::{
 use cargo_toml::Manifest;
 
 const TEMPLATE_BUILTINS: &[&str] = &[
+    "templates/airdrop",
+    "templates/crowdfund",
+    "templates/escrow",
     "templates/faucet",
+    "templates/fixed-price-listing",
+    "templates/fractionalize",
+    "templates/governance",
     "templates/nft-marketplace/templates/index",
     "templates/nft-marketplace/templates/auction",
+    "templates/order-book",
+    "templates/raffle",
+    "templates/rental",
+    "templates/staking",
+    "templates/subscription",
     "templates/tariswap/templates/index",
     "templates/tariswap/templates/pool",
+    "templates/ticket-sale",
+    "templates/tipjar",
+    "templates/vesting",
 ];
 
+const WASM_TARGET: &str = "wasm32-unknown-unknown";
+const INSTALL_WASM_TARGET_COMMAND: &str = "rustup target add wasm32-unknown-unknown";
+// rustc emits this when the standard library for the target is not available
+const MISSING_WASM_TARGET_MESSAGE: &str = "target may not be installed";
+
+// wasm binaries are committed to source control, so we want to catch size regressions early
+const DEFAULT_WASM_SIZE_BUDGET: u64 = 400 * 1024;
+const WASM_SIZE_BUDGETS: &[(&str, u64)] = &[("nft_marketplace_auction", 512 * 1024)];
+// when set, exceeding a size budget fails the build instead of only emitting a warning
+const WASM_STRICT_ENV_VAR: &str = "TARI_WASM_STRICT";
+
 fn main() -> Result<(), Box<dyn Error>> {
     let dist_path = env::current_dir()?.join("wasm");
+    let mut built_wasms = vec![];
+    println!("cargo:rerun-if-env-changed={}", WASM_STRICT_ENV_VAR);
 
     for template in TEMPLATE_BUILTINS {
         // we only want to rebuild if a template was added/modified
@@ -31,30 +60,56 @@ fn main() -> Result<(), Box<dyn Error>> {
 
         let template_path = env::current_dir()?.join(template);
 
-        // compile the template into wasm
-        compile_template(&template_path)?;
+        // local crates shared between templates (e.g. "account-utils") are compiled into the wasm as well
+        let dependency_paths = get_path_dependencies(&template_path)?;
+        for dependency_path in &dependency_paths {
+            println!("cargo:rerun-if-changed={}", dependency_path.join("src").display());
+            println!("cargo:rerun-if-changed={}", dependency_path.join("Cargo.toml").display());
+        }
 
         // get the path of the wasm executable
         let wasm_name = get_wasm_name(&template_path);
         let wasm_path = get_compiled_wasm_path(&template_path, &wasm_name);
+        let wasm_dest = dist_path.join(&wasm_name).with_extension("wasm");
+        built_wasms.push((wasm_name.clone(), wasm_dest.clone()));
 
-        // copy the wasm binary to the dist folder, to be included in source control
-        let wasm_dest = dist_path.join(wasm_name).with_extension("wasm");
-        if wasm_dest.exists() {
-            let existing_contents = fs::read(&wasm_dest)?;
-            let dest_contents = fs::read(&wasm_path)?;
-            if existing_contents == dest_contents {
-                continue;
+        // skip the compilation if the template sources did not change since the last successful build
+        let source_hash = hash_template_sources(&template_path, &dependency_paths)?;
+        let hash_path = get_source_hash_path(&template_path);
+        let stored_hash = fs::read_to_string(&hash_path).ok();
+        let is_cached =
+            stored_hash.as_deref() == Some(source_hash.as_str()) && wasm_path.exists() && wasm_dest.exists();
+        if !is_cached {
+            // compile the template into wasm
+            compile_template(&template_path)?;
+            fs::write(&hash_path, &source_hash)?;
+
+            // copy the wasm binary to the dist folder, to be included in source control
+            let is_unchanged = wasm_dest.exists() && fs::read(&wasm_dest)? == fs::read(&wasm_path)?;
+            if !is_unchanged {
+                fs::copy(&wasm_path, &wasm_dest)?;
             }
         }
-        fs::copy(wasm_path, wasm_dest)?;
+
+        // the size budgets are enforced on every build, even for cached templates
+        check_wasm_size(&wasm_name, &wasm_dest)?;
+    }
+
+    // expose the location of each wasm, so it can be loaded with "env!" instead of hardcoding the "wasm" folder layout
+    // "rustc-env" vars are only visible to the targets of this package, not to the crates that depend on it
+    for (wasm_name, wasm_dest) in built_wasms {
+        println!(
+            "cargo:rustc-env={}={}",
+            get_wasm_env_var(&wasm_name),
+            wasm_dest.display()
+        );
     }
 
     Ok(())
 }
 
 fn compile_template<P: AsRef<Path>>(package_dir: P) -> Result<(), Box<dyn Error>> {
-    let args = ["build", "--target", "wasm32-unknown-unknown", "--release"];
+    let args = ["build", "--target", WASM_TARGET, "--release"];
 
     let output = Command::new("cargo")
         .current_dir(package_dir.as_ref())
@@ -62,10 +117,26 @@ fn compile_template<P: AsRef<Path>>(package_dir: P) -> Result<(), Box<dyn Error>
         .output()?;
 
     if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        // the raw output is always kept for debugging
         eprintln!("stdout:");
         eprintln!("{}", String::from_utf8_lossy(&output.stdout));
         eprintln!("stderr:");
-        eprintln!("{}", String::from_utf8_lossy(&output.stderr));
+        eprintln!("{}", stderr);
+
+        // a very common issue for new contributors, so we give a clear hint on how to fix it
+        if stderr.contains(MISSING_WASM_TARGET_MESSAGE) {
+            println!("cargo:warning=The {} target is not installed, run: {}", WASM_TARGET, INSTALL_WASM_TARGET_COMMAND);
+            return Err(Box::new(io::Error::new(
+                ErrorKind::Other,
+                format!(
+                    "Missing {} target, install it with \"{}\" (see the stderr output above for details)",
+                    WASM_TARGET, INSTALL_WASM_TARGET_COMMAND
+                ),
+            )));
+        }
+
         return Err(Box::new(io::Error::new(
             ErrorKind::Other,
             format!("Failed to compile package: {:?}", package_dir.as_ref(),),
@@ -75,16 +146,119 @@ fn compile_template<P: AsRef<Path>>(package_dir: P) -> Result<(), Box<dyn Error>
     Ok(())
 }
 
+// hashes the "src" folder and the manifest of a template and of its path dependencies, so we can detect when it needs
+// to be rebuilt
+fn hash_template_sources<P: AsRef<Path>>(
+    template_path: P,
+    dependency_paths: &[PathBuf],
+) -> Result<String, Box<dyn Error>> {
+    let mut hasher = DefaultHasher::new();
+    hash_crate_sources(template_path.as_ref(), &mut hasher)?;
+    for dependency_path in dependency_paths {
+        hash_crate_sources(dependency_path, &mut hasher)?;
+    }
+
+    Ok(format!("{:016x}", hasher.finish()))
+}
+
+fn hash_crate_sources(crate_path: &Path, hasher: &mut DefaultHasher) -> Result<(), Box<dyn Error>> {
+    let mut files = vec![crate_path.join("Cargo.toml")];
+    collect_files(&crate_path.join("src"), &mut files)?;
+    // the file order must be deterministic for the hash to be stable
+    files.sort();
+
+    for file in files {
+        hasher.write(file.strip_prefix(crate_path)?.to_string_lossy().as_bytes());
+        hasher.write(&fs::read(&file)?);
+    }
+
+    Ok(())
+}
+
+// returns the local crates the template depends on, including the ones they depend on in turn
+fn get_path_dependencies<P: AsRef<Path>>(template_path: P) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let mut dependency_paths: Vec<PathBuf> = vec![];
+    let mut pending = vec![template_path.as_ref().to_path_buf()];
+
+    while let Some(crate_path) = pending.pop() {
+        let manifest = Manifest::from_path(crate_path.join("Cargo.toml"))?;
+        for dependency in manifest.dependencies.values() {
+            let Some(path) = dependency.detail().and_then(|detail| detail.path.as_ref()) else {
+                continue;
+            };
+            // canonical paths, so the same crate is not visited twice through different relative paths
+            let dependency_path = crate_path.join(path).canonicalize()?;
+            if !dependency_paths.contains(&dependency_path) {
+                dependency_paths.push(dependency_path.clone());
+                pending.push(dependency_path);
+            }
+        }
+    }
+
+    // the order must be deterministic for the hash to be stable
+    dependency_paths.sort();
+    Ok(dependency_paths)
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+// the hash is stored in the target folder of the template, next to the compiled wasm
+fn get_source_hash_path<P: AsRef<Path>>(template_path: P) -> PathBuf {
+    template_path
+        .as_ref()
+        .join("target")
+        .join(WASM_TARGET)
+        .join("release")
+        .join(".source_hash")
+}
+
 fn get_wasm_name<P: AsRef<Path>>(template_path: P) -> String {
     let manifest = Manifest::from_path(template_path.as_ref().join("Cargo.toml")).unwrap();
     manifest.package.unwrap().name
 }
 
+fn check_wasm_size<P: AsRef<Path>>(wasm_name: &str, wasm_path: P) -> Result<(), Box<dyn Error>> {
+    let size = fs::metadata(wasm_path.as_ref())?.len();
+    let budget = WASM_SIZE_BUDGETS
+        .iter()
+        .find(|(name, _)| *name == wasm_name)
+        .map(|(_, budget)| *budget)
+        .unwrap_or(DEFAULT_WASM_SIZE_BUDGET);
+
+    if size > budget {
+        let message = format!(
+            "The {} wasm is {} bytes, which exceeds its size budget of {} bytes",
+            wasm_name, size, budget
+        );
+        if env::var_os(WASM_STRICT_ENV_VAR).is_some() {
+            return Err(Box::new(io::Error::new(ErrorKind::Other, message)));
+        }
+        println!("cargo:warning={}", message);
+    }
+
+    Ok(())
+}
+
+// e.g. "tariswap_pool" -> "TARI_TEMPLATE_TARISWAP_POOL_WASM"
+fn get_wasm_env_var(wasm_name: &str) -> String {
+    format!("TARI_TEMPLATE_{}_WASM", wasm_name.to_uppercase().replace('-', "_"))
+}
+
 fn get_compiled_wasm_path<P: AsRef<Path>>(template_path: P, wasm_name: &str) -> PathBuf {
     template_path
         .as_ref()
         .join("target")
-        .join("wasm32-unknown-unknown")
+        .join(WASM_TARGET)
         .join("release")
         .join(wasm_name)
         .with_extension("wasm")
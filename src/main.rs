@@ -1,4 +1,10 @@
 fn main() {
     // Empy main just to have a workspace-level build.rs script to compile all template wasms
     // More info: https://github.com/rust-lang/cargo/issues/8732
+    //
+    // The build script also exposes the path of each compiled template as a "TARI_TEMPLATE_<NAME>_WASM" env var,
+    // so the binaries can be embedded without hardcoding the "wasm" folder layout:
+    //     let faucet_wasm = include_bytes!(env!("TARI_TEMPLATE_FAUCET_WASM"));
+    // Cargo only sets these env vars when compiling the targets of this package (e.g. this binary and the
+    // integration tests), other crates must still load the binaries from the "wasm" folder
 }
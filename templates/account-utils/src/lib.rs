@@ -0,0 +1,49 @@
+//   Copyright 2024. The Tari Project
+//
+//   Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//   following conditions are met:
+//
+//   1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//   disclaimer.
+//
+//   2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//   following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//   3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//   products derived from this software without specific prior written permission.
+//
+//   THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//   INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//   DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//   SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//   SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//   WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//   USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+
+//! Helpers shared by the templates that need to check that a component is an account, so that all of them agree on
+//! the account template address
+
+use tari_template_lib::{
+    prelude::{ComponentAddress, ComponentManager, TemplateAddress},
+    Hash,
+};
+
+/// The account template is a builtin template, deployed at the zero address
+pub const ACCOUNT_TEMPLATE_ADDRESS: Hash = Hash::from_array([0u8; 32]);
+
+/// Returns true if the template is the builtin account template
+pub fn is_account_template(template_address: &TemplateAddress) -> bool {
+    *template_address == ACCOUNT_TEMPLATE_ADDRESS
+}
+
+/// Returns true if the component was instantiated from the builtin account template
+pub fn is_account(component_address: ComponentAddress) -> bool {
+    let component = ComponentManager::get(component_address);
+    is_account_template(&component.get_template_address())
+}
+
+/// Panics if the component is not an account, as we need to be able to deposit funds into it
+pub fn assert_component_is_account(component_address: ComponentAddress) {
+    assert!(is_account(component_address), "Invalid account");
+}
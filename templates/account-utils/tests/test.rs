@@ -0,0 +1,17 @@
+//   Copyright 2024 The Tari Project
+//   SPDX-License-Identifier: BSD-3-Clause
+
+use account_utils::is_account_template;
+use tari_template_lib::Hash;
+
+#[test]
+fn it_only_recognizes_the_builtin_account_template() {
+    // the account template is a builtin template, deployed at the zero address
+    assert!(is_account_template(&Hash::from_array([0u8; 32])));
+
+    // any other template, even one that only differs in a single byte, is not an account
+    let mut other_template = [0u8; 32];
+    other_template[31] = 1;
+    assert!(!is_account_template(&Hash::from_array(other_template)));
+    assert!(!is_account_template(&Hash::from_array([0xff; 32])));
+}
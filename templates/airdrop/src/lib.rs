@@ -0,0 +1,89 @@
+//   Copyright 2024. The Tari Project
+//
+//   Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//   following conditions are met:
+//
+//   1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//   disclaimer.
+//
+//   2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//   following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//   3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//   products derived from this software without specific prior written permission.
+//
+//   THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//   INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//   DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//   SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//   SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//   WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//   USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+
+use std::collections::{BTreeMap, BTreeSet};
+use tari_template_lib::prelude::*;
+
+#[template]
+mod airdrop {
+    use super::*;
+
+    /// Distributes tokens to a fixed list of eligible accounts, each account can claim its amount once
+    pub struct Airdrop {
+        vault: Vault,
+        claims: BTreeMap<ComponentAddress, Amount>,
+        claimed: BTreeSet<ComponentAddress>,
+    }
+
+    impl Airdrop {
+        // the bucket must hold enough tokens to pay all the claims
+        pub fn new(token_bucket: Bucket, claims: BTreeMap<ComponentAddress, Amount>) -> Component<Self> {
+            assert!(
+                token_bucket.resource_type() == ResourceType::Fungible,
+                "The airdrop resource must be fungible"
+            );
+            assert!(
+                claims.values().all(|amount| amount.is_positive()),
+                "Invalid claim amount"
+            );
+
+            let total_claims = claims.values().fold(Amount::zero(), |total, amount| total + *amount);
+            assert!(
+                token_bucket.amount() >= total_claims,
+                "Insufficient funds for the claims"
+            );
+
+            Component::new(Self {
+                vault: Vault::from_bucket(token_bucket),
+                claims,
+                claimed: BTreeSet::new(),
+            })
+            .with_access_rules(AccessRules::allow_all())
+            .create()
+        }
+
+        // sends the eligible amount to the account
+        pub fn claim(&mut self, account: ComponentAddress) {
+            let amount = *self
+                .claims
+                .get(&account)
+                .unwrap_or_else(|| panic!("The account {} is not eligible", account));
+            assert!(self.claimed.insert(account), "The account already claimed");
+
+            let tokens = self.vault.withdraw(amount);
+            ComponentManager::get(account).call::<_, ()>("deposit".to_string(), args![tokens]);
+        }
+
+        // amount that the account can still claim
+        pub fn get_claimable(&self, account: ComponentAddress) -> Amount {
+            if self.claimed.contains(&account) {
+                return Amount::zero();
+            }
+            self.claims.get(&account).copied().unwrap_or(Amount::zero())
+        }
+
+        pub fn is_claimed(&self, account: ComponentAddress) -> bool {
+            self.claimed.contains(&account)
+        }
+    }
+}
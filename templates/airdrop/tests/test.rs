@@ -0,0 +1,133 @@
+//   Copyright 2024 The Tari Project
+//   SPDX-License-Identifier: BSD-3-Clause
+
+use std::collections::BTreeMap;
+
+use tari_template_lib::args;
+use tari_template_lib::constants::XTR;
+use tari_template_lib::models::{Amount, ComponentAddress, NonFungibleAddress, ResourceAddress};
+use tari_template_test_tooling::crypto::RistrettoSecretKey;
+use tari_template_test_tooling::support::assert_error::assert_reject_reason;
+use tari_template_test_tooling::TemplateTest;
+use tari_transaction::Transaction;
+
+const CLAIM_AMOUNT: Amount = Amount(100);
+
+#[test]
+fn eligible_account_claims_once() {
+    let AirdropTest {
+        mut test,
+        airdrop_component,
+        eligible,
+        ..
+    } = setup();
+
+    let balance = get_account_balance(&mut test, &eligible, &XTR);
+    test.execute_expect_success(
+        claim_transaction(airdrop_component, &eligible),
+        vec![eligible.owner_token.clone()],
+    );
+    assert_eq!(get_account_balance(&mut test, &eligible, &XTR), balance + CLAIM_AMOUNT);
+
+    let is_claimed: bool = test.call_method(airdrop_component, "is_claimed", args![eligible.component], vec![]);
+    assert!(is_claimed);
+
+    // the second claim is rejected
+    let reason = test.execute_expect_failure(
+        claim_transaction(airdrop_component, &eligible),
+        vec![eligible.owner_token.clone()],
+    );
+    assert_reject_reason(reason, "The account already claimed");
+}
+
+#[test]
+fn it_rejects_ineligible_accounts() {
+    let AirdropTest {
+        mut test,
+        airdrop_component,
+        ..
+    } = setup();
+
+    let ineligible = create_account(&mut test);
+    let reason = test.execute_expect_failure(
+        claim_transaction(airdrop_component, &ineligible),
+        vec![ineligible.owner_token.clone()],
+    );
+    assert_reject_reason(reason, "is not eligible");
+
+    let claimable: Amount =
+        test.call_method(airdrop_component, "get_claimable", args![ineligible.component], vec![]);
+    assert_eq!(claimable, Amount(0));
+}
+
+#[derive(Clone, Debug)]
+struct Account {
+    pub component: ComponentAddress,
+    pub owner_token: NonFungibleAddress,
+    pub key: RistrettoSecretKey,
+}
+
+struct AirdropTest {
+    test: TemplateTest,
+    airdrop_component: ComponentAddress,
+    eligible: Account,
+}
+
+fn setup() -> AirdropTest {
+    let mut test = TemplateTest::new(["."]);
+    let airdrop_template = test.get_template_address("Airdrop");
+
+    let creator = create_account(&mut test);
+    let eligible = create_account(&mut test);
+
+    let mut claims = BTreeMap::new();
+    claims.insert(eligible.component, CLAIM_AMOUNT);
+
+    let result = test.execute_expect_success(
+        Transaction::builder()
+            .call_method(creator.component, "withdraw", args![XTR, CLAIM_AMOUNT])
+            .put_last_instruction_output_on_workspace("tokens")
+            .call_function(airdrop_template, "new", args![Workspace("tokens"), claims])
+            .sign(&creator.key)
+            .build(),
+        vec![creator.owner_token.clone()],
+    );
+    let airdrop_component = result.finalize.execution_results[2]
+        .decode::<ComponentAddress>()
+        .unwrap();
+
+    AirdropTest {
+        test,
+        airdrop_component,
+        eligible,
+    }
+}
+
+fn create_account(test: &mut TemplateTest) -> Account {
+    let (component, owner_token, key) = test.create_funded_account();
+    Account {
+        component,
+        owner_token,
+        key,
+    }
+}
+
+fn get_account_balance(test: &mut TemplateTest, account: &Account, resource: &ResourceAddress) -> Amount {
+    let result = test.execute_expect_success(
+        Transaction::builder()
+            .call_method(account.component, "balance", args![resource])
+            .sign(&account.key)
+            .build(),
+        vec![account.owner_token.clone()],
+    );
+    result.finalize.execution_results[0]
+        .decode::<Amount>()
+        .unwrap()
+}
+
+fn claim_transaction(airdrop_component: ComponentAddress, account: &Account) -> Transaction {
+    Transaction::builder()
+        .call_method(airdrop_component, "claim", args![account.component])
+        .sign(&account.key)
+        .build()
+}
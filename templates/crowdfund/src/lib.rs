@@ -0,0 +1,140 @@
+//   Copyright 2024. The Tari Project
+//
+//   Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//   following conditions are met:
+//
+//   1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//   disclaimer.
+//
+//   2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//   following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//   3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//   products derived from this software without specific prior written permission.
+//
+//   THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//   INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//   DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//   SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//   SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//   WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//   USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::collections::BTreeMap;
+use account_utils::assert_component_is_account;
+use tari_template_lib::prelude::*;
+
+#[template]
+mod crowdfund {
+    use super::*;
+
+    /// All-or-nothing crowdfunding campaign
+    /// Pledges are locked until the deadline. If the goal is met, the beneficiary gets all the funds, otherwise each
+    /// pledger can get a refund of their pledge.
+    pub struct Crowdfund {
+        // minimum amount of pledged tokens for the campaign to succeed
+        goal: Amount,
+
+        // epoch from which no more pledges are accepted and the campaign can be settled
+        ending_epoch: u64,
+
+        // address of the account component that will receive the funds
+        beneficiary: ComponentAddress,
+
+        // holds all the pledged tokens until the campaign is settled
+        vault: Vault,
+
+        // pledged amount of each account, used for refunds
+        pledges: BTreeMap<ComponentAddress, Amount>,
+
+        // set when the beneficiary has received the funds
+        claimed: bool,
+    }
+
+    impl Crowdfund {
+        pub fn new(goal: Amount, deadline_epochs: u64, beneficiary: ComponentAddress) -> Component<Self> {
+            assert!(goal.is_positive(), "Invalid goal");
+            assert!(deadline_epochs > 0, "Invalid deadline");
+
+            // needed to ensure that we can send the funds when the campaign ends
+            assert_component_is_account(beneficiary);
+
+            Component::new(Self {
+                goal,
+                ending_epoch: Consensus::current_epoch() + deadline_epochs,
+                beneficiary,
+                vault: Vault::new_empty(XTR),
+                pledges: BTreeMap::new(),
+                claimed: false,
+            })
+            .with_access_rules(AccessRules::allow_all())
+            .create()
+        }
+
+        pub fn pledge(&mut self, account: ComponentAddress, payment: Bucket) {
+            assert!(
+                Consensus::current_epoch() < self.ending_epoch,
+                "Campaign has ended"
+            );
+
+            assert_eq!(
+                payment.resource_address(),
+                XTR,
+                "Invalid payment resource, the campaign only accepts Tari (XTR) tokens"
+            );
+            assert!(payment.amount().is_positive(), "Invalid pledge amount");
+
+            // validate that the pledger is really an account, so we can deposit the refund later
+            assert_component_is_account(account);
+
+            let pledge = self.pledges.entry(account).or_insert(Amount::zero());
+            *pledge = *pledge + payment.amount();
+            self.vault.deposit(payment);
+        }
+
+        // sends all the pledged funds to the beneficiary, only if the goal was met
+        pub fn claim(&mut self) {
+            self.assert_has_ended();
+            assert!(self.is_goal_met(), "The goal was not met");
+            assert!(!self.claimed, "The funds were already claimed");
+
+            let funds = self.vault.withdraw_all();
+            ComponentManager::get(self.beneficiary).call::<_, ()>("deposit".to_string(), args![funds]);
+
+            self.claimed = true;
+        }
+
+        // returns the pledge to the account, only if the goal was not met
+        pub fn refund(&mut self, account: ComponentAddress) {
+            self.assert_has_ended();
+            assert!(!self.is_goal_met(), "The goal was met, no refunds are allowed");
+
+            let pledge = self
+                .pledges
+                .remove(&account)
+                .unwrap_or_else(|| panic!("There is no pledge for account {}", account));
+            let refund_bucket = self.vault.withdraw(pledge);
+            ComponentManager::get(account).call::<_, ()>("deposit".to_string(), args![refund_bucket]);
+        }
+
+        pub fn get_total_pledged(&self) -> Amount {
+            self.vault.balance()
+        }
+
+        pub fn get_pledge(&self, account: ComponentAddress) -> Amount {
+            self.pledges.get(&account).copied().unwrap_or(Amount::zero())
+        }
+
+        pub fn is_goal_met(&self) -> bool {
+            // the vault is emptied when the funds are claimed
+            self.claimed || self.vault.balance() >= self.goal
+        }
+
+        fn assert_has_ended(&self) {
+            assert!(
+                Consensus::current_epoch() >= self.ending_epoch,
+                "Campaign is still in progress"
+            );
+        }
+    }
+}
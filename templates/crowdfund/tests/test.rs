@@ -0,0 +1,207 @@
+//   Copyright 2024 The Tari Project
+//   SPDX-License-Identifier: BSD-3-Clause
+
+use tari_engine_types::virtual_substate::{VirtualSubstate, VirtualSubstateId};
+use tari_template_lib::args;
+use tari_template_lib::constants::XTR;
+use tari_template_lib::models::{Amount, ComponentAddress, NonFungibleAddress};
+use tari_template_test_tooling::crypto::RistrettoSecretKey;
+use tari_template_test_tooling::support::assert_error::assert_reject_reason;
+use tari_template_test_tooling::TemplateTest;
+use tari_transaction::Transaction;
+
+const DEADLINE_EPOCHS: u64 = 10;
+
+#[test]
+fn successful_campaign_pays_the_beneficiary() {
+    let goal = Amount(300);
+    let CrowdfundTest {
+        mut test,
+        crowdfund_component,
+        beneficiary,
+    } = setup(goal);
+
+    let beneficiary_balance = get_account_tari_balance(&mut test, &beneficiary);
+
+    // two pledgers reach the goal together
+    let pledger1 = create_account(&mut test);
+    let pledger2 = create_account(&mut test);
+    pledge(&mut test, crowdfund_component, &pledger1, Amount(100));
+    pledge(&mut test, crowdfund_component, &pledger2, Amount(200));
+
+    let total_pledged: Amount = test.call_method(crowdfund_component, "get_total_pledged", args![], vec![]);
+    assert_eq!(total_pledged, goal);
+
+    // the funds cannot be claimed before the deadline
+    let reason = test.execute_expect_failure(claim_transaction(crowdfund_component, &pledger1), vec![]);
+    assert_reject_reason(reason, "Campaign is still in progress");
+
+    // after the deadline anybody can send the funds to the beneficiary
+    set_epoch(&mut test, DEADLINE_EPOCHS);
+    test.execute_expect_success(claim_transaction(crowdfund_component, &pledger1), vec![]);
+
+    let beneficiary_balance_after_claim = get_account_tari_balance(&mut test, &beneficiary);
+    assert_eq!(beneficiary_balance_after_claim, beneficiary_balance + goal);
+
+    // no refunds are allowed on a successful campaign
+    let reason = test.execute_expect_failure(
+        refund_transaction(crowdfund_component, &pledger1),
+        vec![],
+    );
+    assert_reject_reason(reason, "The goal was met, no refunds are allowed");
+}
+
+#[test]
+fn failed_campaign_refunds_the_pledgers() {
+    let goal = Amount(1000);
+    let CrowdfundTest {
+        mut test,
+        crowdfund_component,
+        ..
+    } = setup(goal);
+
+    let pledger = create_account(&mut test);
+    let pledger_balance = get_account_tari_balance(&mut test, &pledger);
+    pledge(&mut test, crowdfund_component, &pledger, Amount(100));
+    pledge(&mut test, crowdfund_component, &pledger, Amount(50));
+
+    // pledges of the same account are accumulated
+    let pledged: Amount = test.call_method(crowdfund_component, "get_pledge", args![pledger.component], vec![]);
+    assert_eq!(pledged, Amount(150));
+
+    // the goal is not reached by the deadline, so the funds cannot be claimed
+    set_epoch(&mut test, DEADLINE_EPOCHS);
+    let reason = test.execute_expect_failure(claim_transaction(crowdfund_component, &pledger), vec![]);
+    assert_reject_reason(reason, "The goal was not met");
+
+    // the pledger gets the full pledge back
+    test.execute_expect_success(refund_transaction(crowdfund_component, &pledger), vec![]);
+    let pledger_balance_after_refund = get_account_tari_balance(&mut test, &pledger);
+    assert_eq!(pledger_balance_after_refund, pledger_balance);
+
+    // the pledge can only be refunded once
+    let reason = test.execute_expect_failure(refund_transaction(crowdfund_component, &pledger), vec![]);
+    assert_reject_reason(reason, "There is no pledge for account");
+}
+
+#[test]
+fn it_rejects_pledges_after_the_deadline() {
+    let CrowdfundTest {
+        mut test,
+        crowdfund_component,
+        ..
+    } = setup(Amount(1000));
+
+    let pledger = create_account(&mut test);
+    set_epoch(&mut test, DEADLINE_EPOCHS);
+
+    let reason = test.execute_expect_failure(
+        pledge_transaction(crowdfund_component, &pledger, Amount(100)),
+        vec![pledger.owner_token.clone()],
+    );
+    assert_reject_reason(reason, "Campaign has ended");
+}
+
+#[derive(Clone, Debug)]
+struct Account {
+    pub component: ComponentAddress,
+    pub owner_token: NonFungibleAddress,
+    pub key: RistrettoSecretKey,
+}
+
+struct CrowdfundTest {
+    test: TemplateTest,
+    crowdfund_component: ComponentAddress,
+    beneficiary: Account,
+}
+
+fn setup(goal: Amount) -> CrowdfundTest {
+    let mut test = TemplateTest::new(["."]);
+    let crowdfund_template = test.get_template_address("Crowdfund");
+
+    let beneficiary = create_account(&mut test);
+
+    let result = test.execute_expect_success(
+        Transaction::builder()
+            .call_function(
+                crowdfund_template,
+                "new",
+                args![goal, DEADLINE_EPOCHS, beneficiary.component],
+            )
+            .sign(&beneficiary.key)
+            .build(),
+        vec![beneficiary.owner_token.clone()],
+    );
+    let crowdfund_component = result.finalize.execution_results[0]
+        .decode::<ComponentAddress>()
+        .unwrap();
+
+    CrowdfundTest {
+        test,
+        crowdfund_component,
+        beneficiary,
+    }
+}
+
+fn create_account(test: &mut TemplateTest) -> Account {
+    let (component, owner_token, key) = test.create_funded_account();
+    Account {
+        component,
+        owner_token,
+        key,
+    }
+}
+
+fn get_account_tari_balance(test: &mut TemplateTest, account: &Account) -> Amount {
+    let result = test.execute_expect_success(
+        Transaction::builder()
+            .call_method(account.component, "balance", args![XTR])
+            .sign(&account.key)
+            .build(),
+        vec![account.owner_token.clone()],
+    );
+    result.finalize.execution_results[0]
+        .decode::<Amount>()
+        .unwrap()
+}
+
+fn set_epoch(test: &mut TemplateTest, new_epoch: u64) {
+    test.set_virtual_substate(
+        VirtualSubstateId::CurrentEpoch,
+        VirtualSubstate::CurrentEpoch(new_epoch),
+    );
+}
+
+fn pledge_transaction(crowdfund_component: ComponentAddress, pledger: &Account, amount: Amount) -> Transaction {
+    Transaction::builder()
+        .call_method(pledger.component, "withdraw", args![XTR, amount])
+        .put_last_instruction_output_on_workspace("payment")
+        .call_method(
+            crowdfund_component,
+            "pledge",
+            args![pledger.component, Workspace("payment")],
+        )
+        .sign(&pledger.key)
+        .build()
+}
+
+fn pledge(test: &mut TemplateTest, crowdfund_component: ComponentAddress, pledger: &Account, amount: Amount) {
+    test.execute_expect_success(
+        pledge_transaction(crowdfund_component, pledger, amount),
+        vec![pledger.owner_token.clone()],
+    );
+}
+
+fn claim_transaction(crowdfund_component: ComponentAddress, signer: &Account) -> Transaction {
+    Transaction::builder()
+        .call_method(crowdfund_component, "claim", args![])
+        .sign(&signer.key)
+        .build()
+}
+
+fn refund_transaction(crowdfund_component: ComponentAddress, pledger: &Account) -> Transaction {
+    Transaction::builder()
+        .call_method(crowdfund_component, "refund", args![pledger.component])
+        .sign(&pledger.key)
+        .build()
+}
@@ -0,0 +1,146 @@
+//   Copyright 2024. The Tari Project
+//
+//   Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//   following conditions are met:
+//
+//   1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//   disclaimer.
+//
+//   2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//   following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//   3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//   products derived from this software without specific prior written permission.
+//
+//   THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//   INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//   DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//   SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//   SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//   WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//   USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use account_utils::assert_component_is_account;
+use tari_template_lib::prelude::*;
+
+#[template]
+mod escrow {
+    use super::*;
+
+    /// Escrow of a payment between a buyer and a seller, with a neutral arbiter to resolve disputes
+    /// Each party receives a badge that allows them to act on the escrow:
+    ///     - The buyer or the arbiter can release the payment to the seller
+    ///     - The seller or the arbiter can refund the payment to the buyer
+    pub struct Escrow {
+        buyer: ComponentAddress,
+        seller: ComponentAddress,
+
+        // holds the buyer's payment until the escrow is settled
+        vault: Vault,
+
+        // badge resources of each party
+        buyer_badge_resource: ResourceAddress,
+        seller_badge_resource: ResourceAddress,
+        arbiter_badge_resource: ResourceAddress,
+
+        // set when the payment is released or refunded, no further actions are allowed afterwards
+        settled: bool,
+    }
+
+    impl Escrow {
+        // the badges of each party are deposited directly in their accounts
+        pub fn new(
+            buyer: ComponentAddress,
+            seller: ComponentAddress,
+            arbiter: ComponentAddress,
+            payment: Bucket,
+        ) -> Component<Self> {
+            assert_eq!(
+                payment.resource_address(),
+                XTR,
+                "Invalid payment resource, the escrow only accepts Tari (XTR) tokens"
+            );
+            assert!(payment.amount().is_positive(), "Invalid payment amount");
+
+            // needed to ensure that we can deposit the badges and the payment
+            assert_component_is_account(buyer);
+            assert_component_is_account(seller);
+            assert_component_is_account(arbiter);
+
+            let buyer_badge_resource = Self::issue_badge(buyer);
+            let seller_badge_resource = Self::issue_badge(seller);
+            let arbiter_badge_resource = Self::issue_badge(arbiter);
+
+            Component::new(Self {
+                buyer,
+                seller,
+                vault: Vault::from_bucket(payment),
+                buyer_badge_resource,
+                seller_badge_resource,
+                arbiter_badge_resource,
+                settled: false,
+            })
+            .with_access_rules(AccessRules::allow_all())
+            .create()
+        }
+
+        // pays the seller, callable by the buyer or the arbiter
+        // returns the badge back to the caller
+        pub fn release(&mut self, badge: Bucket) -> Bucket {
+            let badge_resource = badge.resource_address();
+            assert!(
+                badge_resource == self.buyer_badge_resource || badge_resource == self.arbiter_badge_resource,
+                "Only the buyer or the arbiter can release the payment"
+            );
+            self.settle(self.seller);
+            badge
+        }
+
+        // refunds the buyer, callable by the seller or the arbiter
+        // returns the badge back to the caller
+        pub fn refund(&mut self, badge: Bucket) -> Bucket {
+            let badge_resource = badge.resource_address();
+            assert!(
+                badge_resource == self.seller_badge_resource || badge_resource == self.arbiter_badge_resource,
+                "Only the seller or the arbiter can refund the payment"
+            );
+            self.settle(self.buyer);
+            badge
+        }
+
+        // convenience method for external APIs and interfaces, in (buyer, seller, arbiter) order
+        pub fn get_badge_resources(&self) -> (ResourceAddress, ResourceAddress, ResourceAddress) {
+            (
+                self.buyer_badge_resource,
+                self.seller_badge_resource,
+                self.arbiter_badge_resource,
+            )
+        }
+
+        pub fn is_settled(&self) -> bool {
+            self.settled
+        }
+
+        // this method MUST ALWAYS be private, to prevent payments by unauthorized third parties
+        fn settle(&mut self, recipient: ComponentAddress) {
+            assert!(!self.settled, "The escrow is already settled");
+
+            let payment = self.vault.withdraw_all();
+            ComponentManager::get(recipient).call::<_, ()>("deposit".to_string(), args![payment]);
+
+            self.settled = true;
+        }
+
+        // we make sure that only the initial badge will be minted
+        fn issue_badge(account: ComponentAddress) -> ResourceAddress {
+            let badge_bucket = ResourceBuilder::non_fungible()
+                .mintable(AccessRule::DenyAll)
+                .initial_supply_with_data(Some((NonFungibleId::random(), (&(), &()))));
+            let badge_resource = badge_bucket.resource_address();
+
+            ComponentManager::get(account).call::<_, ()>("deposit".to_string(), args![badge_bucket]);
+
+            badge_resource
+        }
+    }
+}
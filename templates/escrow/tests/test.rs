@@ -0,0 +1,235 @@
+//   Copyright 2024 The Tari Project
+//   SPDX-License-Identifier: BSD-3-Clause
+
+use tari_template_lib::args;
+use tari_template_lib::constants::XTR;
+use tari_template_lib::models::{Amount, ComponentAddress, NonFungibleAddress, ResourceAddress};
+use tari_template_test_tooling::crypto::RistrettoSecretKey;
+use tari_template_test_tooling::support::assert_error::assert_reject_reason;
+use tari_template_test_tooling::TemplateTest;
+use tari_transaction::Transaction;
+
+const PAYMENT: Amount = Amount(500);
+
+#[test]
+fn buyer_releases_the_payment() {
+    let EscrowTest {
+        mut test,
+        escrow_component,
+        buyer,
+        seller,
+        ..
+    } = setup();
+
+    let seller_balance = get_account_tari_balance(&mut test, &seller);
+
+    let (buyer_badge, _, _) = get_badge_resources(&mut test, escrow_component);
+    test.execute_expect_success(
+        settle_transaction(escrow_component, &buyer, buyer_badge, "release"),
+        vec![buyer.owner_token.clone()],
+    );
+
+    let seller_balance_after_release = get_account_tari_balance(&mut test, &seller);
+    assert_eq!(seller_balance_after_release, seller_balance + PAYMENT);
+
+    // the escrow cannot be settled twice
+    let reason = test.execute_expect_failure(
+        settle_transaction(escrow_component, &buyer, buyer_badge, "release"),
+        vec![buyer.owner_token.clone()],
+    );
+    assert_reject_reason(reason, "The escrow is already settled");
+}
+
+#[test]
+fn arbiter_forces_a_refund() {
+    let EscrowTest {
+        mut test,
+        escrow_component,
+        buyer,
+        arbiter,
+        ..
+    } = setup();
+
+    let buyer_balance = get_account_tari_balance(&mut test, &buyer);
+
+    let (_, _, arbiter_badge) = get_badge_resources(&mut test, escrow_component);
+    test.execute_expect_success(
+        settle_transaction(escrow_component, &arbiter, arbiter_badge, "refund"),
+        vec![arbiter.owner_token.clone()],
+    );
+
+    let buyer_balance_after_refund = get_account_tari_balance(&mut test, &buyer);
+    assert_eq!(buyer_balance_after_refund, buyer_balance + PAYMENT);
+
+    let settled: bool = test.call_method(escrow_component, "is_settled", args![], vec![]);
+    assert!(settled);
+}
+
+#[test]
+fn it_rejects_unauthorized_parties() {
+    let EscrowTest {
+        mut test,
+        escrow_component,
+        buyer,
+        seller,
+        ..
+    } = setup();
+
+    let (buyer_badge, seller_badge, _) = get_badge_resources(&mut test, escrow_component);
+
+    // the seller cannot release the payment to itself
+    let reason = test.execute_expect_failure(
+        settle_transaction(escrow_component, &seller, seller_badge, "release"),
+        vec![seller.owner_token.clone()],
+    );
+    assert_reject_reason(reason, "Only the buyer or the arbiter can release the payment");
+
+    // the buyer cannot refund the payment to itself
+    let reason = test.execute_expect_failure(
+        settle_transaction(escrow_component, &buyer, buyer_badge, "refund"),
+        vec![buyer.owner_token.clone()],
+    );
+    assert_reject_reason(reason, "Only the seller or the arbiter can refund the payment");
+
+    // a third party without a badge of the escrow is rejected
+    let third_party = create_account(&mut test);
+    let fake_badge = create_fake_badge(&mut test, &third_party);
+    let reason = test.execute_expect_failure(
+        settle_transaction(escrow_component, &third_party, fake_badge, "release"),
+        vec![third_party.owner_token.clone()],
+    );
+    assert_reject_reason(reason, "Only the buyer or the arbiter can release the payment");
+}
+
+#[derive(Clone, Debug)]
+struct Account {
+    pub component: ComponentAddress,
+    pub owner_token: NonFungibleAddress,
+    pub key: RistrettoSecretKey,
+}
+
+struct EscrowTest {
+    test: TemplateTest,
+    escrow_component: ComponentAddress,
+    buyer: Account,
+    seller: Account,
+    arbiter: Account,
+}
+
+fn setup() -> EscrowTest {
+    let mut test = TemplateTest::new(["."]);
+    let escrow_template = test.get_template_address("Escrow");
+
+    let buyer = create_account(&mut test);
+    let seller = create_account(&mut test);
+    let arbiter = create_account(&mut test);
+
+    // the buyer locks the payment in the escrow
+    let result = test.execute_expect_success(
+        Transaction::builder()
+            .call_method(buyer.component, "withdraw", args![XTR, PAYMENT])
+            .put_last_instruction_output_on_workspace("payment")
+            .call_function(
+                escrow_template,
+                "new",
+                args![buyer.component, seller.component, arbiter.component, Workspace("payment")],
+            )
+            .sign(&buyer.key)
+            .build(),
+        vec![buyer.owner_token.clone()],
+    );
+    let (substate_addr, _) = result
+        .expect_success()
+        .up_iter()
+        .find(|(address, substate)| {
+            address.is_component() && substate.substate_value().component().unwrap().module_name == "Escrow"
+        })
+        .unwrap();
+    let escrow_component = substate_addr.as_component_address().unwrap();
+
+    EscrowTest {
+        test,
+        escrow_component,
+        buyer,
+        seller,
+        arbiter,
+    }
+}
+
+fn create_account(test: &mut TemplateTest) -> Account {
+    let (component, owner_token, key) = test.create_funded_account();
+    Account {
+        component,
+        owner_token,
+        key,
+    }
+}
+
+// creates a badge-like NFT that is not related to the escrow
+fn create_fake_badge(test: &mut TemplateTest, account: &Account) -> ResourceAddress {
+    let escrow_template = test.get_template_address("Escrow");
+    let other_party = create_account(test);
+
+    // a badge issued by another escrow is not valid for this one
+    let result = test.execute_expect_success(
+        Transaction::builder()
+            .call_method(account.component, "withdraw", args![XTR, Amount(1)])
+            .put_last_instruction_output_on_workspace("payment")
+            .call_function(
+                escrow_template,
+                "new",
+                args![account.component, other_party.component, account.component, Workspace("payment")],
+            )
+            .sign(&account.key)
+            .build(),
+        vec![account.owner_token.clone()],
+    );
+    let (substate_addr, _) = result
+        .expect_success()
+        .up_iter()
+        .find(|(address, substate)| {
+            address.is_component() && substate.substate_value().component().unwrap().module_name == "Escrow"
+        })
+        .unwrap();
+    let other_escrow = substate_addr.as_component_address().unwrap();
+
+    let (buyer_badge, _, _) = get_badge_resources(test, other_escrow);
+    buyer_badge
+}
+
+fn get_account_tari_balance(test: &mut TemplateTest, account: &Account) -> Amount {
+    let result = test.execute_expect_success(
+        Transaction::builder()
+            .call_method(account.component, "balance", args![XTR])
+            .sign(&account.key)
+            .build(),
+        vec![account.owner_token.clone()],
+    );
+    result.finalize.execution_results[0]
+        .decode::<Amount>()
+        .unwrap()
+}
+
+fn get_badge_resources(
+    test: &mut TemplateTest,
+    escrow_component: ComponentAddress,
+) -> (ResourceAddress, ResourceAddress, ResourceAddress) {
+    test.call_method(escrow_component, "get_badge_resources", args![], vec![])
+}
+
+// calls "release" or "refund" with the badge of the account
+fn settle_transaction(
+    escrow_component: ComponentAddress,
+    account: &Account,
+    badge_resource: ResourceAddress,
+    method: &str,
+) -> Transaction {
+    Transaction::builder()
+        .call_method(account.component, "withdraw", args![badge_resource, Amount(1)])
+        .put_last_instruction_output_on_workspace("badge")
+        .call_method(escrow_component, method, args![Workspace("badge")])
+        .put_last_instruction_output_on_workspace("badge")
+        .call_method(account.component, "deposit", args![Workspace("badge")])
+        .sign(&account.key)
+        .build()
+}
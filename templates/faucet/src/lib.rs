@@ -20,30 +20,102 @@
 //   WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //   USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use std::collections::BTreeMap;
 use tari_template_lib::prelude::*;
 
+// amount of coins handed out by "take_free_coins"
+const FREE_COINS_AMOUNT: i64 = 1000;
+
+// same scale as Tari (XTR) amounts
+const DEFAULT_DECIMALS: u8 = 6;
+
 #[template]
 mod faucet_template {
     use super::*;
 
     pub struct TestFaucet {
         vault: Vault,
+        // minimum amount of epochs between two limited withdrawals of the same account
+        cooldown_epochs: u64,
+        // epoch of the last limited withdrawal of each account
+        last_withdrawals: BTreeMap<ComponentAddress, u64>,
+        // maximum amount of coins that can be requested in "take_coins"
+        max_per_withdrawal: Amount,
+        // amounts are in the smallest unit of the token, e.g. 1 token is 10^decimals units
+        decimals: u8,
     }
 
     impl TestFaucet {
         pub fn mint(initial_supply: Amount) -> Self {
-            let coins = ResourceBuilder::fungible()
-                .with_token_symbol("🪙")
-                .initial_supply(initial_supply);
+            Self::mint_with_limits(initial_supply, 0, Amount::new(FREE_COINS_AMOUNT))
+        }
 
-            Self {
-                vault: Vault::from_bucket(coins),
-            }
+        pub fn mint_with_limits(initial_supply: Amount, cooldown_epochs: u64, max_per_withdrawal: Amount) -> Self {
+            Self::create("🪙".to_string(), DEFAULT_DECIMALS, initial_supply, cooldown_epochs, max_per_withdrawal)
+        }
+
+        // the decimals are stored in the resource metadata, so price math can account for tokens of different scales
+        pub fn mint_with_symbol(initial_supply: Amount, symbol: String, decimals: u8) -> Self {
+            Self::create(symbol, decimals, initial_supply, 0, Amount::new(FREE_COINS_AMOUNT))
         }
 
         pub fn take_free_coins(&mut self) -> Bucket {
-            debug!("Withdrawing 1000 coins from faucet");
-            self.vault.withdraw(Amount(1000))
+            debug!("Withdrawing {} coins from faucet", FREE_COINS_AMOUNT);
+            self.vault.withdraw(Amount::new(FREE_COINS_AMOUNT))
+        }
+
+        // returns exactly "amount" coins, for tests that need precise quantities
+        pub fn take_coins(&mut self, amount: Amount) -> Bucket {
+            assert!(
+                amount <= self.max_per_withdrawal,
+                "The amount exceeds the maximum of {} coins per withdrawal",
+                self.max_per_withdrawal
+            );
+
+            self.take_exact(amount)
+        }
+
+        // same as "take_coins" but without the withdrawal limit, so tests can fund accounts with any known amount
+        pub fn take_exact(&mut self, amount: Amount) -> Bucket {
+            assert!(amount.is_positive(), "Invalid amount");
+            assert!(amount <= self.vault.balance(), "Insufficient coins in the faucet");
+
+            self.vault.withdraw(amount)
+        }
+
+        // same as "take_free_coins", but each account can only withdraw once per cooldown period
+        // the account address is supplied by the caller without any proof of ownership, so the limit is only advisory:
+        // it throttles well-behaved clients, but anyone can bypass it by passing a different address
+        pub fn take_free_coins_with_advisory_limit(&mut self, account: ComponentAddress) -> Bucket {
+            let current_epoch = Consensus::current_epoch();
+            if let Some(last_epoch) = self.last_withdrawals.get(&account) {
+                assert!(
+                    current_epoch >= last_epoch + self.cooldown_epochs,
+                    "The account must wait until epoch {} to withdraw again",
+                    last_epoch + self.cooldown_epochs
+                );
+            }
+            self.last_withdrawals.insert(account, current_epoch);
+
+            self.take_free_coins()
+        }
+
+        // tops up the faucet, so long-running tests do not exhaust it
+        pub fn refill(&mut self, bucket: Bucket) {
+            assert!(
+                bucket.resource_address() == self.vault.resource_address(),
+                "Invalid refill resource {}",
+                bucket.resource_address()
+            );
+            self.vault.deposit(bucket);
+        }
+
+        pub fn get_balance(&self) -> Amount {
+            self.vault.balance()
+        }
+
+        pub fn get_decimals(&self) -> u8 {
+            self.decimals
         }
 
         // TODO: we can make a fungible utility template with these common operations
@@ -55,5 +127,26 @@ mod faucet_template {
         pub fn total_supply(&self) -> Amount {
             ResourceManager::get(self.vault.resource_address()).total_supply()
         }
+
+        fn create(
+            symbol: String,
+            decimals: u8,
+            initial_supply: Amount,
+            cooldown_epochs: u64,
+            max_per_withdrawal: Amount,
+        ) -> Self {
+            let coins = ResourceBuilder::fungible()
+                .with_token_symbol(symbol)
+                .add_metadata("decimals", decimals.to_string())
+                .initial_supply(initial_supply);
+
+            Self {
+                vault: Vault::from_bucket(coins),
+                cooldown_epochs,
+                last_withdrawals: BTreeMap::new(),
+                max_per_withdrawal,
+                decimals,
+            }
+        }
     }
 }
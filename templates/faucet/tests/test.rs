@@ -0,0 +1,251 @@
+//   Copyright 2024 The Tari Project
+//   SPDX-License-Identifier: BSD-3-Clause
+
+use tari_engine_types::virtual_substate::{VirtualSubstate, VirtualSubstateId};
+use tari_template_lib::{
+    args,
+    constants::XTR,
+    models::{Amount, ComponentAddress, NonFungibleAddress, ResourceAddress},
+};
+use tari_template_test_tooling::crypto::RistrettoSecretKey;
+use tari_template_test_tooling::support::assert_error::assert_reject_reason;
+use tari_template_test_tooling::{SubstateType, TemplateTest};
+use tari_transaction::Transaction;
+
+struct FaucetTest {
+    template_test: TemplateTest,
+    faucet_component: ComponentAddress,
+    faucet_resource: ResourceAddress,
+    account_address: ComponentAddress,
+    account_proof: NonFungibleAddress,
+    account_key: RistrettoSecretKey,
+}
+
+const INITIAL_SUPPLY: i64 = 1_000_000;
+
+fn setup(cooldown_epochs: u64, max_per_withdrawal: Amount) -> FaucetTest {
+    let mut template_test = TemplateTest::new(["."]);
+
+    let faucet_component: ComponentAddress = template_test.call_function(
+        "TestFaucet",
+        "mint_with_limits",
+        args![Amount(INITIAL_SUPPLY), cooldown_epochs, max_per_withdrawal],
+        vec![],
+    );
+    let faucet_resource = template_test
+        .get_previous_output_address(SubstateType::Resource)
+        .as_resource_address()
+        .unwrap();
+
+    let (account_address, account_proof, account_key) = template_test.create_funded_account();
+
+    FaucetTest {
+        template_test,
+        faucet_component,
+        faucet_resource,
+        account_address,
+        account_proof,
+        account_key,
+    }
+}
+
+fn take_free_coins_transaction(test: &FaucetTest) -> Transaction {
+    Transaction::builder()
+        .call_method(test.faucet_component, "take_free_coins", args![])
+        .put_last_instruction_output_on_workspace("coins")
+        .call_method(test.account_address, "deposit", args![Workspace("coins")])
+        .sign(&test.account_key)
+        .build()
+}
+
+fn take_free_coins_with_advisory_limit_transaction(test: &FaucetTest) -> Transaction {
+    Transaction::builder()
+        .call_method(test.faucet_component, "take_free_coins_with_advisory_limit", args![test.account_address])
+        .put_last_instruction_output_on_workspace("coins")
+        .call_method(test.account_address, "deposit", args![Workspace("coins")])
+        .sign(&test.account_key)
+        .build()
+}
+
+fn take_coins_transaction(test: &FaucetTest, amount: Amount) -> Transaction {
+    Transaction::builder()
+        .call_method(test.faucet_component, "take_coins", args![amount])
+        .put_last_instruction_output_on_workspace("coins")
+        .call_method(test.account_address, "deposit", args![Workspace("coins")])
+        .sign(&test.account_key)
+        .build()
+}
+
+fn take_exact_transaction(test: &FaucetTest, amount: Amount) -> Transaction {
+    Transaction::builder()
+        .call_method(test.faucet_component, "take_exact", args![amount])
+        .put_last_instruction_output_on_workspace("coins")
+        .call_method(test.account_address, "deposit", args![Workspace("coins")])
+        .sign(&test.account_key)
+        .build()
+}
+
+fn get_account_balance(test: &mut FaucetTest) -> Amount {
+    test.template_test.call_method(
+        test.account_address,
+        "balance",
+        args![test.faucet_resource],
+        vec![test.account_proof.clone()],
+    )
+}
+
+fn refill_transaction(test: &FaucetTest, resource: ResourceAddress, amount: Amount) -> Transaction {
+    Transaction::builder()
+        .call_method(test.account_address, "withdraw", args![resource, amount])
+        .put_last_instruction_output_on_workspace("refill")
+        .call_method(test.faucet_component, "refill", args![Workspace("refill")])
+        .sign(&test.account_key)
+        .build()
+}
+
+fn get_faucet_balance(test: &mut FaucetTest) -> Amount {
+    test.template_test
+        .call_method(test.faucet_component, "get_balance", args![], vec![])
+}
+
+fn set_epoch(test: &mut FaucetTest, new_epoch: u64) {
+    test.template_test.set_virtual_substate(
+        VirtualSubstateId::CurrentEpoch,
+        VirtualSubstate::CurrentEpoch(new_epoch),
+    );
+}
+
+#[test]
+fn it_rate_limits_withdrawals_per_account() {
+    let cooldown_epochs = 10;
+    let mut test = setup(cooldown_epochs, Amount(1000));
+
+    // the first withdrawal always succeeds
+    set_epoch(&mut test, 1);
+    let transaction = take_free_coins_with_advisory_limit_transaction(&test);
+    test.template_test
+        .execute_expect_success(transaction, vec![test.account_proof.clone()]);
+
+    // a second withdrawal inside the cooldown period is rejected
+    set_epoch(&mut test, 5);
+    let transaction = take_free_coins_with_advisory_limit_transaction(&test);
+    let reason = test
+        .template_test
+        .execute_expect_failure(transaction, vec![test.account_proof.clone()]);
+    assert_reject_reason(reason, "The account must wait until epoch 11 to withdraw again");
+
+    // after the cooldown the account can withdraw again
+    set_epoch(&mut test, 11);
+    let transaction = take_free_coins_with_advisory_limit_transaction(&test);
+    test.template_test
+        .execute_expect_success(transaction, vec![test.account_proof.clone()]);
+}
+
+#[test]
+fn it_withdraws_exact_amounts() {
+    let max_per_withdrawal = Amount(INITIAL_SUPPLY);
+    let mut test = setup(0, max_per_withdrawal);
+
+    // a valid amount is returned exactly
+    let transaction = take_coins_transaction(&test, Amount(400_000));
+    test.template_test
+        .execute_expect_success(transaction, vec![test.account_proof.clone()]);
+    assert_eq!(get_account_balance(&mut test), Amount(400_000));
+
+    // amounts over the maximum are rejected
+    let transaction = take_coins_transaction(&test, max_per_withdrawal + Amount(1));
+    let reason = test
+        .template_test
+        .execute_expect_failure(transaction, vec![test.account_proof.clone()]);
+    assert_reject_reason(reason, "The amount exceeds the maximum of 1000000 coins per withdrawal");
+
+    // amounts under the maximum but over the remaining balance are also rejected
+    let transaction = take_coins_transaction(&test, Amount(700_000));
+    let reason = test
+        .template_test
+        .execute_expect_failure(transaction, vec![test.account_proof.clone()]);
+    assert_reject_reason(reason, "Insufficient coins in the faucet");
+}
+
+#[test]
+fn it_funds_accounts_with_exact_amounts() {
+    // the withdrawal limit does not apply to exact withdrawals
+    let mut test = setup(0, Amount(1000));
+
+    let transaction = take_exact_transaction(&test, Amount(1234));
+    test.template_test
+        .execute_expect_success(transaction, vec![test.account_proof.clone()]);
+    assert_eq!(get_account_balance(&mut test), Amount(1234));
+    assert_eq!(get_faucet_balance(&mut test), Amount(INITIAL_SUPPLY - 1234));
+
+    // but the faucet balance does
+    let transaction = take_exact_transaction(&test, Amount(INITIAL_SUPPLY));
+    let reason = test
+        .template_test
+        .execute_expect_failure(transaction, vec![test.account_proof.clone()]);
+    assert_reject_reason(reason, "Insufficient coins in the faucet");
+}
+
+#[test]
+fn it_stores_the_decimals_of_the_token() {
+    let mut test = TemplateTest::new(["."]);
+
+    let mut mint_with_decimals = |symbol: &str, decimals: u8| {
+        let faucet_component: ComponentAddress = test.call_function(
+            "TestFaucet",
+            "mint_with_symbol",
+            args![Amount(INITIAL_SUPPLY), symbol.to_string(), decimals],
+            vec![],
+        );
+        let faucet_resource = test
+            .get_previous_output_address(SubstateType::Resource)
+            .as_resource_address()
+            .unwrap();
+        (faucet_component, faucet_resource)
+    };
+    let (a_faucet, a_resource) = mint_with_decimals("A", 6);
+    let (b_faucet, b_resource) = mint_with_decimals("B", 18);
+
+    for (faucet_component, resource, decimals) in [(a_faucet, a_resource, 6u8), (b_faucet, b_resource, 18)] {
+        let faucet_decimals: u8 = test.call_method(faucet_component, "get_decimals", args![], vec![]);
+        assert_eq!(faucet_decimals, decimals);
+
+        let resource = test.read_only_state_store().get_resource(&resource).unwrap();
+        assert_eq!(resource.metadata().get("decimals"), Some(decimals.to_string().as_str()));
+    }
+}
+
+#[test]
+fn it_can_be_refilled() {
+    let mut test = setup(0, Amount(INITIAL_SUPPLY));
+
+    // drain most of the faucet
+    let transaction = take_coins_transaction(&test, Amount(INITIAL_SUPPLY - 500));
+    test.template_test
+        .execute_expect_success(transaction, vec![test.account_proof.clone()]);
+    assert_eq!(get_faucet_balance(&mut test), Amount(500));
+
+    // there are not enough coins left for a free withdrawal
+    let transaction = take_free_coins_transaction(&test);
+    test.template_test
+        .execute_expect_failure(transaction, vec![test.account_proof.clone()]);
+
+    // refill the faucet with some of the coins
+    let transaction_refill = refill_transaction(&test, test.faucet_resource, Amount(10_000));
+    test.template_test
+        .execute_expect_success(transaction_refill, vec![test.account_proof.clone()]);
+    assert_eq!(get_faucet_balance(&mut test), Amount(10_500));
+
+    // free withdrawals work again
+    let transaction = take_free_coins_transaction(&test);
+    test.template_test
+        .execute_expect_success(transaction, vec![test.account_proof.clone()]);
+    assert_eq!(get_faucet_balance(&mut test), Amount(9_500));
+
+    // other resources cannot be used to refill the faucet
+    let transaction = refill_transaction(&test, XTR, Amount(1000));
+    let reason = test
+        .template_test
+        .execute_expect_failure(transaction, vec![test.account_proof.clone()]);
+    assert_reject_reason(reason, "Invalid refill resource");
+}
@@ -0,0 +1,218 @@
+//   Copyright 2024. The Tari Project
+//
+//   Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//   following conditions are met:
+//
+//   1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//   disclaimer.
+//
+//   2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//   following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//   3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//   products derived from this software without specific prior written permission.
+//
+//   THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//   INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//   DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//   SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//   SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//   WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//   USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::collections::BTreeMap;
+use account_utils::assert_component_is_account;
+use tari_template_lib::prelude::*;
+
+#[template]
+mod fixed_price_listing {
+    use super::*;
+
+    /// Buy-it-now listing of a single NFT at a fixed price
+    pub struct FixedPriceListing {
+        seller_badge_resource: ResourceAddress,
+
+        // The NFT will be locked until it's bought or the listing is cancelled
+        vault: Vault,
+
+        // address of the account component of the seller
+        seller_address: ComponentAddress,
+
+        // price (in XTR) that a buyer must pay
+        price: Amount,
+
+        // set when the NFT is sold or the listing is cancelled, no further actions are allowed afterwards
+        closed: bool,
+
+        // below-ask offers, the funds of each buyer are locked until the offer is accepted or withdrawn
+        // vaults cannot be dropped from the component state (it results in an "OrphanedSubstate" error), so settled or
+        // withdrawn offers are kept with an empty vault
+        offers: BTreeMap<ComponentAddress, Vault>,
+    }
+
+    impl FixedPriceListing {
+        // returns a badge used to cancel the listing in the future
+        pub fn list(nft_bucket: Bucket, seller_address: ComponentAddress, price: Amount) -> (Component<Self>, Bucket) {
+            assert!(
+                nft_bucket.resource_type() == ResourceType::NonFungible,
+                "The resource is not a NFT"
+            );
+
+            assert!(
+                nft_bucket.amount() == Amount(1),
+                "Can only list a single NFT"
+            );
+
+            assert!(price.is_positive(), "Invalid price");
+
+            // needed to ensure that we can send the payment when the NFT is bought
+            assert_component_is_account(seller_address);
+
+            // create the bucket with the badge to allow the seller to cancel the listing at any time
+            // we make sure that only the initial badge will be minted
+            let seller_badge_bucket = ResourceBuilder::non_fungible()
+                .mintable(AccessRule::DenyAll)
+                .burnable(AccessRule::AllowAll)
+                .initial_supply_with_data(Some((NonFungibleId::random(), (&(), &()))));
+            let seller_badge_resource = seller_badge_bucket.resource_address();
+
+            let component = Component::new(Self {
+                seller_badge_resource,
+                vault: Vault::from_bucket(nft_bucket),
+                seller_address,
+                price,
+                closed: false,
+                offers: BTreeMap::new(),
+            })
+            .with_access_rules(AccessRules::allow_all())
+            .create();
+
+            (component, seller_badge_bucket)
+        }
+
+        // sends the NFT to the buyer and the payment to the seller
+        pub fn buy(&mut self, buyer_account_address: ComponentAddress, payment: Bucket) {
+            assert!(!self.closed, "Listing is closed");
+
+            assert_eq!(
+                payment.resource_address(),
+                XTR,
+                "Invalid payment resource, the listing only accepts Tari (XTR) tokens"
+            );
+            assert!(payment.amount() == self.price, "Payment does not match the price");
+
+            // validate that the buyer account is really an account, so we can deposit the NFT
+            assert_component_is_account(buyer_account_address);
+
+            let nft_bucket = self.vault.withdraw_all();
+            ComponentManager::get(buyer_account_address).call::<_, ()>("deposit".to_string(), args![nft_bucket]);
+            ComponentManager::get(self.seller_address).call::<_, ()>("deposit".to_string(), args![payment]);
+
+            self.closed = true;
+        }
+
+        // returns the NFT to the seller
+        pub fn cancel(&mut self, seller_badge_bucket: Bucket) {
+            // as the seller badge resource cannot be minted and only one token exist,
+            // we only need to check that the resource address matches
+            assert!(
+                seller_badge_bucket.resource_address() == self.seller_badge_resource,
+                "Invalid seller badge"
+            );
+            assert!(!self.closed, "Listing is closed");
+
+            // burn the seller token to prevent it from being used again, as it has no more purpose
+            seller_badge_bucket.burn();
+
+            let nft_bucket = self.vault.withdraw_all();
+            ComponentManager::get(self.seller_address).call::<_, ()>("deposit".to_string(), args![nft_bucket]);
+
+            self.closed = true;
+        }
+
+        // locks a below-ask offer until the seller accepts it or the buyer withdraws it
+        pub fn offer(&mut self, buyer_account_address: ComponentAddress, payment: Bucket) {
+            assert!(!self.closed, "Listing is closed");
+
+            assert_eq!(
+                payment.resource_address(),
+                XTR,
+                "Invalid payment resource, the listing only accepts Tari (XTR) tokens"
+            );
+            assert!(payment.amount().is_positive(), "Invalid offer amount");
+            assert!(payment.amount() < self.price, "Offers must be lower than the price");
+
+            // the funds will be returned to the buyer account, so it must be a valid account
+            assert_component_is_account(buyer_account_address);
+
+            // buyers can make a new offer once their previous one was withdrawn, reusing its empty vault
+            match self.offers.get_mut(&buyer_account_address) {
+                Some(offer_vault) => {
+                    assert!(offer_vault.balance().is_zero(), "The buyer already has an offer");
+                    offer_vault.deposit(payment);
+                },
+                None => {
+                    self.offers.insert(buyer_account_address, Vault::from_bucket(payment));
+                },
+            }
+        }
+
+        // only returns the offers that still have funds locked
+        pub fn get_offers(&self) -> BTreeMap<ComponentAddress, Amount> {
+            self.offers
+                .iter()
+                .filter(|(_, vault)| !vault.balance().is_zero())
+                .map(|(buyer, vault)| (*buyer, vault.balance()))
+                .collect()
+        }
+
+        // settles the trade with the offer of the specified buyer
+        pub fn accept_offer(&mut self, seller_badge_bucket: Bucket, buyer_account_address: ComponentAddress) {
+            assert!(
+                seller_badge_bucket.resource_address() == self.seller_badge_resource,
+                "Invalid seller badge"
+            );
+            assert!(!self.closed, "Listing is closed");
+
+            let payment = self.take_offer_funds(buyer_account_address);
+
+            // the listing is finished, so the seller badge has no more purpose
+            seller_badge_bucket.burn();
+
+            let nft_bucket = self.vault.withdraw_all();
+            ComponentManager::get(buyer_account_address).call::<_, ()>("deposit".to_string(), args![nft_bucket]);
+            ComponentManager::get(self.seller_address).call::<_, ()>("deposit".to_string(), args![payment]);
+
+            self.closed = true;
+        }
+
+        // returns the locked funds of an offer to the buyer account, even after the listing is closed
+        pub fn withdraw_offer(&mut self, buyer_account_address: ComponentAddress) {
+            let funds = self.take_offer_funds(buyer_account_address);
+            ComponentManager::get(buyer_account_address).call::<_, ()>("deposit".to_string(), args![funds]);
+        }
+
+        pub fn get_price(&self) -> Amount {
+            self.price
+        }
+
+        pub fn is_closed(&self) -> bool {
+            self.closed
+        }
+
+        pub fn seller_badge_resource(&self) -> ResourceAddress {
+            self.seller_badge_resource
+        }
+
+        // empties the vault of an offer, which stays in the map as it cannot be removed
+        fn take_offer_funds(&mut self, buyer_account_address: ComponentAddress) -> Bucket {
+            let offer_vault = self
+                .offers
+                .get_mut(&buyer_account_address)
+                .filter(|vault| !vault.balance().is_zero())
+                .unwrap_or_else(|| panic!("There is no offer from {}", buyer_account_address));
+
+            offer_vault.withdraw_all()
+        }
+    }
+}
@@ -0,0 +1,349 @@
+//   Copyright 2024 The Tari Project
+//   SPDX-License-Identifier: BSD-3-Clause
+
+use std::collections::BTreeMap;
+
+use tari_template_lib::args;
+use tari_template_lib::constants::XTR;
+use tari_template_lib::models::{Amount, ComponentAddress, NonFungibleAddress, ResourceAddress};
+use tari_template_lib::prelude::Metadata;
+use tari_template_test_tooling::crypto::RistrettoSecretKey;
+use tari_template_test_tooling::support::assert_error::assert_reject_reason;
+use tari_template_test_tooling::{SubstateType, TemplateTest};
+use tari_transaction::Transaction;
+
+const PRICE: Amount = Amount(100);
+
+#[test]
+fn buyer_purchases_the_nft() {
+    let ListingTest {
+        mut test,
+        listing_component,
+        seller,
+        nft,
+    } = setup();
+
+    let buyer = create_account(&mut test);
+    let seller_balance = get_account_balance(&mut test, &seller, &XTR);
+
+    test.execute_expect_success(
+        buy_transaction(listing_component, &buyer, PRICE),
+        vec![buyer.owner_token.clone()],
+    );
+
+    // the buyer got the NFT and the seller got the payment
+    assert_eq!(get_account_balance(&mut test, &buyer, &nft.resource_address()), Amount(1));
+    assert_eq!(get_account_balance(&mut test, &seller, &XTR), seller_balance + PRICE);
+
+    // the NFT cannot be bought twice
+    let reason = test.execute_expect_failure(
+        buy_transaction(listing_component, &buyer, PRICE),
+        vec![buyer.owner_token.clone()],
+    );
+    assert_reject_reason(reason, "Listing is closed");
+}
+
+#[test]
+fn seller_cancels_the_listing() {
+    let ListingTest {
+        mut test,
+        listing_component,
+        seller,
+        nft,
+    } = setup();
+
+    let seller_badge_resource: ResourceAddress =
+        test.call_method(listing_component, "seller_badge_resource", args![], vec![]);
+    test.execute_expect_success(
+        Transaction::builder()
+            .call_method(seller.component, "withdraw", args![seller_badge_resource, Amount(1)])
+            .put_last_instruction_output_on_workspace("badge")
+            .call_method(listing_component, "cancel", args![Workspace("badge")])
+            .sign(&seller.key)
+            .build(),
+        vec![seller.owner_token.clone()],
+    );
+
+    // the NFT is back in the seller account
+    assert_eq!(get_account_balance(&mut test, &seller, &nft.resource_address()), Amount(1));
+    let closed: bool = test.call_method(listing_component, "is_closed", args![], vec![]);
+    assert!(closed);
+}
+
+#[test]
+fn it_rejects_underpayments() {
+    let ListingTest {
+        mut test,
+        listing_component,
+        ..
+    } = setup();
+
+    let buyer = create_account(&mut test);
+    let reason = test.execute_expect_failure(
+        buy_transaction(listing_component, &buyer, PRICE - Amount(1)),
+        vec![buyer.owner_token.clone()],
+    );
+    assert_reject_reason(reason, "Payment does not match the price");
+}
+
+#[test]
+fn seller_accepts_an_offer_and_other_buyers_reclaim_their_funds() {
+    let ListingTest {
+        mut test,
+        listing_component,
+        seller,
+        nft,
+    } = setup();
+
+    let buyer_1 = create_account(&mut test);
+    let buyer_2 = create_account(&mut test);
+    let seller_balance = get_account_balance(&mut test, &seller, &XTR);
+    let buyer_2_balance = get_account_balance(&mut test, &buyer_2, &XTR);
+
+    // offers at or above the price must use "buy" instead
+    let reason = test.execute_expect_failure(
+        offer_transaction(listing_component, &buyer_1, PRICE),
+        vec![buyer_1.owner_token.clone()],
+    );
+    assert_reject_reason(reason, "Offers must be lower than the price");
+
+    test.execute_expect_success(
+        offer_transaction(listing_component, &buyer_1, Amount(80)),
+        vec![buyer_1.owner_token.clone()],
+    );
+    test.execute_expect_success(
+        offer_transaction(listing_component, &buyer_2, Amount(60)),
+        vec![buyer_2.owner_token.clone()],
+    );
+
+    let offers: BTreeMap<ComponentAddress, Amount> =
+        test.call_method(listing_component, "get_offers", args![], vec![]);
+    assert_eq!(offers.len(), 2);
+    assert_eq!(offers[&buyer_1.component], Amount(80));
+    assert_eq!(offers[&buyer_2.component], Amount(60));
+
+    // the seller accepts the first offer
+    let seller_badge_resource: ResourceAddress =
+        test.call_method(listing_component, "seller_badge_resource", args![], vec![]);
+    test.execute_expect_success(
+        Transaction::builder()
+            .call_method(seller.component, "withdraw", args![seller_badge_resource, Amount(1)])
+            .put_last_instruction_output_on_workspace("badge")
+            .call_method(
+                listing_component,
+                "accept_offer",
+                args![Workspace("badge"), buyer_1.component],
+            )
+            .sign(&seller.key)
+            .build(),
+        vec![seller.owner_token.clone()],
+    );
+    assert_eq!(get_account_balance(&mut test, &buyer_1, &nft.resource_address()), Amount(1));
+    assert_eq!(get_account_balance(&mut test, &seller, &XTR), seller_balance + Amount(80));
+
+    // the accepted offer has no funds left to withdraw
+    let reason = test.execute_expect_failure(
+        withdraw_offer_transaction(listing_component, &buyer_1),
+        vec![buyer_1.owner_token.clone()],
+    );
+    assert_reject_reason(reason, "There is no offer from");
+
+    // the other buyer gets the locked funds back
+    assert_eq!(
+        get_account_balance(&mut test, &buyer_2, &XTR),
+        buyer_2_balance - Amount(60)
+    );
+    test.execute_expect_success(
+        withdraw_offer_transaction(listing_component, &buyer_2),
+        vec![buyer_2.owner_token.clone()],
+    );
+    assert_eq!(get_account_balance(&mut test, &buyer_2, &XTR), buyer_2_balance);
+
+    let offers: BTreeMap<ComponentAddress, Amount> =
+        test.call_method(listing_component, "get_offers", args![], vec![]);
+    assert!(offers.is_empty());
+}
+
+#[test]
+fn buyer_withdraws_an_offer_while_the_listing_is_open() {
+    let ListingTest {
+        mut test,
+        listing_component,
+        ..
+    } = setup();
+
+    let buyer = create_account(&mut test);
+    let buyer_balance = get_account_balance(&mut test, &buyer, &XTR);
+
+    test.execute_expect_success(
+        offer_transaction(listing_component, &buyer, Amount(80)),
+        vec![buyer.owner_token.clone()],
+    );
+    test.execute_expect_success(
+        withdraw_offer_transaction(listing_component, &buyer),
+        vec![buyer.owner_token.clone()],
+    );
+    assert_eq!(get_account_balance(&mut test, &buyer, &XTR), buyer_balance);
+    let offers: BTreeMap<ComponentAddress, Amount> =
+        test.call_method(listing_component, "get_offers", args![], vec![]);
+    assert!(offers.is_empty());
+
+    // the offer cannot be withdrawn twice
+    let reason = test.execute_expect_failure(
+        withdraw_offer_transaction(listing_component, &buyer),
+        vec![buyer.owner_token.clone()],
+    );
+    assert_reject_reason(reason, "There is no offer from");
+
+    // but the buyer can make a new offer afterwards
+    test.execute_expect_success(
+        offer_transaction(listing_component, &buyer, Amount(90)),
+        vec![buyer.owner_token.clone()],
+    );
+    let offers: BTreeMap<ComponentAddress, Amount> =
+        test.call_method(listing_component, "get_offers", args![], vec![]);
+    assert_eq!(offers[&buyer.component], Amount(90));
+    assert_eq!(get_account_balance(&mut test, &buyer, &XTR), buyer_balance - Amount(90));
+}
+
+#[derive(Clone, Debug)]
+struct Account {
+    pub component: ComponentAddress,
+    pub owner_token: NonFungibleAddress,
+    pub key: RistrettoSecretKey,
+}
+
+struct ListingTest {
+    test: TemplateTest,
+    listing_component: ComponentAddress,
+    seller: Account,
+    nft: NonFungibleAddress,
+}
+
+fn setup() -> ListingTest {
+    let mut test = TemplateTest::new(["."]);
+    let listing_template = test.get_template_address("FixedPriceListing");
+
+    // create the NFT that the seller is going to list
+    let seller = create_account(&mut test);
+    let nft = mint_account_nft(&mut test, &seller);
+
+    let result = test.execute_expect_success(
+        Transaction::builder()
+            .call_method(
+                seller.component,
+                "withdraw_non_fungible",
+                args![nft.resource_address(), nft.id()],
+            )
+            .put_last_instruction_output_on_workspace("nft_bucket")
+            .call_function(
+                listing_template,
+                "list",
+                args![Workspace("nft_bucket"), seller.component, PRICE],
+            )
+            .put_last_instruction_output_on_workspace("listing")
+            .call_method(seller.component, "deposit", args![Workspace("listing.1")])
+            .sign(&seller.key)
+            .build(),
+        vec![seller.owner_token.clone()],
+    );
+    let (substate_addr, _) = result
+        .expect_success()
+        .up_iter()
+        .find(|(address, substate)| {
+            address.is_component() && substate.substate_value().component().unwrap().module_name == "FixedPriceListing"
+        })
+        .unwrap();
+    let listing_component = substate_addr.as_component_address().unwrap();
+
+    ListingTest {
+        test,
+        listing_component,
+        seller,
+        nft,
+    }
+}
+
+fn create_account(test: &mut TemplateTest) -> Account {
+    let (component, owner_token, key) = test.create_funded_account();
+    Account {
+        component,
+        owner_token,
+        key,
+    }
+}
+
+fn get_account_balance(test: &mut TemplateTest, account: &Account, resource: &ResourceAddress) -> Amount {
+    let result = test.execute_expect_success(
+        Transaction::builder()
+            .call_method(account.component, "balance", args![resource])
+            .sign(&account.key)
+            .build(),
+        vec![account.owner_token.clone()],
+    );
+    result.finalize.execution_results[0]
+        .decode::<Amount>()
+        .unwrap()
+}
+
+fn mint_account_nft(test: &mut TemplateTest, account: &Account) -> NonFungibleAddress {
+    let account_nft_template = test.get_template_address("AccountNonFungible");
+    let result = test.execute_expect_success(
+        Transaction::builder()
+            .call_function(account_nft_template, "create", args![account.owner_token])
+            .sign(&account.key)
+            .build(),
+        vec![account.owner_token.clone()],
+    );
+    let account_nft_component = result.finalize.execution_results[0]
+        .decode::<ComponentAddress>()
+        .unwrap();
+
+    let mut nft_metadata = Metadata::new();
+    nft_metadata.insert("name".to_string(), "my_custom_nft".to_string());
+
+    test.execute_expect_success(
+        Transaction::builder()
+            .call_method(account_nft_component, "mint", args![nft_metadata])
+            .put_last_instruction_output_on_workspace("nft_bucket")
+            .call_method(account.component, "deposit", args![Workspace("nft_bucket")])
+            .sign(&account.key)
+            .build(),
+        vec![account.owner_token.clone()],
+    );
+    let output = test.get_previous_output_address(SubstateType::NonFungible);
+    output.as_non_fungible_address().unwrap().clone()
+}
+
+fn buy_transaction(listing_component: ComponentAddress, buyer: &Account, payment: Amount) -> Transaction {
+    Transaction::builder()
+        .call_method(buyer.component, "withdraw", args![XTR, payment])
+        .put_last_instruction_output_on_workspace("payment")
+        .call_method(
+            listing_component,
+            "buy",
+            args![buyer.component, Workspace("payment")],
+        )
+        .sign(&buyer.key)
+        .build()
+}
+
+fn offer_transaction(listing_component: ComponentAddress, buyer: &Account, amount: Amount) -> Transaction {
+    Transaction::builder()
+        .call_method(buyer.component, "withdraw", args![XTR, amount])
+        .put_last_instruction_output_on_workspace("offer")
+        .call_method(
+            listing_component,
+            "offer",
+            args![buyer.component, Workspace("offer")],
+        )
+        .sign(&buyer.key)
+        .build()
+}
+
+fn withdraw_offer_transaction(listing_component: ComponentAddress, buyer: &Account) -> Transaction {
+    Transaction::builder()
+        .call_method(listing_component, "withdraw_offer", args![buyer.component])
+        .sign(&buyer.key)
+        .build()
+}
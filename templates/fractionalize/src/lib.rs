@@ -0,0 +1,113 @@
+//   Copyright 2024. The Tari Project
+//
+//   Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//   following conditions are met:
+//
+//   1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//   disclaimer.
+//
+//   2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//   following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//   3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//   products derived from this software without specific prior written permission.
+//
+//   THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//   INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//   DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//   SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//   SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//   WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//   USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+
+use tari_template_lib::prelude::*;
+
+#[template]
+mod fractionalize {
+    use super::*;
+
+    /// Locks a single NFT and splits its ownership into fungible fraction tokens
+    /// The NFT can only be taken out again by presenting the whole supply of fractions
+    pub struct Fractionalize {
+        // holds the locked NFT until it's redeemed
+        vault: Vault,
+        fraction_resource: ResourceAddress,
+        // total supply of fractions, all of them are needed to redeem the NFT
+        shares: Amount,
+    }
+
+    impl Fractionalize {
+        // returns all the fraction tokens to the depositor of the NFT
+        pub fn new(nft_bucket: Bucket, shares: Amount) -> (Component<Self>, Bucket) {
+            assert!(
+                nft_bucket.resource_type() == ResourceType::NonFungible,
+                "The resource is not a NFT"
+            );
+
+            assert!(
+                nft_bucket.amount() == Amount(1),
+                "Can only fractionalize a single NFT"
+            );
+
+            assert!(shares.is_positive(), "Invalid amount of shares");
+
+            // the address of the component is allocated beforehand, so the fractions can be restricted to it
+            let component_alloc = CallerContext::allocate_component_address(None);
+            let component_rule = AccessRule::Restricted(RestrictedAccessRule::Require(RequireRule::Require(
+                RuleRequirement::ScopedToComponent(*component_alloc.address()),
+            )));
+
+            // the whole supply is minted upfront, so no more fractions can be created afterwards
+            // only this component can burn the fractions, otherwise holders could destroy them and lock the NFT forever
+            let fractions_bucket = ResourceBuilder::fungible()
+                .burnable(component_rule)
+                .initial_supply(shares);
+            let fraction_resource = fractions_bucket.resource_address();
+
+            let component = Component::new(Self {
+                vault: Vault::from_bucket(nft_bucket),
+                fraction_resource,
+                shares,
+            })
+            .with_access_rules(AccessRules::allow_all())
+            .with_address_allocation(component_alloc)
+            .create();
+
+            (component, fractions_bucket)
+        }
+
+        // burns the whole supply of fractions and returns the NFT
+        pub fn redeem(&mut self, fraction_bucket: Bucket) -> Bucket {
+            assert!(
+                fraction_bucket.resource_address() == self.fraction_resource,
+                "Invalid fraction resource"
+            );
+            assert!(
+                fraction_bucket.amount() == self.shares,
+                "All the {} fractions are needed to redeem the NFT",
+                self.shares
+            );
+
+            fraction_bucket.burn();
+
+            self.vault.withdraw_all()
+        }
+
+        // returns None once the NFT has been redeemed
+        pub fn get_locked_nft(&self) -> Option<NonFungibleAddress> {
+            self.vault
+                .get_non_fungible_ids()
+                .first()
+                .map(|id| NonFungibleAddress::new(self.vault.resource_address(), id.clone()))
+        }
+
+        pub fn get_fraction_resource(&self) -> ResourceAddress {
+            self.fraction_resource
+        }
+
+        pub fn get_shares(&self) -> Amount {
+            self.shares
+        }
+    }
+}
@@ -0,0 +1,188 @@
+//   Copyright 2024 The Tari Project
+//   SPDX-License-Identifier: BSD-3-Clause
+
+use tari_template_lib::args;
+use tari_template_lib::models::{Amount, ComponentAddress, NonFungibleAddress, ResourceAddress};
+use tari_template_lib::prelude::Metadata;
+use tari_template_test_tooling::crypto::RistrettoSecretKey;
+use tari_template_test_tooling::support::assert_error::assert_reject_reason;
+use tari_template_test_tooling::{SubstateType, TemplateTest};
+use tari_transaction::Transaction;
+
+const SHARES: Amount = Amount(1000);
+
+#[test]
+fn it_fractionalizes_an_nft() {
+    let FractionalizeTest {
+        mut test,
+        fractionalize_component,
+        owner,
+        nft,
+        fraction_resource,
+    } = setup();
+
+    // the NFT is locked and the owner holds all the fractions
+    assert_eq!(get_account_balance(&mut test, &owner, &nft.resource_address()), Amount(0));
+    assert_eq!(get_account_balance(&mut test, &owner, &fraction_resource), SHARES);
+    let locked_nft: Option<NonFungibleAddress> =
+        test.call_method(fractionalize_component, "get_locked_nft", args![], vec![]);
+    assert_eq!(locked_nft, Some(nft));
+}
+
+#[test]
+fn it_redeems_the_nft_with_all_the_fractions() {
+    let FractionalizeTest {
+        mut test,
+        fractionalize_component,
+        owner,
+        nft,
+        fraction_resource,
+    } = setup();
+
+    // a partial redemption is rejected
+    let reason = test.execute_expect_failure(
+        redeem_transaction(fractionalize_component, &owner, fraction_resource, SHARES - Amount(1)),
+        vec![owner.owner_token.clone()],
+    );
+    assert_reject_reason(reason, "All the 1000 fractions are needed to redeem the NFT");
+
+    // presenting the entire supply returns the NFT
+    test.execute_expect_success(
+        redeem_transaction(fractionalize_component, &owner, fraction_resource, SHARES),
+        vec![owner.owner_token.clone()],
+    );
+    assert_eq!(get_account_balance(&mut test, &owner, &nft.resource_address()), Amount(1));
+    assert_eq!(get_account_balance(&mut test, &owner, &fraction_resource), Amount(0));
+    let locked_nft: Option<NonFungibleAddress> =
+        test.call_method(fractionalize_component, "get_locked_nft", args![], vec![]);
+    assert_eq!(locked_nft, None);
+}
+
+#[derive(Clone, Debug)]
+struct Account {
+    pub component: ComponentAddress,
+    pub owner_token: NonFungibleAddress,
+    pub key: RistrettoSecretKey,
+}
+
+struct FractionalizeTest {
+    test: TemplateTest,
+    fractionalize_component: ComponentAddress,
+    owner: Account,
+    nft: NonFungibleAddress,
+    fraction_resource: ResourceAddress,
+}
+
+fn setup() -> FractionalizeTest {
+    let mut test = TemplateTest::new(["."]);
+    let fractionalize_template = test.get_template_address("Fractionalize");
+
+    let owner = create_account(&mut test);
+    let nft = mint_account_nft(&mut test, &owner);
+
+    let result = test.execute_expect_success(
+        Transaction::builder()
+            .call_method(
+                owner.component,
+                "withdraw_non_fungible",
+                args![nft.resource_address(), nft.id()],
+            )
+            .put_last_instruction_output_on_workspace("nft_bucket")
+            .call_function(
+                fractionalize_template,
+                "new",
+                args![Workspace("nft_bucket"), SHARES],
+            )
+            .put_last_instruction_output_on_workspace("ret")
+            .call_method(owner.component, "deposit", args![Workspace("ret.1")])
+            .sign(&owner.key)
+            .build(),
+        vec![owner.owner_token.clone()],
+    );
+    let (substate_addr, _) = result
+        .expect_success()
+        .up_iter()
+        .find(|(address, substate)| {
+            address.is_component() && substate.substate_value().component().unwrap().module_name == "Fractionalize"
+        })
+        .unwrap();
+    let fractionalize_component = substate_addr.as_component_address().unwrap();
+
+    let fraction_resource: ResourceAddress =
+        test.call_method(fractionalize_component, "get_fraction_resource", args![], vec![]);
+
+    FractionalizeTest {
+        test,
+        fractionalize_component,
+        owner,
+        nft,
+        fraction_resource,
+    }
+}
+
+fn create_account(test: &mut TemplateTest) -> Account {
+    let (component, owner_token, key) = test.create_funded_account();
+    Account {
+        component,
+        owner_token,
+        key,
+    }
+}
+
+fn get_account_balance(test: &mut TemplateTest, account: &Account, resource: &ResourceAddress) -> Amount {
+    let result = test.execute_expect_success(
+        Transaction::builder()
+            .call_method(account.component, "balance", args![resource])
+            .sign(&account.key)
+            .build(),
+        vec![account.owner_token.clone()],
+    );
+    result.finalize.execution_results[0]
+        .decode::<Amount>()
+        .unwrap()
+}
+
+fn mint_account_nft(test: &mut TemplateTest, account: &Account) -> NonFungibleAddress {
+    let account_nft_template = test.get_template_address("AccountNonFungible");
+    let result = test.execute_expect_success(
+        Transaction::builder()
+            .call_function(account_nft_template, "create", args![account.owner_token])
+            .sign(&account.key)
+            .build(),
+        vec![account.owner_token.clone()],
+    );
+    let account_nft_component = result.finalize.execution_results[0]
+        .decode::<ComponentAddress>()
+        .unwrap();
+
+    let mut nft_metadata = Metadata::new();
+    nft_metadata.insert("name".to_string(), "my_custom_nft".to_string());
+
+    test.execute_expect_success(
+        Transaction::builder()
+            .call_method(account_nft_component, "mint", args![nft_metadata])
+            .put_last_instruction_output_on_workspace("nft_bucket")
+            .call_method(account.component, "deposit", args![Workspace("nft_bucket")])
+            .sign(&account.key)
+            .build(),
+        vec![account.owner_token.clone()],
+    );
+    let output = test.get_previous_output_address(SubstateType::NonFungible);
+    output.as_non_fungible_address().unwrap().clone()
+}
+
+fn redeem_transaction(
+    fractionalize_component: ComponentAddress,
+    account: &Account,
+    fraction_resource: ResourceAddress,
+    amount: Amount,
+) -> Transaction {
+    Transaction::builder()
+        .call_method(account.component, "withdraw", args![fraction_resource, amount])
+        .put_last_instruction_output_on_workspace("fractions")
+        .call_method(fractionalize_component, "redeem", args![Workspace("fractions")])
+        .put_last_instruction_output_on_workspace("nft")
+        .call_method(account.component, "deposit", args![Workspace("nft")])
+        .sign(&account.key)
+        .build()
+}
@@ -0,0 +1,191 @@
+//   Copyright 2024. The Tari Project
+//
+//   Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//   following conditions are met:
+//
+//   1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//   disclaimer.
+//
+//   2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//   following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//   3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//   products derived from this software without specific prior written permission.
+//
+//   THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//   INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//   DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//   SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//   SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//   WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//   USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::collections::{BTreeMap, BTreeSet};
+use tari_template_lib::prelude::*;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Proposal {
+    description: String,
+    ending_epoch: u64,
+    votes_for: Amount,
+    votes_against: Amount,
+    // voter badges that already voted in the proposal
+    voters: BTreeSet<NonFungibleId>,
+}
+
+#[template]
+mod governance {
+    use super::*;
+
+    /// Token-weighted governance
+    /// Holders of the governance resource stake it in exchange for a voter badge, that allows them to create proposals
+    /// and vote on them. The voting weight of each badge is the amount staked with it.
+    pub struct Governance {
+        staked_vault: Vault,
+
+        // badges issued to the stakers, along with the amount staked with each one
+        voter_badge_resource: ResourceAddress,
+        voter_weights: BTreeMap<NonFungibleId, Amount>,
+
+        proposals: BTreeMap<u64, Proposal>,
+        next_proposal_id: u64,
+    }
+
+    impl Governance {
+        pub fn new(governance_resource: ResourceAddress) -> Component<Self> {
+            // the address of the component is allocated beforehand, so the badges can be restricted to it
+            let component_alloc = CallerContext::allocate_component_address(None);
+            let component_rule = AccessRule::Restricted(RestrictedAccessRule::Require(RequireRule::Require(
+                RuleRequirement::ScopedToComponent(*component_alloc.address()),
+            )));
+
+            // only this component can mint and burn the badges, as each one is backed by the tokens staked with it
+            let voter_badge_resource = ResourceBuilder::non_fungible()
+                .mintable(component_rule.clone())
+                .burnable(component_rule)
+                .build();
+
+            Component::new(Self {
+                staked_vault: Vault::new_empty(governance_resource),
+                voter_badge_resource,
+                voter_weights: BTreeMap::new(),
+                proposals: BTreeMap::new(),
+                next_proposal_id: 0,
+            })
+            .with_access_rules(AccessRules::allow_all())
+            .with_address_allocation(component_alloc)
+            .create()
+        }
+
+        // locks the governance tokens and returns a voter badge with an equivalent voting weight
+        pub fn stake(&mut self, bucket: Bucket) -> Bucket {
+            assert!(
+                bucket.resource_address() == self.staked_vault.resource_address(),
+                "Invalid governance resource"
+            );
+            assert!(bucket.amount().is_positive(), "Invalid stake amount");
+
+            let badge_id = NonFungibleId::random();
+            self.voter_weights.insert(badge_id.clone(), bucket.amount());
+            self.staked_vault.deposit(bucket);
+
+            ResourceManager::get(self.voter_badge_resource).mint_non_fungible(badge_id, &(), &())
+        }
+
+        // burns the voter badge and returns the tokens staked with it
+        // the tokens stay locked while the badge has votes in proposals that did not end yet, so they cannot be moved
+        // to another badge to vote again
+        pub fn unstake(&mut self, voter_badge: Bucket) -> Bucket {
+            let (badge_id, weight) = self.get_voter(&voter_badge);
+
+            let current_epoch = Consensus::current_epoch();
+            let has_active_votes = self
+                .proposals
+                .values()
+                .any(|proposal| current_epoch < proposal.ending_epoch && proposal.voters.contains(&badge_id));
+            assert!(!has_active_votes, "The voter badge has votes in active proposals");
+
+            self.voter_weights.remove(&badge_id);
+            voter_badge.burn();
+
+            self.staked_vault.withdraw(weight)
+        }
+
+        // returns the new proposal id and the voter badge back to the caller
+        pub fn create_proposal(&mut self, description: String, voting_epochs: u64, voter_badge: Bucket) -> (u64, Bucket) {
+            self.get_voter(&voter_badge);
+            assert!(voting_epochs > 0, "Invalid voting period");
+
+            let proposal_id = self.next_proposal_id;
+            self.proposals.insert(proposal_id, Proposal {
+                description,
+                ending_epoch: Consensus::current_epoch() + voting_epochs,
+                votes_for: Amount::zero(),
+                votes_against: Amount::zero(),
+                voters: BTreeSet::new(),
+            });
+            self.next_proposal_id += 1;
+
+            (proposal_id, voter_badge)
+        }
+
+        // returns the voter badge back to the caller
+        pub fn vote(&mut self, proposal_id: u64, support: bool, voter_badge: Bucket) -> Bucket {
+            let (badge_id, weight) = self.get_voter(&voter_badge);
+
+            let proposal = self
+                .proposals
+                .get_mut(&proposal_id)
+                .unwrap_or_else(|| panic!("Proposal {} does not exist", proposal_id));
+            assert!(
+                Consensus::current_epoch() < proposal.ending_epoch,
+                "Voting has ended"
+            );
+            assert!(proposal.voters.insert(badge_id), "Already voted in this proposal");
+
+            if support {
+                proposal.votes_for = proposal.votes_for + weight;
+            } else {
+                proposal.votes_against = proposal.votes_against + weight;
+            }
+
+            voter_badge
+        }
+
+        // a proposal passes if it has more votes in favour than against after the voting period
+        pub fn tally(&self, proposal_id: u64) -> bool {
+            let proposal = self.get_proposal(proposal_id);
+            assert!(
+                Consensus::current_epoch() >= proposal.ending_epoch,
+                "Voting is still in progress"
+            );
+
+            proposal.votes_for > proposal.votes_against
+        }
+
+        pub fn get_proposal(&self, proposal_id: u64) -> Proposal {
+            self.proposals
+                .get(&proposal_id)
+                .cloned()
+                .unwrap_or_else(|| panic!("Proposal {} does not exist", proposal_id))
+        }
+
+        pub fn voter_badge_resource(&self) -> ResourceAddress {
+            self.voter_badge_resource
+        }
+
+        // returns the badge id and its voting weight
+        fn get_voter(&self, voter_badge: &Bucket) -> (NonFungibleId, Amount) {
+            assert!(
+                voter_badge.resource_address() == self.voter_badge_resource,
+                "Invalid voter badge"
+            );
+            let badge_ids = voter_badge.get_non_fungible_ids();
+            assert!(badge_ids.len() == 1, "Only a single voter badge is allowed");
+
+            let badge_id = badge_ids[0].clone();
+            let weight = *self.voter_weights.get(&badge_id).expect("Unknown voter badge");
+            (badge_id, weight)
+        }
+    }
+}
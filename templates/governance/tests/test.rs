@@ -0,0 +1,269 @@
+//   Copyright 2024 The Tari Project
+//   SPDX-License-Identifier: BSD-3-Clause
+
+use tari_engine_types::virtual_substate::{VirtualSubstate, VirtualSubstateId};
+use tari_template_lib::args;
+use tari_template_lib::constants::XTR;
+use tari_template_lib::models::{Amount, ComponentAddress, NonFungibleAddress, ResourceAddress};
+use tari_template_test_tooling::crypto::RistrettoSecretKey;
+use tari_template_test_tooling::support::assert_error::assert_reject_reason;
+use tari_template_test_tooling::TemplateTest;
+use tari_transaction::Transaction;
+
+const VOTING_EPOCHS: u64 = 10;
+
+#[test]
+fn proposal_passes_with_majority_support() {
+    let GovernanceTest {
+        mut test,
+        governance_component,
+        voter_badge_resource,
+        whale,
+        minnow,
+    } = setup();
+
+    create_proposal(&mut test, governance_component, voter_badge_resource, &minnow);
+    let proposal_id = 0u64;
+
+    // the voting weight in favour (300) is higher than the one against (100)
+    let transaction = vote_transaction(governance_component, voter_badge_resource, &whale, proposal_id, true);
+    test.execute_expect_success(transaction, vec![whale.owner_token.clone()]);
+    let transaction = vote_transaction(governance_component, voter_badge_resource, &minnow, proposal_id, false);
+    test.execute_expect_success(transaction, vec![minnow.owner_token.clone()]);
+
+    // the proposal cannot be tallied until the voting period ends
+    let transaction = tally_transaction(governance_component, &whale, proposal_id);
+    let reason = test.execute_expect_failure(transaction, vec![]);
+    assert_reject_reason(reason, "Voting is still in progress");
+
+    set_epoch(&mut test, VOTING_EPOCHS);
+    let passed: bool = test.call_method(governance_component, "tally", args![proposal_id], vec![]);
+    assert!(passed);
+}
+
+#[test]
+fn proposal_fails_without_majority_support() {
+    let GovernanceTest {
+        mut test,
+        governance_component,
+        voter_badge_resource,
+        whale,
+        minnow,
+    } = setup();
+
+    create_proposal(&mut test, governance_component, voter_badge_resource, &minnow);
+    let proposal_id = 0u64;
+
+    let transaction = vote_transaction(governance_component, voter_badge_resource, &whale, proposal_id, false);
+    test.execute_expect_success(transaction, vec![whale.owner_token.clone()]);
+    let transaction = vote_transaction(governance_component, voter_badge_resource, &minnow, proposal_id, true);
+    test.execute_expect_success(transaction, vec![minnow.owner_token.clone()]);
+
+    // each voter can only vote once per proposal
+    let transaction = vote_transaction(governance_component, voter_badge_resource, &minnow, proposal_id, true);
+    let reason = test.execute_expect_failure(transaction, vec![minnow.owner_token.clone()]);
+    assert_reject_reason(reason, "Already voted in this proposal");
+
+    set_epoch(&mut test, VOTING_EPOCHS);
+    let passed: bool = test.call_method(governance_component, "tally", args![proposal_id], vec![]);
+    assert!(!passed);
+}
+
+#[test]
+fn it_rejects_votes_after_the_deadline() {
+    let GovernanceTest {
+        mut test,
+        governance_component,
+        voter_badge_resource,
+        whale,
+        ..
+    } = setup();
+
+    create_proposal(&mut test, governance_component, voter_badge_resource, &whale);
+    let proposal_id = 0u64;
+
+    set_epoch(&mut test, VOTING_EPOCHS);
+    let transaction = vote_transaction(governance_component, voter_badge_resource, &whale, proposal_id, true);
+    let reason = test.execute_expect_failure(transaction, vec![whale.owner_token.clone()]);
+    assert_reject_reason(reason, "Voting has ended");
+}
+
+#[test]
+fn voters_unstake_once_their_proposals_end() {
+    let GovernanceTest {
+        mut test,
+        governance_component,
+        voter_badge_resource,
+        whale,
+        minnow,
+    } = setup();
+
+    create_proposal(&mut test, governance_component, voter_badge_resource, &minnow);
+    let proposal_id = 0u64;
+    let transaction = vote_transaction(governance_component, voter_badge_resource, &whale, proposal_id, true);
+    test.execute_expect_success(transaction, vec![whale.owner_token.clone()]);
+
+    // the tokens are locked while the vote counts in the proposal
+    let transaction = unstake_transaction(governance_component, voter_badge_resource, &whale);
+    let reason = test.execute_expect_failure(transaction, vec![whale.owner_token.clone()]);
+    assert_reject_reason(reason, "The voter badge has votes in active proposals");
+
+    // voters that did not vote, like the proposal creator, can unstake at any time
+    let transaction = unstake_transaction(governance_component, voter_badge_resource, &minnow);
+    test.execute_expect_success(transaction, vec![minnow.owner_token.clone()]);
+
+    // the badge is burned in exchange for the staked tokens
+    set_epoch(&mut test, VOTING_EPOCHS);
+    let balance_before = get_balance(&mut test, &whale, XTR);
+    let transaction = unstake_transaction(governance_component, voter_badge_resource, &whale);
+    test.execute_expect_success(transaction, vec![whale.owner_token.clone()]);
+    assert_eq!(get_balance(&mut test, &whale, XTR), balance_before + Amount(300));
+    assert_eq!(get_balance(&mut test, &whale, voter_badge_resource), Amount::zero());
+
+    // the votes are kept after unstaking
+    let passed: bool = test.call_method(governance_component, "tally", args![proposal_id], vec![]);
+    assert!(passed);
+}
+
+#[derive(Clone, Debug)]
+struct Account {
+    pub component: ComponentAddress,
+    pub owner_token: NonFungibleAddress,
+    pub key: RistrettoSecretKey,
+}
+
+struct GovernanceTest {
+    test: TemplateTest,
+    governance_component: ComponentAddress,
+    // the voter badge resource is the same for all the voters
+    voter_badge_resource: ResourceAddress,
+    // voter with a big stake
+    whale: Account,
+    // voter with a small stake
+    minnow: Account,
+}
+
+fn setup() -> GovernanceTest {
+    let mut test = TemplateTest::new(["."]);
+
+    // XTR is used as the governance resource
+    let governance_component: ComponentAddress = test.call_function("Governance", "new", args![XTR], vec![]);
+    let voter_badge_resource: ResourceAddress =
+        test.call_method(governance_component, "voter_badge_resource", args![], vec![]);
+
+    let whale = create_account(&mut test);
+    stake(&mut test, governance_component, &whale, Amount(300));
+    let minnow = create_account(&mut test);
+    stake(&mut test, governance_component, &minnow, Amount(100));
+
+    GovernanceTest {
+        test,
+        governance_component,
+        voter_badge_resource,
+        whale,
+        minnow,
+    }
+}
+
+fn create_account(test: &mut TemplateTest) -> Account {
+    let (component, owner_token, key) = test.create_funded_account();
+    Account {
+        component,
+        owner_token,
+        key,
+    }
+}
+
+fn set_epoch(test: &mut TemplateTest, new_epoch: u64) {
+    test.set_virtual_substate(
+        VirtualSubstateId::CurrentEpoch,
+        VirtualSubstate::CurrentEpoch(new_epoch),
+    );
+}
+
+fn stake(test: &mut TemplateTest, governance_component: ComponentAddress, voter: &Account, amount: Amount) {
+    test.execute_expect_success(
+        Transaction::builder()
+            .call_method(voter.component, "withdraw", args![XTR, amount])
+            .put_last_instruction_output_on_workspace("stake")
+            .call_method(governance_component, "stake", args![Workspace("stake")])
+            .put_last_instruction_output_on_workspace("badge")
+            .call_method(voter.component, "deposit", args![Workspace("badge")])
+            .sign(&voter.key)
+            .build(),
+        vec![voter.owner_token.clone()],
+    );
+}
+
+fn create_proposal(
+    test: &mut TemplateTest,
+    governance_component: ComponentAddress,
+    voter_badge_resource: ResourceAddress,
+    voter: &Account,
+) {
+    test.execute_expect_success(
+        Transaction::builder()
+            .call_method(voter.component, "withdraw", args![voter_badge_resource, Amount(1)])
+            .put_last_instruction_output_on_workspace("badge")
+            .call_method(
+                governance_component,
+                "create_proposal",
+                args!["Fund the community treasury".to_string(), VOTING_EPOCHS, Workspace("badge")],
+            )
+            .put_last_instruction_output_on_workspace("proposal")
+            .call_method(voter.component, "deposit", args![Workspace("proposal.1")])
+            .sign(&voter.key)
+            .build(),
+        vec![voter.owner_token.clone()],
+    );
+}
+
+fn vote_transaction(
+    governance_component: ComponentAddress,
+    voter_badge_resource: ResourceAddress,
+    voter: &Account,
+    proposal_id: u64,
+    support: bool,
+) -> Transaction {
+    Transaction::builder()
+        .call_method(voter.component, "withdraw", args![voter_badge_resource, Amount(1)])
+        .put_last_instruction_output_on_workspace("badge")
+        .call_method(governance_component, "vote", args![proposal_id, support, Workspace("badge")])
+        .put_last_instruction_output_on_workspace("badge")
+        .call_method(voter.component, "deposit", args![Workspace("badge")])
+        .sign(&voter.key)
+        .build()
+}
+
+fn unstake_transaction(
+    governance_component: ComponentAddress,
+    voter_badge_resource: ResourceAddress,
+    voter: &Account,
+) -> Transaction {
+    Transaction::builder()
+        .call_method(voter.component, "withdraw", args![voter_badge_resource, Amount(1)])
+        .put_last_instruction_output_on_workspace("badge")
+        .call_method(governance_component, "unstake", args![Workspace("badge")])
+        .put_last_instruction_output_on_workspace("tokens")
+        .call_method(voter.component, "deposit", args![Workspace("tokens")])
+        .sign(&voter.key)
+        .build()
+}
+
+fn get_balance(test: &mut TemplateTest, account: &Account, resource: ResourceAddress) -> Amount {
+    let result = test.execute_expect_success(
+        Transaction::builder()
+            .call_method(account.component, "balance", args![resource])
+            .sign(&account.key)
+            .build(),
+        vec![account.owner_token.clone()],
+    );
+    result.finalize.execution_results[0].decode::<Amount>().unwrap()
+}
+
+fn tally_transaction(governance_component: ComponentAddress, signer: &Account, proposal_id: u64) -> Transaction {
+    Transaction::builder()
+        .call_method(governance_component, "tally", args![proposal_id])
+        .sign(&signer.key)
+        .build()
+}
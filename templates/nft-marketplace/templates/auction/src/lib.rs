@@ -20,11 +20,10 @@
 //   WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //   USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use tari_template_lib::prelude::*;
-use tari_template_lib::Hash;
+use std::collections::BTreeSet;
 
-/// TODO: create constant in template_lib for account template address (and other builtin templates)
-pub const ACCOUNT_TEMPLATE_ADDRESS: Hash = Hash::from_array([0u8; 32]);
+use account_utils::is_account;
+use tari_template_lib::prelude::*;
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Bid {
@@ -32,13 +31,51 @@ pub struct Bid {
     vault: Vault,
 }
 
+// lightweight view of an auction for UIs, without exposing the internal vaults
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct AuctionInfo {
+    pub seller: ComponentAddress,
+    pub min_price: Option<Amount>,
+    pub buy_price: Option<Amount>,
+    pub highest_bidder: Option<ComponentAddress>,
+    pub highest_bid: Option<Amount>,
+    pub ending_epoch: u64,
+    pub closed: bool,
+}
+
+// options chosen by the seller when creating an auction
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct AuctionConfig {
+    pub min_price: Option<Amount>,
+    pub buy_price: Option<Amount>,
+    pub epoch_period: u64,
+    pub vickrey: bool,
+    // resources in which the bids can be paid, all the bids of an auction must be paid in the same one
+    pub allowed_payments: Vec<ResourceAddress>,
+    // restricts the accounts that can bid, any account can bid if not set
+    pub allowed_bidders: Option<Vec<ComponentAddress>>,
+    // the NFT is returned to the seller if the auction ends with fewer bidders
+    pub min_bidders: Option<u32>,
+    // the NFT is returned to the seller if the highest bid is below it
+    pub reserve_price: Option<Amount>,
+}
+
+// immutable data of the receipt NFT minted to the winner, as a proof of purchase
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SaleReceipt {
+    pub nft: NonFungibleAddress,
+    pub price: Amount,
+    pub epoch: u64,
+}
+
 #[template]
 mod nft_marketplace {
     use super::*;
 
     /// Simple English-like auctions
-    /// The winner needs to claim the nft after the bidding period finishes. For simplicity, no marketplace fees are
-    /// considered. There exist a lot more approaches to auctions, we can highlight:
+    /// The winner needs to claim the nft after the bidding period finishes. A marketplace fee (per thousand of the sale
+    /// price) is paid to the fee recipient, and the seller gets the rest. There exist a lot more approaches to auctions,
+    /// we can highlight:
     ///     - Price descending, dutch-like auctions. The first bidder gets the nft right away, no need to wait or claim
     ///       afterwards
     ///     - Blind auctions, were bids are not known until the end. This requires cryptography support, and implies that
@@ -53,15 +90,26 @@ mod nft_marketplace {
         // address of the account component of the seller
         seller_address: ComponentAddress,
 
+        // resources in which bids can be paid
+        // all the bids must be paid in the resource of the first bid, so they can be compared without price oracles
+        allowed_payments: Vec<ResourceAddress>,
+
         // minimum required price for a bid
         min_price: Option<Amount>,
 
         // price at which the NFT will be sold automatically
         buy_price: Option<Amount>,
 
+        // hidden minimum price: lower bids are accepted, but the NFT is not sold if the auction ends below it
+        reserve_price: Option<Amount>,
+
         // Holds the current highest bidder, it's replaced when a new highest bidder appears
         highest_bid: Option<Bid>,
 
+        // second-price (Vickrey) auctions: the winner only pays the runner-up bid amount
+        vickrey: bool,
+        second_highest: Option<Amount>,
+
         // Time sensitive logic is a big issue, we need custom support for it. I see two options:
         //      1. Ad hoc protocol in the second layer to agree on timestamps (inside of a commitee? globally?)
         //      2. Leverage the base layer block number (~3 minute intervals)
@@ -69,6 +117,31 @@ mod nft_marketplace {
         // We are going with (3) here. But either way this means custom utils and that some external state influences
         // execution
         ending_epoch: u64,
+
+        // every bid placed in the auction as (bidder, amount, epoch), capped to the latest `max_bid_history` entries
+        bid_history: Vec<(ComponentAddress, Amount, u64)>,
+        max_bid_history: usize,
+
+        // set when the auction is settled or cancelled, no further actions are allowed afterwards
+        closed: bool,
+
+        // account that got the NFT when the auction was settled
+        winner: Option<ComponentAddress>,
+
+        // restricts the accounts that can bid (e.g. KYC'd accounts), an empty list means that nobody can bid
+        allowed_bidders: Option<Vec<ComponentAddress>>,
+
+        // the NFT is only sold if at least `min_bidders` distinct accounts placed a bid
+        min_bidders: Option<u32>,
+        bidders: BTreeSet<ComponentAddress>,
+
+        // part of the sale price (per thousand) that goes to the marketplace
+        fee_recipient: ComponentAddress,
+        fee_per_thousand: u16,
+
+        // the winner gets a sale receipt NFT when the auction is settled
+        sale_receipt_resource: ResourceAddress,
+        sale_receipt_id: Option<NonFungibleId>,
     }
 
     impl Auction {
@@ -77,10 +150,22 @@ mod nft_marketplace {
         pub fn new(
             nft_bucket: Bucket,
             seller_address: ComponentAddress,
-            min_price: Option<Amount>,
-            buy_price: Option<Amount>,
-            epoch_period: u64,
+            config: AuctionConfig,
+            max_bid_history: usize,
+            fee_recipient: ComponentAddress,
+            fee_per_thousand: u16,
         ) -> (Component<Auction>, Bucket) {
+            let AuctionConfig {
+                min_price,
+                buy_price,
+                epoch_period,
+                vickrey,
+                allowed_payments,
+                allowed_bidders,
+                min_bidders,
+                reserve_price,
+            } = config;
+
             assert!(
                 nft_bucket.resource_type() == ResourceType::NonFungible,
                 "The resource is not a NFT"
@@ -93,8 +178,21 @@ mod nft_marketplace {
 
             assert!(epoch_period > 0, "Invalid auction period");
 
+            assert!(!allowed_payments.is_empty(), "The auction needs at least one payment resource");
+
+            assert!(fee_per_thousand < 1000, "Invalid fee {}", fee_per_thousand);
+            assert!(is_account(fee_recipient), "Invalid fee recipient account");
+
+            // a buying price under the reserve would sell the NFT below it
+            if let (Some(reserve_price), Some(buy_price)) = (reserve_price, buy_price) {
+                assert!(
+                    reserve_price <= buy_price,
+                    "The reserve price cannot be higher than the buying price"
+                );
+            }
+
             // needed to ensure that we can process the auction payments when it ends
-            Self::assert_component_is_account(seller_address);
+            assert!(is_account(seller_address), "Invalid bidder account");
 
             // create the bucket with the badge to allow the seller to cancel the auction at any time
             // we make sure that only the initial badge will be minted
@@ -104,52 +202,105 @@ mod nft_marketplace {
                 .initial_supply_with_data(Some((NonFungibleId::random(), (&(), &()))));
             let seller_badge_resource = seller_badge_bucket.resource_address();
 
+            // the address of the component is allocated beforehand, so the receipts can be restricted to it
+            let component_alloc = CallerContext::allocate_component_address(None);
+            let component_rule = AccessRule::Restricted(RestrictedAccessRule::Require(RequireRule::Require(
+                RuleRequirement::ScopedToComponent(*component_alloc.address()),
+            )));
+
+            // only this component can mint sale receipts, so they always prove a settled sale
+            let sale_receipt_resource = ResourceBuilder::non_fungible().mintable(component_rule).build();
+
             // initialize the auction component
             let component = Component::new(Self {
                 vault: Vault::from_bucket(nft_bucket),
                 seller_address,
+                allowed_payments,
                 min_price,
                 buy_price,
+                reserve_price,
                 highest_bid: None,
+                vickrey,
+                second_highest: None,
                 ending_epoch: Consensus::current_epoch() + epoch_period,
                 seller_badge_resource,
+                bid_history: vec![],
+                max_bid_history,
+                closed: false,
+                winner: None,
+                allowed_bidders,
+                min_bidders,
+                bidders: BTreeSet::new(),
+                fee_recipient,
+                fee_per_thousand,
+                sale_receipt_resource,
+                sale_receipt_id: None,
             })
             .with_access_rules(AccessRules::allow_all())
+            .with_address_allocation(component_alloc)
             .create();
 
             (component, seller_badge_bucket)
         }
 
         // process a new bid for an ongoing auction
-        pub fn bid(&mut self, bidder_account_address: ComponentAddress, payment: Bucket) {
+        pub fn bid(&mut self, bidder_account_address: ComponentAddress, mut payment: Bucket) {
+            // a cancelled auction keeps the refund of the highest bid, so no new bids can replace it
+            assert!(!self.closed, "Auction is closed");
+
             assert!(
                 Consensus::current_epoch() < self.ending_epoch,
                 "Auction has expired"
             );
 
-            assert_eq!(
-                payment.resource_address(),
-                XTR,
-                "Invalid payment resource, the marketplace only accepts Tari (XTR) tokens"
-            );
+            self.assert_payment_resource(&payment);
+
+            // the NFT and refunds always go to the bidder account, so checking the account is enough
+            if let Some(allowed_bidders) = &self.allowed_bidders {
+                assert!(
+                    allowed_bidders.contains(&bidder_account_address),
+                    "Bidder not allowed"
+                );
+            }
+
+            // outbidding yourself only inflates the activity of the auction and churns refunds
+            if let Some(highest_bid) = &self.highest_bid {
+                assert!(
+                    highest_bid.bidder_account != bidder_account_address,
+                    "You are already the highest bidder"
+                );
+            }
 
             // validate that the bidder account is really an account
             // so we can deposit the refund later if a higher bidder comes
             // otherwise an attacker could block newer higher bids
-            Self::assert_component_is_account(bidder_account_address);
+            assert!(is_account(bidder_account_address), "Invalid bidder account");
 
             // check that the minimum price (if set) is met
-            let payment_amount = payment.amount();
+            let mut payment_amount = payment.amount();
             if let Some(min_price) = self.min_price {
                 assert!(payment_amount >= min_price, "Minimum price not met");
             }
 
+            // a payment above the buying price (if set) buys the NFT, so we refund the overpayment
+            if let Some(buy_price) = self.buy_price {
+                if payment_amount > buy_price {
+                    let overpayment_bucket = payment.take(payment_amount - buy_price);
+                    ComponentManager::get(bidder_account_address)
+                        .call::<_, ()>("deposit".to_string(), args![overpayment_bucket]);
+                    payment_amount = buy_price;
+                }
+            }
+
             // immediatly refund the previous highest bidder if there is one
             if let Some(highest_bid) = &mut self.highest_bid {
                 assert!(
                     payment_amount > highest_bid.vault.balance(),
                     "There is a higher bid placed"
                 );
+                // the previous highest bid is now the runner-up
+                self.second_highest = Some(highest_bid.vault.balance());
+
                 let previous_bidder_account = ComponentManager::get(highest_bid.bidder_account);
                 let refund_bucket = highest_bid.vault.withdraw_all();
                 // TODO: improve call method generics when there is no return value
@@ -157,47 +308,105 @@ mod nft_marketplace {
 
                 // update the highest bidder in the auction
                 highest_bid.bidder_account = bidder_account_address;
-                highest_bid.vault.deposit(payment.clone());
+                highest_bid.vault.deposit(payment);
             } else {
                 // the bidder is the first one to place a bid
                 let highest_bid = Bid {
                     bidder_account: bidder_account_address,
-                    vault: Vault::from_bucket(payment.clone()),
+                    vault: Vault::from_bucket(payment),
                 };
                 self.highest_bid = Some(highest_bid);
             }
 
+            self.record_bid(bidder_account_address, payment_amount);
+
             // if the bid meets the buying price, we process the sell immediatly
+            if self.buy_price == Some(payment_amount) {
+                self.process_payments();
+            }
+        }
+
+        // adds funds to the current highest bid, avoiding the refund of the whole previous bid
+        // the bid belongs to the bidder account, so it does not matter who provides the additional funds
+        pub fn increase_bid(&mut self, bidder_account_address: ComponentAddress, mut additional_payment: Bucket) {
+            assert!(!self.closed, "Auction is closed");
+
+            assert!(
+                Consensus::current_epoch() < self.ending_epoch,
+                "Auction has expired"
+            );
+
+            self.assert_payment_resource(&additional_payment);
+            assert!(additional_payment.amount().is_positive(), "Invalid bid increase");
+
+            let highest_bid = self
+                .highest_bid
+                .as_mut()
+                .filter(|bid| bid.bidder_account == bidder_account_address)
+                .unwrap_or_else(|| panic!("Only the highest bidder can increase the bid"));
+
+            // as with regular bids, reaching the buying price (if set) buys the NFT
+            let mut bid_amount = highest_bid.vault.balance() + additional_payment.amount();
             if let Some(buy_price) = self.buy_price {
-                assert!(
-                    payment_amount <= buy_price,
-                    "Payment exceeds the buying price"
-                );
-                if payment_amount == buy_price {
-                    self.process_payments();
+                if bid_amount > buy_price {
+                    let overpayment_bucket = additional_payment.take(bid_amount - buy_price);
+                    ComponentManager::get(bidder_account_address)
+                        .call::<_, ()>("deposit".to_string(), args![overpayment_bucket]);
+                    bid_amount = buy_price;
                 }
             }
+            highest_bid.vault.deposit(additional_payment);
+
+            self.record_bid(bidder_account_address, bid_amount);
+
+            if self.buy_price == Some(bid_amount) {
+                self.process_payments();
+            }
         }
 
         // finish the auction by sending the NFT and payment to the respective accounts
         // used by a bid seller to receive the bid payment, or by the buyer to get the NFT, whatever happens first
         pub fn finish(&mut self) {
+            // the payments can only be processed once
+            assert!(!self.closed, "Auction already settled");
+
             assert!(
                 Consensus::current_epoch() >= self.ending_epoch,
                 "Auction is still in progress"
             );
 
+            // without enough competition the sale is called off
+            let min_bidders_met = self
+                .min_bidders
+                .map_or(true, |min_bidders| self.get_bidder_count() >= min_bidders);
+            if !min_bidders_met {
+                self.return_to_seller();
+                return;
+            }
+
+            // the top bid did not reach the reserve, so off-chain systems are notified that there was no sale
+            if let (Some(reserve_price), Some(top_bid)) = (self.reserve_price, self.get_highest_bid()) {
+                if top_bid < reserve_price {
+                    let mut event_payload = Metadata::new();
+                    event_payload.insert("top_bid".to_string(), top_bid.to_string());
+                    event_payload.insert("reserve_price".to_string(), reserve_price.to_string());
+                    emit_event("reserve_not_met", event_payload);
+
+                    self.return_to_seller();
+                    return;
+                }
+            }
+
             self.process_payments();
         }
 
         // the seller wants to cancel the auction
         pub fn cancel(&mut self, seller_badge_bucket: Bucket) {
-            // as the seller badge resource cannot be minted and only one token exist,
-            // we only need to check that the resource address matches
-            assert!(
-                seller_badge_bucket.resource_address() == self.seller_badge_resource,
-                "Invalid seller badge"
-            );
+            self.assert_seller_badge(&seller_badge_bucket);
+
+            // cancelling burns the badge, so a closed auction here was settled (e.g. by a buying price bid before the
+            // ending epoch) and the NFT is no longer in the vault
+            assert!(!self.closed, "Auction already settled");
 
             // an auction cannot be cancelled if it has ended
             assert!(
@@ -205,16 +414,9 @@ mod nft_marketplace {
                 "Auction has ended"
             );
 
-            // we are canceling the bid
-            // so we need to pay back the highest bidded (if there's one)
-            if let Some(highest_bid) = &mut self.highest_bid {
-                let bidder_account = ComponentManager::get(highest_bid.bidder_account);
-                let refund_bucket = highest_bid.vault.withdraw_all();
-                bidder_account.call::<_, ()>("deposit".to_string(), args![refund_bucket]);
-                // TODO: removing the bid ends up in a OrphanedSubstate error in the
-                //       but we need to mark that the auction is finished somehow to prevent new bids
-                // self.highest_bid = None;
-            }
+            // we are canceling the bid, so the highest bidder (if there's one) must be paid back
+            // the refund is kept in the bid vault to be claimed later with "claim_cancelled_refund", as pushing it
+            // into the bidder account could fail and the seller would never be able to get the NFT back
 
             // burn the seller token to prevent it from being used again, as it has no more purpose
             seller_badge_bucket.burn();
@@ -223,35 +425,266 @@ mod nft_marketplace {
             let seller_account = ComponentManager::get(self.seller_address);
             let nft_bucket = self.vault.withdraw_all();
             seller_account.call::<_, ()>("deposit".to_string(), args![nft_bucket]);
+
+            self.closed = true;
         }
 
-        fn assert_component_is_account(component_address: ComponentAddress) {
-            let component = ComponentManager::get(component_address);
+        // sends the bid of a cancelled auction back to the bidder
+        pub fn claim_cancelled_refund(&mut self, bidder_account_address: ComponentAddress) {
+            assert!(self.closed, "Auction is still open");
+
+            let highest_bid = self
+                .highest_bid
+                .as_mut()
+                .filter(|bid| bid.bidder_account == bidder_account_address)
+                .unwrap_or_else(|| panic!("There is no refund for {}", bidder_account_address));
+
+            // settled auctions always leave the bid vault empty
+            assert!(!highest_bid.vault.balance().is_zero(), "There is no refund to claim");
+
+            let refund_bucket = highest_bid.vault.withdraw_all();
+            ComponentManager::get(bidder_account_address).call::<_, ()>("deposit".to_string(), args![refund_bucket]);
+        }
+
+        // once the auction is closed the seller badge has no more purpose, so the seller can get rid of it
+        pub fn redeem_seller_badge(&mut self, seller_badge_bucket: Bucket) {
+            self.assert_seller_badge(&seller_badge_bucket);
+
+            assert!(self.closed, "Auction is still open");
+
+            seller_badge_bucket.burn();
+        }
+
+        // the seller wants more time for bidding
+        // the badge is returned to the caller, as it's still needed to cancel the auction
+        pub fn extend(&mut self, seller_badge_bucket: Bucket, extra_epochs: u64) -> Bucket {
+            self.assert_seller_badge(&seller_badge_bucket);
+
+            assert!(!self.closed, "Auction is closed");
+
+            // an auction cannot be extended if it has ended
             assert!(
-                component.get_template_address() == ACCOUNT_TEMPLATE_ADDRESS,
-                "Invalid bidder account"
+                Consensus::current_epoch() < self.ending_epoch,
+                "Auction has ended"
             );
+
+            assert!(extra_epochs > 0, "Invalid extension period");
+
+            self.ending_epoch += extra_epochs;
+
+            seller_badge_bucket
+        }
+
+        // adds (or updates) the instant-buy option of a live auction
+        // the badge is returned to the caller, as it's still needed to cancel the auction
+        pub fn set_buy_price(&mut self, seller_badge_bucket: Bucket, buy_price: Amount) -> Bucket {
+            self.assert_seller_badge(&seller_badge_bucket);
+
+            assert!(!self.closed, "Auction is closed");
+            assert!(
+                Consensus::current_epoch() < self.ending_epoch,
+                "Auction has ended"
+            );
+
+            // new bids must be higher than the current one, so a lower buying price could never be paid
+            if let Some(highest_bid) = &self.highest_bid {
+                assert!(
+                    buy_price > highest_bid.vault.balance(),
+                    "The buying price must be higher than the highest bid"
+                );
+            }
+
+            self.buy_price = Some(buy_price);
+
+            seller_badge_bucket
+        }
+
+        // convenience method for UIs to show countdowns, returns 0 once the auction has expired
+        pub fn time_remaining(&self) -> u64 {
+            self.ending_epoch.saturating_sub(Consensus::current_epoch())
+        }
+
+        pub fn has_ended(&self) -> bool {
+            Consensus::current_epoch() >= self.ending_epoch
+        }
+
+        // true when the auction has been settled or cancelled
+        pub fn is_closed(&self) -> bool {
+            self.closed
+        }
+
+        // the resource of the badge that the seller needs to cancel or extend the auction
+        pub fn get_seller_badge_resource(&self) -> ResourceAddress {
+            self.seller_badge_resource
+        }
+
+        // lowest amount that a new bid must pay, None if the auction is not accepting bids anymore
+        pub fn get_min_next_bid(&self) -> Option<Amount> {
+            if self.closed || self.has_ended() {
+                return None;
+            }
+
+            match &self.highest_bid {
+                Some(highest_bid) => Some(highest_bid.vault.balance() + Amount(1)),
+                None => Some(self.min_price.unwrap_or(Amount(1))),
+            }
+        }
+
+        // None until the auction is settled with a sale
+        pub fn get_winner(&self) -> Option<ComponentAddress> {
+            self.winner
+        }
+
+        // amount locked in the current highest bid, None if there are no bids
+        pub fn get_highest_bid(&self) -> Option<Amount> {
+            self.highest_bid.as_ref().map(|bid| bid.vault.balance())
+        }
+
+        pub fn get_auction_info(&self) -> AuctionInfo {
+            AuctionInfo {
+                seller: self.seller_address,
+                min_price: self.min_price,
+                buy_price: self.buy_price,
+                highest_bidder: self.highest_bid.as_ref().map(|bid| bid.bidder_account),
+                highest_bid: self.highest_bid.as_ref().map(|bid| bid.vault.balance()),
+                ending_epoch: self.ending_epoch,
+                closed: self.closed,
+            }
+        }
+
+        // amount of distinct accounts that placed a bid
+        pub fn get_bidder_count(&self) -> u32 {
+            self.bidders.len() as u32
+        }
+
+        pub fn get_bid_history(&self) -> Vec<(ComponentAddress, Amount, u64)> {
+            self.bid_history.clone()
+        }
+
+        pub fn get_sale_receipt_resource(&self) -> ResourceAddress {
+            self.sale_receipt_resource
+        }
+
+        // data of the receipt minted to the winner, None if the auction was not settled with a sale
+        pub fn get_sale_receipt(&self) -> Option<SaleReceipt> {
+            self.sale_receipt_id.as_ref().map(|receipt_id| {
+                ResourceManager::get(self.sale_receipt_resource)
+                    .get_non_fungible(receipt_id)
+                    .get_data()
+            })
+        }
+
+        pub fn get_allowed_payments(&self) -> Vec<ResourceAddress> {
+            self.allowed_payments.clone()
+        }
+
+        fn assert_seller_badge(&self, seller_badge_bucket: &Bucket) {
+            // as the seller badge resource cannot be minted and only one token exist,
+            // we only need to check that the resource address matches
+            assert!(
+                seller_badge_bucket.resource_address() == self.seller_badge_resource,
+                "Invalid seller badge"
+            );
+        }
+
+        fn assert_payment_resource(&self, payment: &Bucket) {
+            let payment_resource = payment.resource_address();
+            assert!(
+                self.allowed_payments.contains(&payment_resource),
+                "Invalid payment resource, the auction does not accept {} tokens",
+                payment_resource
+            );
+
+            // the vault of the highest bid keeps the resource of the first bid, even after refunds
+            if let Some(highest_bid) = &self.highest_bid {
+                assert!(
+                    payment_resource == highest_bid.vault.resource_address(),
+                    "Bids must be paid in {}, the resource of the first bid",
+                    highest_bid.vault.resource_address()
+                );
+            }
+        }
+
+        // keep track of the bid, dropping the oldest entries to bound the substate size
+        fn record_bid(&mut self, bidder_account_address: ComponentAddress, amount: Amount) {
+            self.bid_history
+                .push((bidder_account_address, amount, Consensus::current_epoch()));
+            while self.bid_history.len() > self.max_bid_history {
+                self.bid_history.remove(0);
+            }
+            self.bidders.insert(bidder_account_address);
         }
 
         // this method MUST ALWAYS be private, to prevent auction cancellation by unauthorized third parties
         fn process_payments(&mut self) {
             let seller_account = ComponentManager::get(self.seller_address);
             let nft_bucket = self.vault.withdraw_all();
+            let nft_id = nft_bucket.get_non_fungible_ids()[0].clone();
+            let nft = NonFungibleAddress::new(nft_bucket.resource_address(), nft_id);
 
             if let Some(highest_bid) = &mut self.highest_bid {
                 // deposit the nft to the bidder
                 let bidder_account = ComponentManager::get(highest_bid.bidder_account);
                 bidder_account.call::<_, ()>("deposit".to_string(), args![nft_bucket]);
 
-                // deposit the funds to the seller
-                let payment = highest_bid.vault.withdraw_all();
+                // in second-price auctions the winner pays the runner-up bid (or the minimum price if there was a
+                // single bid), so we refund the difference. An instant sale at the buying price is not affected.
+                let winning_amount = highest_bid.vault.balance();
+                let is_buy_price_sale = self.buy_price == Some(winning_amount);
+                if self.vickrey && !is_buy_price_sale {
+                    if let Some(price) = self.second_highest.or(self.min_price) {
+                        if price < winning_amount {
+                            let refund_bucket = highest_bid.vault.withdraw(winning_amount - price);
+                            bidder_account.call::<_, ()>("deposit".to_string(), args![refund_bucket]);
+                        }
+                    }
+                }
+
+                // deposit the funds to the seller, minus the marketplace fee
+                let mut payment = highest_bid.vault.withdraw_all();
+                let price = payment.amount();
+                let fee = price * Amount::new(self.fee_per_thousand as i64) / Amount::new(1000);
+                if fee.is_positive() {
+                    let fee_bucket = payment.take(fee);
+                    ComponentManager::get(self.fee_recipient).call::<_, ()>("deposit".to_string(), args![fee_bucket]);
+                }
                 seller_account.call::<_, ()>("deposit".to_string(), args![payment]);
+
+                // mint the sale receipt to the winner
+                let receipt = SaleReceipt {
+                    nft,
+                    price,
+                    epoch: Consensus::current_epoch(),
+                };
+                let receipt_id = NonFungibleId::random();
+                let receipt_bucket = ResourceManager::get(self.sale_receipt_resource)
+                    .mint_non_fungible(receipt_id.clone(), &receipt, &());
+                bidder_account.call::<_, ()>("deposit".to_string(), args![receipt_bucket]);
+                self.sale_receipt_id = Some(receipt_id);
+
+                self.winner = Some(highest_bid.bidder_account);
             } else {
                 // no bidders in the auction, so just return the NFT to the seller
                 seller_account.call::<_, ()>("deposit".to_string(), args![nft_bucket]);
             }
 
-            // TODO: burn the seller badge to avoid it being used again (should we recall it first?)
+            // the seller badge can now be burned with "redeem_seller_badge"
+            self.closed = true;
+        }
+
+        // returns the NFT to the seller and the highest bid (if any) to its bidder, without any sale
+        fn return_to_seller(&mut self) {
+            let seller_account = ComponentManager::get(self.seller_address);
+            let nft_bucket = self.vault.withdraw_all();
+            seller_account.call::<_, ()>("deposit".to_string(), args![nft_bucket]);
+
+            if let Some(highest_bid) = &mut self.highest_bid {
+                let refund_bucket = highest_bid.vault.withdraw_all();
+                ComponentManager::get(highest_bid.bidder_account)
+                    .call::<_, ()>("deposit".to_string(), args![refund_bucket]);
+            }
+
+            self.closed = true;
         }
     }
 }
@@ -20,8 +20,41 @@
 //   WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //   USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use account_utils::is_account;
 use tari_template_lib::prelude::*;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct AuctionPrices {
+    min_price: Option<Amount>,
+    buy_price: Option<Amount>,
+}
+
+// mirrors the "AuctionInfo" struct returned by the auction template
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct AuctionInfo {
+    pub seller: ComponentAddress,
+    pub min_price: Option<Amount>,
+    pub buy_price: Option<Amount>,
+    pub highest_bidder: Option<ComponentAddress>,
+    pub highest_bid: Option<Amount>,
+    pub ending_epoch: u64,
+    pub closed: bool,
+}
+
+// mirrors the "AuctionConfig" struct expected by the auction template
+// an empty list of allowed payments defaults to the payment resource of the index
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct AuctionConfig {
+    pub min_price: Option<Amount>,
+    pub buy_price: Option<Amount>,
+    pub epoch_period: u64,
+    pub vickrey: bool,
+    pub allowed_payments: Vec<ResourceAddress>,
+    pub allowed_bidders: Option<Vec<ComponentAddress>>,
+    pub min_bidders: Option<u32>,
+    pub reserve_price: Option<Amount>,
+}
 
 #[template]
 mod nft_marketplace_index {
@@ -29,52 +62,471 @@ mod nft_marketplace_index {
 
     pub struct AuctionIndex {
         auction_template: TemplateAddress,
+        // default resource in which the bids of the auctions of the index must be paid
+        accepted_payment: ResourceAddress,
         auctions: BTreeMap<u64, Vec<ComponentAddress>>,
+        // reverse lookup of auctions by the NFT resource being sold
+        by_resource: BTreeMap<ResourceAddress, Vec<ComponentAddress>>,
+        // auctions created by each seller account
+        by_seller: BTreeMap<ComponentAddress, Vec<ComponentAddress>>,
+        // price bounds of each auction, used for filtering
+        prices: BTreeMap<ComponentAddress, AuctionPrices>,
+        // seller badges of the indexed auctions, which cannot be auctioned themselves
+        seller_badge_resources: BTreeSet<ResourceAddress>,
+        // maximum amount of bids that each auction keeps in its history
+        max_bid_history: usize,
+        // bounds (inclusive) of the auction periods allowed in the index
+        min_period: u64,
+        max_period: u64,
+        // account that receives the marketplace fee of every sale, as a per thousand amount of the sale price
+        fee_recipient: ComponentAddress,
+        fee_per_thousand: u16,
     }
 
     impl AuctionIndex {
-        pub fn new(auction_template: TemplateAddress) -> Self {
+        pub fn new(
+            auction_template: TemplateAddress,
+            accepted_payment: ResourceAddress,
+            max_bid_history: usize,
+            min_period: u64,
+            max_period: u64,
+            fee_recipient: ComponentAddress,
+            fee_per_thousand: u16,
+        ) -> Self {
+            assert!(accepted_payment != ResourceAddress::default(), "Invalid payment resource");
+            assert!(min_period <= max_period, "Invalid auction period bounds");
+            assert!(fee_per_thousand < 1000, "Invalid fee {}", fee_per_thousand);
+
+            // the fees are deposited on each sale, so the recipient must be able to receive them
+            assert!(is_account(fee_recipient), "Invalid fee recipient account");
+
             Self {
                 auction_template,
-                auctions: BTreeMap::new()
+                accepted_payment,
+                auctions: BTreeMap::new(),
+                by_resource: BTreeMap::new(),
+                by_seller: BTreeMap::new(),
+                prices: BTreeMap::new(),
+                seller_badge_resources: BTreeSet::new(),
+                max_bid_history,
+                min_period,
+                max_period,
+                fee_recipient,
+                fee_per_thousand,
             }
         }
 
+        pub fn get_accepted_payment(&self) -> ResourceAddress {
+            self.accepted_payment
+        }
+
         // convenience method for external APIs and interfaces
-        // TODO: support for advanced filtering (price ranges, etc.) could be desirable
         pub fn get_auctions(&self) -> BTreeMap<u64, Vec<ComponentAddress>> {
             self.auctions.clone()
         }
 
+        // returns at most `limit` auctions, ordered by ending epoch, starting from `start_epoch`
+        pub fn get_auctions_page(&self, start_epoch: u64, limit: usize) -> Vec<(u64, ComponentAddress)> {
+            self.auctions
+                .range(start_epoch..)
+                .flat_map(|(ending_epoch, auctions)| auctions.iter().map(move |auction| (*ending_epoch, *auction)))
+                .take(limit)
+                .collect()
+        }
+
+        // skips the auctions whose ending epoch has already been reached at `current_epoch`
+        pub fn get_auctions_ending_after(&self, current_epoch: u64) -> BTreeMap<u64, Vec<ComponentAddress>> {
+            self.auctions
+                .range(current_epoch + 1..)
+                .map(|(ending_epoch, auctions)| (*ending_epoch, auctions.clone()))
+                .collect()
+        }
+
+        // returns the auctions that are still open and accepting bids, as reported by the auction components
+        // this cross-calls every indexed auction (O(n)), so the amount of results can be limited
+        pub fn get_active_auctions(&self, limit: Option<usize>) -> Vec<ComponentAddress> {
+            let limit = limit.unwrap_or(usize::MAX);
+            let mut active_auctions = vec![];
+
+            for auction in self.auctions.values().flatten() {
+                if active_auctions.len() >= limit {
+                    break;
+                }
+
+                let auction_component = ComponentManager::get(*auction);
+                let has_ended: bool = auction_component.call("has_ended".to_string(), args![]);
+                let is_closed: bool = auction_component.call("is_closed".to_string(), args![]);
+                if !has_ended && !is_closed {
+                    active_auctions.push(*auction);
+                }
+            }
+
+            active_auctions
+        }
+
+        // lets clients quote a valid bid with only the index and auction addresses
+        // returns None for auctions that are not in the index or that are not accepting bids anymore
+        pub fn get_min_next_bid_for(&self, auction: ComponentAddress) -> Option<Amount> {
+            if !self.prices.contains_key(&auction) {
+                return None;
+            }
+
+            ComponentManager::get(auction).call("get_min_next_bid".to_string(), args![])
+        }
+
+        // sum of the highest bids locked in the open auctions of the index
+        // this cross-calls every indexed auction (O(n)), so the amount of auctions visited can be limited
+        pub fn get_total_locked_value(&self, limit: Option<usize>) -> Amount {
+            let limit = limit.unwrap_or(usize::MAX);
+
+            self.auctions
+                .values()
+                .flatten()
+                .take(limit)
+                .filter_map(|auction| {
+                    let auction_component = ComponentManager::get(*auction);
+                    let is_closed: bool = auction_component.call("is_closed".to_string(), args![]);
+                    if is_closed {
+                        return None;
+                    }
+                    auction_component.call::<_, Option<Amount>>("get_highest_bid".to_string(), args![])
+                })
+                .fold(Amount::zero(), |total, amount| total + amount)
+        }
+
+        // settles up to `limit` auctions ending at or before `current_epoch`, so keepers can use a single transaction
+        // auctions that are already closed, or that were extended and are still live, are skipped
+        // returns the amount of auctions settled
+        pub fn finish_expired(&mut self, current_epoch: u64, limit: usize) -> u64 {
+            assert!(
+                current_epoch <= Consensus::current_epoch(),
+                "The epoch {} is in the future",
+                current_epoch
+            );
+
+            let mut settled = 0;
+            for auction in self.auctions.range(..=current_epoch).flat_map(|(_, auctions)| auctions) {
+                if settled >= limit {
+                    break;
+                }
+
+                let auction_component = ComponentManager::get(*auction);
+                let has_ended: bool = auction_component.call("has_ended".to_string(), args![]);
+                let is_closed: bool = auction_component.call("is_closed".to_string(), args![]);
+                if !has_ended || is_closed {
+                    continue;
+                }
+                auction_component.call::<_, ()>("finish".to_string(), args![]);
+                settled += 1;
+            }
+
+            settled as u64
+        }
+
+        // returns the auctions whose ending epoch is strictly lower than `epoch`, e.g. the ones about to end
+        pub fn get_auctions_ending_before(&self, epoch: u64) -> BTreeMap<u64, Vec<ComponentAddress>> {
+            self.auctions
+                .range(..epoch)
+                .map(|(ending_epoch, auctions)| (*ending_epoch, auctions.clone()))
+                .collect()
+        }
+
+        pub fn get_auctions_for_resource(&self, resource: ResourceAddress) -> Vec<ComponentAddress> {
+            self.by_resource.get(&resource).cloned().unwrap_or_default()
+        }
+
+        // sellers may have lots of auctions, so the amount of results can be limited
+        pub fn get_auctions_by_seller(&self, seller: ComponentAddress, limit: Option<usize>) -> Vec<ComponentAddress> {
+            let auctions = self.by_seller.get(&seller).map(|a| a.as_slice()).unwrap_or_default();
+            let limit = limit.unwrap_or(auctions.len());
+            auctions.iter().take(limit).copied().collect()
+        }
+
+        // returns the auctions with a minimum price or buying price inside the (inclusive) range
+        // auctions without any price bounds are only returned if `include_unpriced` is set
+        pub fn get_auctions_in_price_range(
+            &self,
+            min: Amount,
+            max: Amount,
+            include_unpriced: bool,
+        ) -> Vec<ComponentAddress> {
+            let in_range = |price: Option<Amount>| price.map_or(false, |p| p >= min && p <= max);
+
+            self.prices
+                .iter()
+                .filter(|(_, prices)| {
+                    let is_unpriced = prices.min_price.is_none() && prices.buy_price.is_none();
+                    if is_unpriced {
+                        include_unpriced
+                    } else {
+                        in_range(prices.min_price) || in_range(prices.buy_price)
+                    }
+                })
+                .map(|(auction, _)| *auction)
+                .collect()
+        }
+
+        // extends an indexed auction, keeping its ending epoch in the index up to date
+        // the badge is returned to the caller, as it's still needed to cancel the auction
+        pub fn extend_auction(
+            &mut self,
+            auction: ComponentAddress,
+            seller_badge_bucket: Bucket,
+            extra_epochs: u64,
+        ) -> Bucket {
+            self.assert_indexed_auction(auction);
+            let seller_badge_bucket: Bucket = ComponentManager::get(auction)
+                .call("extend".to_string(), args![seller_badge_bucket, extra_epochs]);
+            self.refresh_auction(auction);
+
+            seller_badge_bucket
+        }
+
+        // sets the buying price of an indexed auction, keeping its price bounds in the index up to date
+        // the badge is returned to the caller, as it's still needed to cancel the auction
+        pub fn set_auction_buy_price(
+            &mut self,
+            auction: ComponentAddress,
+            seller_badge_bucket: Bucket,
+            buy_price: Amount,
+        ) -> Bucket {
+            self.assert_indexed_auction(auction);
+            let seller_badge_bucket: Bucket = ComponentManager::get(auction)
+                .call("set_buy_price".to_string(), args![seller_badge_bucket, buy_price]);
+            self.refresh_auction(auction);
+
+            seller_badge_bucket
+        }
+
+        // syncs the ending epoch and the prices of an indexed auction with the auction component
+        // sellers can also update their auctions directly, so anyone is allowed to refresh them
+        pub fn refresh_auction(&mut self, auction: ComponentAddress) {
+            self.assert_indexed_auction(auction);
+            let info: AuctionInfo = ComponentManager::get(auction).call("get_auction_info".to_string(), args![]);
+
+            let previous_epoch = self
+                .auctions
+                .iter()
+                .find(|(_, auctions)| auctions.contains(&auction))
+                .map(|(ending_epoch, _)| *ending_epoch);
+            if previous_epoch != Some(info.ending_epoch) {
+                if let Some(previous_epoch) = previous_epoch {
+                    let auctions = self.auctions.get_mut(&previous_epoch).unwrap();
+                    auctions.retain(|a| *a != auction);
+                    if auctions.is_empty() {
+                        self.auctions.remove(&previous_epoch);
+                    }
+                }
+                self.auctions.entry(info.ending_epoch).or_default().push(auction);
+            }
+
+            self.prices.insert(auction, AuctionPrices {
+                min_price: info.min_price,
+                buy_price: info.buy_price,
+            });
+
+            // notify off-chain indexers about the new values
+            let mut event_payload = Metadata::new();
+            event_payload.insert("auction_component".to_string(), auction.to_string());
+            event_payload.insert("ending_epoch".to_string(), info.ending_epoch.to_string());
+            emit_event("auction_updated", event_payload);
+        }
+
+        // removes a settled or cancelled auction from the index
+        pub fn remove_auction(&mut self, ending_epoch: u64, auction: ComponentAddress) {
+            let auctions = self
+                .auctions
+                .get_mut(&ending_epoch)
+                .unwrap_or_else(|| panic!("There are no auctions ending at epoch {}", ending_epoch));
+            let position = auctions
+                .iter()
+                .position(|a| *a == auction)
+                .unwrap_or_else(|| panic!("Auction {} is not in the index", auction));
+
+            // live auctions must not be pruned by third parties
+            let is_closed: bool = ComponentManager::get(auction).call("is_closed".to_string(), args![]);
+            assert!(is_closed, "The auction is still open");
+
+            auctions.remove(position);
+            if auctions.is_empty() {
+                self.auctions.remove(&ending_epoch);
+            }
+
+            for resource_auctions in self.by_resource.values_mut() {
+                resource_auctions.retain(|a| *a != auction);
+            }
+            self.by_resource.retain(|_, resource_auctions| !resource_auctions.is_empty());
+
+            for seller_auctions in self.by_seller.values_mut() {
+                seller_auctions.retain(|a| *a != auction);
+            }
+            self.by_seller.retain(|_, seller_auctions| !seller_auctions.is_empty());
+
+            self.prices.remove(&auction);
+        }
+
         // returns a badge used to cancel the sell order in the future
         // the badge will contain immutable metadata referencing the nft being sold
         pub fn create_auction(
             &mut self,
             nft_bucket: Bucket,
             seller_address: ComponentAddress,
-            min_price: Option<Amount>,
-            buy_price: Option<Amount>,
-            epoch_period: u64,
-        ) -> (ComponentAddress, Bucket) {
+            mut config: AuctionConfig,
+        ) -> (ComponentAddress, Bucket, u64) {
+            self.assert_valid_period(config.epoch_period);
+
+            // auctions can accept other currencies, but by default bids are paid in the index resource
+            if config.allowed_payments.is_empty() {
+                config.allowed_payments = vec![self.accepted_payment];
+            }
+
+            // the bucket is moved into the auction, so we need to get the resource beforehand
+            let nft_resource = nft_bucket.resource_address();
+            self.assert_not_seller_badge(nft_resource);
+
+            // the config is moved into the auction, so we keep the values needed by the index beforehand
+            let (min_price, buy_price) = (config.min_price, config.buy_price);
+            let ending_epoch = Consensus::current_epoch() + config.epoch_period;
+
             // init the auction component
             let (auction_component, seller_badge): (ComponentAddress, Bucket) = TemplateManager::get(self.auction_template)
                 .call("new".to_string(), args![
                     nft_bucket,
                     seller_address,
-                    min_price,
-                    buy_price,
-                    epoch_period
+                    config,
+                    self.max_bid_history,
+                    self.fee_recipient,
+                    self.fee_per_thousand
                 ]);
 
-            // add the new auction component to the index
+            self.register_auction(
+                auction_component,
+                nft_resource,
+                seller_badge.resource_address(),
+                seller_address,
+                min_price,
+                buy_price,
+                ending_epoch,
+            );
+
+            (auction_component, seller_badge, ending_epoch)
+        }
+
+        // creates one auction for each NFT in the bucket, all of them sharing the same prices and period
+        // returns the auction components with their corresponding seller badges
+        pub fn create_auctions_batch(
+            &mut self,
+            mut nft_bucket: Bucket,
+            seller_address: ComponentAddress,
+            min_price: Option<Amount>,
+            buy_price: Option<Amount>,
+            epoch_period: u64,
+        ) -> Vec<(ComponentAddress, Bucket)> {
+            assert!(
+                nft_bucket.resource_type() == ResourceType::NonFungible,
+                "The resource is not a NFT"
+            );
+            assert!(!nft_bucket.amount().is_zero(), "The bucket is empty");
+            self.assert_valid_period(epoch_period);
+
+            let nft_resource = nft_bucket.resource_address();
+            self.assert_not_seller_badge(nft_resource);
             let ending_epoch = Consensus::current_epoch() + epoch_period;
+            let config = AuctionConfig {
+                min_price,
+                buy_price,
+                epoch_period,
+                vickrey: false,
+                allowed_payments: vec![self.accepted_payment],
+                allowed_bidders: None,
+                min_bidders: None,
+                reserve_price: None,
+            };
+
+            let mut results = vec![];
+            while !nft_bucket.amount().is_zero() {
+                let single_nft_bucket = nft_bucket.take(Amount(1));
+                let (auction_component, seller_badge): (ComponentAddress, Bucket) =
+                    TemplateManager::get(self.auction_template).call("new".to_string(), args![
+                        single_nft_bucket,
+                        seller_address,
+                        config.clone(),
+                        self.max_bid_history,
+                        self.fee_recipient,
+                        self.fee_per_thousand
+                    ]);
+                self.register_auction(
+                    auction_component,
+                    nft_resource,
+                    seller_badge.resource_address(),
+                    seller_address,
+                    min_price,
+                    buy_price,
+                    ending_epoch,
+                );
+                results.push((auction_component, seller_badge));
+            }
+
+            results
+        }
+
+        fn assert_valid_period(&self, epoch_period: u64) {
+            assert!(
+                epoch_period >= self.min_period,
+                "The auction period must be at least {} epochs",
+                self.min_period
+            );
+            assert!(
+                epoch_period <= self.max_period,
+                "The auction period must be at most {} epochs",
+                self.max_period
+            );
+        }
+
+        fn assert_indexed_auction(&self, auction: ComponentAddress) {
+            assert!(self.prices.contains_key(&auction), "Auction {} is not in the index", auction);
+        }
+
+        // auctioning a seller badge would hand over the control of another auction (e.g. its cancellation)
+        fn assert_not_seller_badge(&self, nft_resource: ResourceAddress) {
+            assert!(
+                !self.seller_badge_resources.contains(&nft_resource),
+                "Seller badges cannot be auctioned"
+            );
+        }
+
+        // adds a new auction component to all the lookups of the index
+        fn register_auction(
+            &mut self,
+            auction_component: ComponentAddress,
+            nft_resource: ResourceAddress,
+            seller_badge_resource: ResourceAddress,
+            seller_address: ComponentAddress,
+            min_price: Option<Amount>,
+            buy_price: Option<Amount>,
+            ending_epoch: u64,
+        ) {
             if let Some(auctions) = self.auctions.get_mut(&ending_epoch) {
                 auctions.push(auction_component);
             } else {
                 self.auctions.insert(ending_epoch, vec![auction_component]);
             }
-            
-            (auction_component, seller_badge)
+            self.by_resource
+                .entry(nft_resource)
+                .or_default()
+                .push(auction_component);
+            self.by_seller
+                .entry(seller_address)
+                .or_default()
+                .push(auction_component);
+            self.prices.insert(auction_component, AuctionPrices { min_price, buy_price });
+            self.seller_badge_resources.insert(seller_badge_resource);
+
+            // notify off-chain indexers about the new auction
+            let mut event_payload = Metadata::new();
+            event_payload.insert("auction_component".to_string(), auction_component.to_string());
+            event_payload.insert("ending_epoch".to_string(), ending_epoch.to_string());
+            emit_event("auction_created", event_payload);
         }
     }
 }
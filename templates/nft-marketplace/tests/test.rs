@@ -1,4 +1,7 @@
+use std::collections::BTreeMap;
+
 use tari_template_lib::args;
+use tari_template_lib::args::Arg;
 use tari_template_lib::models::{Bucket, ComponentAddress, NonFungibleAddress, ResourceAddress};
 use tari_template_lib::prelude::Amount;
 use tari_template_lib::prelude::Metadata;
@@ -12,6 +15,46 @@ use tari_engine_types::virtual_substate::{VirtualSubstate, VirtualSubstateId};
 
 use tari_template_lib::constants::XTR;
 
+// mirrors the "AuctionInfo" struct returned by the auction template
+#[derive(Debug, serde::Deserialize)]
+struct AuctionInfo {
+    seller: ComponentAddress,
+    min_price: Option<Amount>,
+    buy_price: Option<Amount>,
+    highest_bidder: Option<ComponentAddress>,
+    highest_bid: Option<Amount>,
+    ending_epoch: u64,
+    closed: bool,
+}
+
+// mirrors the "AuctionConfig" struct expected by the index and the auction templates
+#[derive(Debug, Clone, serde::Serialize)]
+struct AuctionConfig {
+    min_price: Option<Amount>,
+    buy_price: Option<Amount>,
+    epoch_period: u64,
+    vickrey: bool,
+    allowed_payments: Vec<ResourceAddress>,
+    allowed_bidders: Option<Vec<ComponentAddress>>,
+    min_bidders: Option<u32>,
+    reserve_price: Option<Amount>,
+}
+
+// mirrors the "SaleReceipt" struct of the auction template
+#[derive(Debug, serde::Deserialize)]
+struct SaleReceipt {
+    nft: NonFungibleAddress,
+    price: Amount,
+    epoch: u64,
+}
+
+// maximum amount of bids stored in the history of each auction
+const MAX_BID_HISTORY: usize = 10;
+
+// bounds of the auction periods allowed by the index
+const MIN_AUCTION_PERIOD: u64 = 2;
+const MAX_AUCTION_PERIOD: u64 = 100;
+
 #[test]
 fn auction_period_ends_with_winning_bid() {
     let TestSetup {
@@ -30,37 +73,40 @@ fn auction_period_ends_with_winning_bid() {
         min_price: None,
         buy_price: None,
         epoch_period: 10,
+        vickrey: false,
     };
     let (auction_component, _) = create_auction(&mut test, &auction);
 
     // store the seller account balance for later checks
     let seller_balance = get_account_tari_balance(&mut test, &seller);
 
+    // create the bidder accounts
+    let bidders = create_funded_accounts(&mut test, 2, Amount(1000));
+    let (bidder1, bidder2) = (&bidders[0], &bidders[1]);
+
     // place a bid
-    let bidder1 = create_account(&mut test);
     let bid1 = BidRequest {
         auction: auction_component,
         bidder: bidder1.clone(),
         bid: Amount(100),
     };
     bid(&mut test, &bid1);
-    let bidder1_balance = get_account_tari_balance(&mut test, &bidder1);
 
     // place a higher bid
-    let bidder2 = create_account(&mut test);
+    // bidder2 is now the highest bidder, so the previous bid must have been refunded to bidder1
     let bid2 = BidRequest {
         auction: auction_component,
         bidder: bidder2.clone(),
         bid: Amount(200),
     };
-    bid(&mut test, &bid2);
+    assert_balance_delta(&mut test, bidder1, &XTR, bid1.bid, |test| bid(test, &bid2));
 
-    // bidder2 is now the highest bidder, so the previous bid must have been refunded to bidder1
-    let bidder1_balance_after_refund = get_account_tari_balance(&mut test, &bidder1);
-    assert_eq!(bidder1_balance_after_refund, bidder1_balance + bid1.bid);
+    // jump past the deadline of the auction
+    advance_epochs(&mut test, auction.epoch_period + 1);
 
-    // advance the epoch so the auction period expires
-    set_epoch(&mut test, auction.epoch_period + 1);
+    // there is no winner until the auction is settled
+    let winner: Option<ComponentAddress> = test.call_method(auction_component, "get_winner", args![], vec![]);
+    assert_eq!(winner, None);
 
     // the winning bidder (bidder2) withdraws the NFT
     let finish = FinishRequest {
@@ -72,6 +118,111 @@ fn auction_period_ends_with_winning_bid() {
     // the seller received the bid payment
     let seller_balance_after_sell = get_account_tari_balance(&mut test, &seller);
     assert_eq!(seller_balance_after_sell, seller_balance + bid2.bid);
+
+    let winner: Option<ComponentAddress> = test.call_method(auction_component, "get_winner", args![], vec![]);
+    assert_eq!(winner, Some(bidder2.component));
+}
+
+#[test]
+fn auction_winner_receives_a_sale_receipt() {
+    let TestSetup {
+        mut test,
+        auction_index_component,
+        seller,
+        seller_nft_address,
+        ..
+    } = setup();
+
+    let auction = AuctionRequest {
+        marketplace: auction_index_component,
+        seller: seller.clone(),
+        nft: seller_nft_address.clone(),
+        min_price: None,
+        buy_price: None,
+        epoch_period: 10,
+        vickrey: false,
+    };
+    let (auction_component, _) = create_auction(&mut test, &auction);
+    let receipt_resource: ResourceAddress =
+        test.call_method(auction_component, "get_sale_receipt_resource", args![], vec![]);
+
+    let bidder = create_account(&mut test);
+    let bid1 = BidRequest {
+        auction: auction_component,
+        bidder: bidder.clone(),
+        bid: Amount(100),
+    };
+    bid(&mut test, &bid1);
+
+    // no receipt is minted until the auction is settled
+    let receipt: Option<SaleReceipt> = test.call_method(auction_component, "get_sale_receipt", args![], vec![]);
+    assert!(receipt.is_none());
+
+    let settlement_epoch = auction.epoch_period + 1;
+    set_epoch(&mut test, settlement_epoch);
+    let finish = FinishRequest {
+        auction: auction_component,
+        account: bidder.clone(),
+    };
+    finish_auction(&mut test, &finish);
+
+    // the winner holds the receipt, which references the NFT sold, the final price and the epoch of the sale
+    let receipt_balance = get_account_balance(&mut test, &bidder, &receipt_resource);
+    assert_eq!(receipt_balance, Amount(1));
+    let receipt: Option<SaleReceipt> = test.call_method(auction_component, "get_sale_receipt", args![], vec![]);
+    let receipt = receipt.unwrap();
+    assert_eq!(receipt.nft, seller_nft_address);
+    assert_eq!(receipt.price, bid1.bid);
+    assert_eq!(receipt.epoch, settlement_epoch);
+}
+
+#[test]
+fn auction_index_pays_the_marketplace_fee() {
+    // the marketplace takes 2.5% of each sale
+    let test = TemplateTest::new(["./templates/index", "./templates/auction"]);
+    let (
+        TestSetup {
+            mut test,
+            auction_index_component,
+            seller,
+            seller_nft_address,
+            ..
+        },
+        fee_recipient,
+    ) = setup_with_fee(test, XTR, 25);
+
+    let auction = AuctionRequest {
+        marketplace: auction_index_component,
+        seller: seller.clone(),
+        nft: seller_nft_address.clone(),
+        min_price: None,
+        buy_price: None,
+        epoch_period: 10,
+        vickrey: false,
+    };
+    let (auction_component, _) = create_auction(&mut test, &auction);
+
+    let bidder = create_account(&mut test);
+    let bid1 = BidRequest {
+        auction: auction_component,
+        bidder: bidder.clone(),
+        bid: Amount(200),
+    };
+    bid(&mut test, &bid1);
+
+    let seller_balance = get_account_tari_balance(&mut test, &seller);
+    let fee_recipient_balance = get_account_tari_balance(&mut test, &fee_recipient);
+    advance_epochs(&mut test, auction.epoch_period + 1);
+    let finish = FinishRequest {
+        auction: auction_component,
+        account: bidder.clone(),
+    };
+    finish_auction(&mut test, &finish);
+
+    // the fee recipient gets its cut and the seller the rest of the winning bid
+    let fee = Amount(5);
+    assert_eq!(get_account_tari_balance(&mut test, &fee_recipient), fee_recipient_balance + fee);
+    assert_eq!(get_account_tari_balance(&mut test, &seller), seller_balance + bid1.bid - fee);
 }
 
 #[test]
@@ -92,6 +243,7 @@ fn auction_period_ends_with_no_winning_bid() {
         min_price: None,
         buy_price: None,
         epoch_period: 10,
+        vickrey: false,
     };
     let (auction_component, _) = create_auction(&mut test, &auction);
 
@@ -100,8 +252,8 @@ fn auction_period_ends_with_no_winning_bid() {
         get_account_balance(&mut test, &seller, &seller_nft_address.resource_address());
     assert_eq!(seller_nft_balance, Amount(0));
 
-    // advance the epoch so the auction period expires
-    set_epoch(&mut test, auction.epoch_period + 1);
+    // jump past the deadline of the auction
+    advance_epochs(&mut test, auction.epoch_period + 1);
 
     // the seller withdraws the NFT
     let finish = FinishRequest {
@@ -116,6 +268,72 @@ fn auction_period_ends_with_no_winning_bid() {
     assert_eq!(seller_nft_balance, Amount(1));
 }
 
+#[test]
+fn finished_auction_with_no_bids_is_closed() {
+    let TestSetup {
+        mut test,
+        auction_index_component,
+        seller,
+        seller_nft_address,
+        ..
+    } = setup();
+
+    let auction = AuctionRequest {
+        marketplace: auction_index_component,
+        seller: seller.clone(),
+        nft: seller_nft_address.clone(),
+        min_price: None,
+        buy_price: None,
+        epoch_period: 10,
+        vickrey: false,
+    };
+    let (auction_component, seller_badge) = create_auction(&mut test, &auction);
+
+    // nobody bids, the seller finishes the auction
+    advance_epochs(&mut test, auction.epoch_period + 1);
+    let finish = FinishRequest {
+        auction: auction_component,
+        account: seller.clone(),
+    };
+    finish_auction(&mut test, &finish);
+
+    let is_closed: bool = test.call_method(auction_component, "is_closed", args![], vec![]);
+    assert!(is_closed);
+
+    // the auction cannot be cancelled anymore
+    let reason = test.execute_expect_failure(
+        Transaction::builder()
+            .call_method(
+                seller.component,
+                "withdraw_non_fungible",
+                args![seller_badge.resource_address(), seller_badge.id()],
+            )
+            .put_last_instruction_output_on_workspace("badge")
+            .call_method(auction_component, "cancel", args![Workspace("badge")])
+            .sign(&seller.key)
+            .build(),
+        vec![seller.owner_token.clone()],
+    );
+    assert_reject_reason(reason, "Auction already settled");
+
+    // but the seller can get rid of the badge
+    test.execute_expect_success(
+        Transaction::builder()
+            .call_method(
+                seller.component,
+                "withdraw_non_fungible",
+                args![seller_badge.resource_address(), seller_badge.id()],
+            )
+            .put_last_instruction_output_on_workspace("badge")
+            .call_method(auction_component, "redeem_seller_badge", args![Workspace("badge")])
+            .sign(&seller.key)
+            .build(),
+        vec![seller.owner_token.clone()],
+    );
+    let seller_badge_balance = get_account_balance(&mut test, &seller, &seller_badge.resource_address());
+    assert_eq!(seller_badge_balance, Amount(0));
+}
+
 #[test]
 fn auction_finishes_by_buying_price_bid() {
     let TestSetup {
@@ -135,8 +353,9 @@ fn auction_finishes_by_buying_price_bid() {
         min_price: None,
         buy_price: Some(buy_price),
         epoch_period: 10,
+        vickrey: false,
     };
-    let (auction_component, _) = create_auction(&mut test, &auction);
+    let (auction_component, seller_badge) = create_auction(&mut test, &auction);
 
     // store the seller account balance for later checks
     let seller_balance = get_account_tari_balance(&mut test, &seller);
@@ -158,6 +377,120 @@ fn auction_finishes_by_buying_price_bid() {
     // the seller received the bid payment
     let seller_balance_after_sell = get_account_tari_balance(&mut test, &seller);
     assert_eq!(seller_balance_after_sell, seller_balance + buy_price);
+
+    // the auction is settled before its ending epoch, so it cannot be cancelled anymore
+    let reason = test.execute_expect_failure(
+        Transaction::builder()
+            .call_method(
+                seller.component,
+                "withdraw_non_fungible",
+                args![seller_badge.resource_address(), seller_badge.id()],
+            )
+            .put_last_instruction_output_on_workspace("badge")
+            .call_method(auction_component, "cancel", args![Workspace("badge")])
+            .sign(&seller.key)
+            .build(),
+        vec![seller.owner_token.clone()],
+    );
+    assert_reject_reason(reason, "Auction already settled");
+}
+
+#[test]
+fn auction_refunds_overpayment_above_buying_price() {
+    let TestSetup {
+        mut test,
+        auction_index_component,
+        seller,
+        seller_nft_address,
+        ..
+    } = setup();
+
+    // create an auction for the NFT
+    let buy_price = Amount(100);
+    let auction = AuctionRequest {
+        marketplace: auction_index_component,
+        seller: seller.clone(),
+        nft: seller_nft_address.clone(),
+        min_price: None,
+        buy_price: Some(buy_price),
+        epoch_period: 10,
+        vickrey: false,
+    };
+    let (auction_component, _) = create_auction(&mut test, &auction);
+
+    // store the balances for later checks
+    let bidder = create_account(&mut test);
+    let seller_balance = get_account_tari_balance(&mut test, &seller);
+    let bidder_balance = get_account_tari_balance(&mut test, &bidder);
+
+    // place a bid higher than the buying price of the NFT
+    let overpaying_bid = BidRequest {
+        auction: auction_component,
+        bidder: bidder.clone(),
+        bid: buy_price + Amount(50),
+    };
+    bid(&mut test, &overpaying_bid);
+
+    // the sale settled at the buying price and the overpayment was returned to the bidder
+    let seller_balance_after_sell = get_account_tari_balance(&mut test, &seller);
+    let bidder_balance_after_sell = get_account_tari_balance(&mut test, &bidder);
+    assert_eq!(seller_balance_after_sell, seller_balance + buy_price);
+    assert_eq!(bidder_balance_after_sell, bidder_balance - buy_price);
+
+    // the bidder received the NFT
+    let bidder_nft_balance = get_account_balance(&mut test, &bidder, &seller_nft_address.resource_address());
+    assert_eq!(bidder_nft_balance, Amount(1));
+}
+
+#[test]
+fn auction_buying_price_bid_reconciles_balances() {
+    let TestSetup {
+        mut test,
+        auction_index_component,
+        seller,
+        seller_nft_address,
+        ..
+    } = setup();
+
+    // create an auction for the NFT
+    let buy_price = Amount(100);
+    let auction = AuctionRequest {
+        marketplace: auction_index_component,
+        seller: seller.clone(),
+        nft: seller_nft_address.clone(),
+        min_price: None,
+        buy_price: Some(buy_price),
+        epoch_period: 10,
+        vickrey: false,
+    };
+    let (auction_component, _) = create_auction(&mut test, &auction);
+
+    // store the balances for later checks
+    let bidder = create_account(&mut test);
+    let seller_balance = get_account_tari_balance(&mut test, &seller);
+    let bidder_balance = get_account_tari_balance(&mut test, &bidder);
+
+    // place a bid that matches the buying price of the NFT
+    let buy_bid = BidRequest {
+        auction: auction_component,
+        bidder: bidder.clone(),
+        bid: buy_price,
+    };
+    bid(&mut test, &buy_bid);
+
+    // the payment moved exactly once from the bidder to the seller, nothing was leaked or duplicated
+    let seller_balance_after_sell = get_account_tari_balance(&mut test, &seller);
+    let bidder_balance_after_sell = get_account_tari_balance(&mut test, &bidder);
+    assert_eq!(seller_balance_after_sell, seller_balance + buy_price);
+    assert_eq!(bidder_balance_after_sell, bidder_balance - buy_price);
+    assert_eq!(
+        seller_balance_after_sell + bidder_balance_after_sell,
+        seller_balance + bidder_balance
+    );
+
+    // the bidder received the NFT
+    let bidder_nft_balance = get_account_balance(&mut test, &bidder, &seller_nft_address.resource_address());
+    assert_eq!(bidder_nft_balance, Amount(1));
 }
 
 #[test]
@@ -178,6 +511,7 @@ fn auction_cancelled_by_seller() {
         min_price: None,
         buy_price: None,
         epoch_period: 10,
+        vickrey: false,
     };
     let (auction_component, seller_badge) = create_auction(&mut test, &auction);
 
@@ -207,147 +541,1611 @@ fn auction_cancelled_by_seller() {
         get_account_balance(&mut test, &seller, &seller_nft_address.resource_address());
     assert_eq!(seller_nft_balance, Amount(1));
 
-    // the existing bid has been refunded
-    let bidder1_balance_after_cancel = get_account_tari_balance(&mut test, &bidder1);
-    assert_eq!(bidder1_balance_after_cancel, bidder1_balance + bid1.bid);
+    // the existing bid is not pushed to the bidder, but it can be claimed
+    assert_eq!(get_account_tari_balance(&mut test, &bidder1), bidder1_balance);
+    assert_balance_delta(&mut test, &bidder1, &XTR, bid1.bid, |test| {
+        claim_cancelled_refund(test, auction_component, &bidder1)
+    });
+
+    // the refund can only be claimed once
+    let reason = test.execute_expect_failure(
+        claim_cancelled_refund_transaction(auction_component, &bidder1),
+        vec![bidder1.owner_token.clone()],
+    );
+    assert_reject_reason(reason, "There is no refund to claim");
 }
 
 #[test]
-fn it_rejects_invalid_auctions() {
+fn auction_exposes_the_seller_badge_resource() {
     let TestSetup {
         mut test,
         auction_index_component,
-        account_nft_component,
         seller,
         seller_nft_address,
         ..
     } = setup();
 
-    // reject if resource is not an nft
-    // we test it by trying to auction a Tari fungible token
-    let reason = test.execute_expect_failure(
-        Transaction::builder()
-            .call_method(seller.component, "withdraw", args![XTR, Amount(1)]) // invalid resource
-            .put_last_instruction_output_on_workspace("nft_bucket")
-            .call_method(
-                auction_index_component,
-                "create_auction",
-                args![
-                    Workspace("nft_bucket"),
-                    seller.component,
-                    None::<Amount>,
-                    None::<Amount>,
-                    10
-                ],
-            )
-            .put_last_instruction_output_on_workspace("seller_badge")
-            .call_method(
-                seller.component,
-                "deposit",
-                args![Workspace("seller_badge")],
-            )
-            .sign(&seller.key)
-            .build(),
-        vec![seller.owner_token.clone()],
-    );
-    assert_reject_reason(reason, "The resource is not a NFT");
-
-    // reject if multiple nfts in the bucket
-    mint_account_nft(&mut test, &seller, &account_nft_component);
+    let auction = AuctionRequest {
+        marketplace: auction_index_component,
+        seller: seller.clone(),
+        nft: seller_nft_address.clone(),
+        min_price: None,
+        buy_price: None,
+        epoch_period: 10,
+        vickrey: false,
+    };
+    let (auction_component, seller_badge) = create_auction(&mut test, &auction);
+
+    let seller_badge_resource: ResourceAddress =
+        test.call_method(auction_component, "get_seller_badge_resource", args![], vec![]);
+    assert_eq!(seller_badge_resource, *seller_badge.resource_address());
+}
+
+#[test]
+fn auction_info_summarizes_the_auction() {
+    let TestSetup {
+        mut test,
+        auction_index_component,
+        seller,
+        seller_nft_address,
+        ..
+    } = setup();
+
+    let auction = AuctionRequest {
+        marketplace: auction_index_component,
+        seller: seller.clone(),
+        nft: seller_nft_address.clone(),
+        min_price: Some(Amount(50)),
+        buy_price: Some(Amount(500)),
+        epoch_period: 10,
+        vickrey: false,
+    };
+    let (auction_component, _) = create_auction(&mut test, &auction);
+
+    let info: AuctionInfo = test.call_method(auction_component, "get_auction_info", args![], vec![]);
+    assert_eq!(info.highest_bidder, None);
+    assert_eq!(info.highest_bid, None);
+
+    let bidder = create_account(&mut test);
+    let bid1 = BidRequest {
+        auction: auction_component,
+        bidder: bidder.clone(),
+        bid: Amount(100),
+    };
+    bid(&mut test, &bid1);
+
+    let info: AuctionInfo = test.call_method(auction_component, "get_auction_info", args![], vec![]);
+    assert_eq!(info.seller, seller.component);
+    assert_eq!(info.min_price, auction.min_price);
+    assert_eq!(info.buy_price, auction.buy_price);
+    assert_eq!(info.highest_bidder, Some(bidder.component));
+    assert_eq!(info.highest_bid, Some(bid1.bid));
+    assert_eq!(info.ending_epoch, auction.epoch_period);
+    assert!(!info.closed);
+}
+
+#[test]
+fn highest_bidder_increases_the_bid() {
+    let TestSetup {
+        mut test,
+        auction_index_component,
+        seller,
+        seller_nft_address,
+        ..
+    } = setup();
+
+    let auction = AuctionRequest {
+        marketplace: auction_index_component,
+        seller: seller.clone(),
+        nft: seller_nft_address.clone(),
+        min_price: None,
+        buy_price: None,
+        epoch_period: 10,
+        vickrey: false,
+    };
+    let (auction_component, _) = create_auction(&mut test, &auction);
+
+    let bidders = create_funded_accounts(&mut test, 2, Amount(1000));
+    let (bidder1, bidder2) = (&bidders[0], &bidders[1]);
+    let bid1 = BidRequest {
+        auction: auction_component,
+        bidder: bidder1.clone(),
+        bid: Amount(100),
+    };
+    bid(&mut test, &bid1);
+    let bid2 = BidRequest {
+        auction: auction_component,
+        bidder: bidder2.clone(),
+        bid: Amount(150),
+    };
+    bid(&mut test, &bid2);
+
+    // only the highest bidder can increase the bid
+    let reason = test.execute_expect_failure(
+        increase_bid_transaction(auction_component, bidder1, Amount(100)),
+        vec![bidder1.owner_token.clone()],
+    );
+    assert_reject_reason(reason, "Only the highest bidder can increase the bid");
+
+    // the leader only pays the additional funds, the locked bid is not refunded
+    assert_balance_delta(&mut test, bidder2, &XTR, Amount(-50), |test| {
+        test.execute_expect_success(
+            increase_bid_transaction(auction_component, bidder2, Amount(50)),
+            vec![bidder2.owner_token.clone()],
+        );
+    });
+    let info: AuctionInfo = test.call_method(auction_component, "get_auction_info", args![], vec![]);
+    assert_eq!(info.highest_bidder, Some(bidder2.component));
+    assert_eq!(info.highest_bid, Some(Amount(200)));
+}
+
+#[test]
+fn auction_extended_by_seller() {
+    let TestSetup {
+        mut test,
+        auction_index_component,
+        seller,
+        seller_nft_address,
+        ..
+    } = setup();
+
+    // create an auction for the NFT
+    let auction = AuctionRequest {
+        marketplace: auction_index_component,
+        seller: seller.clone(),
+        nft: seller_nft_address.clone(),
+        min_price: None,
+        buy_price: None,
+        epoch_period: 10,
+        vickrey: false,
+    };
+    let (auction_component, seller_badge) = create_auction(&mut test, &auction);
+
+    // the auction is about to expire
+    set_epoch(&mut test, auction.epoch_period - 1);
+
+    // the seller extends the auction period
+    let extend = ExtendRequest {
+        auction: auction_component,
+        account: seller.clone(),
+        seller_badge: seller_badge.clone(),
+        extra_epochs: 5,
+    };
+    extend_auction(&mut test, &extend);
+
+    // the original auction period has passed, but bids are still accepted
+    set_epoch(&mut test, auction.epoch_period + 1);
+    let bidder = create_account(&mut test);
+    let bid1 = BidRequest {
+        auction: auction_component,
+        bidder: bidder.clone(),
+        bid: Amount(100),
+    };
+    bid(&mut test, &bid1);
+
+    // the seller still holds the badge, so the auction can be cancelled
+    let cancel = CancelRequest {
+        auction: auction_component,
+        account: seller.clone(),
+        seller_badge: seller_badge.clone(),
+    };
+    cancel_auction(&mut test, &cancel);
+}
+
+// The old marketplace kept every auction inside a single component, so bids on different auctions always wrote to the
+// same substate and concurrent bids conflicted. With one component per auction, bids on different auctions must not
+// touch any shared substate, so they can be processed concurrently.
+#[test]
+fn bids_on_different_auctions_do_not_conflict() {
+    let TestSetup {
+        mut test,
+        auction_index_component,
+        account_nft_component,
+        seller,
+        seller_nft_address,
+    } = setup();
+
+    let other_nft_address = mint_account_nft(&mut test, &seller, &account_nft_component);
+    let mut auction_components = vec![];
+    for nft in [seller_nft_address, other_nft_address] {
+        let auction = AuctionRequest {
+            marketplace: auction_index_component,
+            seller: seller.clone(),
+            nft,
+            min_price: None,
+            buy_price: None,
+            epoch_period: 10,
+            vickrey: false,
+        };
+        let (auction_component, _) = create_auction(&mut test, &auction);
+        auction_components.push(auction_component);
+    }
+
+    // both transactions are built upfront from the same state, as if they were submitted at the same time
+    let bidders = create_funded_accounts(&mut test, 2, Amount(1000));
+    let bid_requests: Vec<BidRequest> = bidders
+        .iter()
+        .zip(&auction_components)
+        .map(|(bidder, auction)| BidRequest {
+            auction: *auction,
+            bidder: bidder.clone(),
+            bid: Amount(100),
+        })
+        .collect();
+    let transactions: Vec<Transaction> = bid_requests.iter().map(bid_transaction).collect();
+
+    let mut written_substates = vec![];
+    for (req, transaction) in bid_requests.iter().zip(transactions) {
+        let result = test.execute_expect_success(transaction, vec![req.bidder.owner_token.clone()]);
+        let substates: Vec<_> = result
+            .expect_success()
+            .up_iter()
+            .map(|(address, _)| address.clone())
+            .collect();
+        written_substates.push(substates);
+    }
+
+    // the bids did not write to any common substate
+    let (first_bid_substates, second_bid_substates) = (&written_substates[0], &written_substates[1]);
+    assert!(first_bid_substates
+        .iter()
+        .all(|substate| !second_bid_substates.contains(substate)));
+}
+
+#[test]
+fn auction_buying_price_set_by_seller() {
+    let TestSetup {
+        mut test,
+        auction_index_component,
+        seller,
+        seller_nft_address,
+        ..
+    } = setup();
+
+    // the auction starts without a buying price
+    let auction = AuctionRequest {
+        marketplace: auction_index_component,
+        seller: seller.clone(),
+        nft: seller_nft_address.clone(),
+        min_price: None,
+        buy_price: None,
+        epoch_period: 10,
+        vickrey: false,
+    };
+    let (auction_component, seller_badge) = create_auction(&mut test, &auction);
+
+    let bidders = create_funded_accounts(&mut test, 2, Amount(1000));
+    let (bidder1, bidder2) = (&bidders[0], &bidders[1]);
+    bid(&mut test, &BidRequest {
+        auction: auction_component,
+        bidder: bidder1.clone(),
+        bid: Amount(100),
+    });
+
+    // the buying price cannot be lower than the highest bid
+    let reason = test.execute_expect_failure(
+        set_buy_price_transaction(auction_component, &seller, &seller_badge, Amount(100)),
+        vec![seller.owner_token.clone()],
+    );
+    assert_reject_reason(reason, "The buying price must be higher than the highest bid");
+
+    let buy_price = Amount(300);
+    test.execute_expect_success(
+        set_buy_price_transaction(auction_component, &seller, &seller_badge, buy_price),
+        vec![seller.owner_token.clone()],
+    );
+
+    // the seller keeps the badge
+    let seller_badge_balance = get_account_balance(&mut test, &seller, &seller_badge.resource_address());
+    assert_eq!(seller_badge_balance, Amount(1));
+
+    // a bid at the new buying price settles the auction right away
+    let seller_balance = get_account_tari_balance(&mut test, &seller);
+    bid(&mut test, &BidRequest {
+        auction: auction_component,
+        bidder: bidder2.clone(),
+        bid: buy_price,
+    });
+    let is_closed: bool = test.call_method(auction_component, "is_closed", args![], vec![]);
+    assert!(is_closed);
+    let bidder_nft_balance = get_account_balance(&mut test, bidder2, &seller_nft_address.resource_address());
+    assert_eq!(bidder_nft_balance, Amount(1));
+    let seller_balance_after_sell = get_account_tari_balance(&mut test, &seller);
+    assert_eq!(seller_balance_after_sell, seller_balance + buy_price);
+}
+
+#[test]
+fn auction_time_remaining_decreases_with_epochs() {
+    let TestSetup {
+        mut test,
+        auction_index_component,
+        seller,
+        seller_nft_address,
+        ..
+    } = setup();
+
+    // create an auction for the NFT
+    let auction = AuctionRequest {
+        marketplace: auction_index_component,
+        seller: seller.clone(),
+        nft: seller_nft_address.clone(),
+        min_price: None,
+        buy_price: None,
+        epoch_period: 10,
+        vickrey: false,
+    };
+    let (auction_component, _) = create_auction(&mut test, &auction);
+
+    let time_remaining: u64 = test.call_method(auction_component, "time_remaining", args![], vec![]);
+    assert_eq!(time_remaining, auction.epoch_period);
+
+    // the countdown decreases as epochs go by
+    set_epoch(&mut test, 4);
+    let time_remaining: u64 = test.call_method(auction_component, "time_remaining", args![], vec![]);
+    assert_eq!(time_remaining, auction.epoch_period - 4);
+    let has_ended: bool = test.call_method(auction_component, "has_ended", args![], vec![]);
+    assert!(!has_ended);
+
+    // once expired, there is no time left
+    set_epoch(&mut test, auction.epoch_period + 1);
+    let time_remaining: u64 = test.call_method(auction_component, "time_remaining", args![], vec![]);
+    assert_eq!(time_remaining, 0);
+    let has_ended: bool = test.call_method(auction_component, "has_ended", args![], vec![]);
+    assert!(has_ended);
+}
+
+#[test]
+fn auction_records_bid_history() {
+    let TestSetup {
+        mut test,
+        auction_index_component,
+        seller,
+        seller_nft_address,
+        ..
+    } = setup();
+
+    // create an auction for the NFT
+    let auction = AuctionRequest {
+        marketplace: auction_index_component,
+        seller: seller.clone(),
+        nft: seller_nft_address.clone(),
+        min_price: None,
+        buy_price: None,
+        epoch_period: 10,
+        vickrey: false,
+    };
+    let (auction_component, _) = create_auction(&mut test, &auction);
+
+    // place three increasing bids, in different epochs
+    let mut bids = vec![];
+    for (epoch, amount) in [(1, 100), (2, 200), (3, 300)] {
+        set_epoch(&mut test, epoch);
+        let bidder = create_account(&mut test);
+        let bid_req = BidRequest {
+            auction: auction_component,
+            bidder: bidder.clone(),
+            bid: Amount(amount),
+        };
+        bid(&mut test, &bid_req);
+        bids.push((bidder.component, bid_req.bid, epoch));
+    }
+
+    // all bids are recorded in order, even if they are no longer the winning one
+    let bid_history: Vec<(ComponentAddress, Amount, u64)> =
+        test.call_method(auction_component, "get_bid_history", args![], vec![]);
+    assert_eq!(bid_history, bids);
+}
+
+#[test]
+fn vickrey_auction_winner_pays_second_highest_bid() {
+    let TestSetup {
+        mut test,
+        auction_index_component,
+        seller,
+        seller_nft_address,
+        ..
+    } = setup();
+
+    // create a second-price auction for the NFT
+    let auction = AuctionRequest {
+        marketplace: auction_index_component,
+        seller: seller.clone(),
+        nft: seller_nft_address.clone(),
+        min_price: None,
+        buy_price: None,
+        epoch_period: 10,
+        vickrey: true,
+    };
+    let (auction_component, _) = create_auction(&mut test, &auction);
+
+    // store the seller account balance for later checks
+    let seller_balance = get_account_tari_balance(&mut test, &seller);
+
+    // three bidders place increasing bids
+    let mut bidders = vec![];
+    for amount in [100, 200, 300] {
+        let bidder = create_account(&mut test);
+        let bid_req = BidRequest {
+            auction: auction_component,
+            bidder: bidder.clone(),
+            bid: Amount(amount),
+        };
+        bid(&mut test, &bid_req);
+        bidders.push(bidder);
+    }
+    let winner = bidders[2].clone();
+    let winner_balance = get_account_tari_balance(&mut test, &winner);
+
+    // advance the epoch so the auction period expires
+    set_epoch(&mut test, auction.epoch_period + 1);
+    let finish = FinishRequest {
+        auction: auction_component,
+        account: winner.clone(),
+    };
+    finish_auction(&mut test, &finish);
+
+    // the winner only pays the runner-up amount, and gets the difference back
+    let winner_balance_after_finish = get_account_tari_balance(&mut test, &winner);
+    assert_eq!(winner_balance_after_finish, winner_balance + Amount(100));
+
+    // the seller received the second highest bid
+    let seller_balance_after_sell = get_account_tari_balance(&mut test, &seller);
+    assert_eq!(seller_balance_after_sell, seller_balance + Amount(200));
+
+    // the winner holds the NFT
+    let winner_nft_balance = get_account_balance(&mut test, &winner, &seller_nft_address.resource_address());
+    assert_eq!(winner_nft_balance, Amount(1));
+}
+
+#[test]
+fn auction_index_pagination() {
+    let TestSetup {
+        mut test,
+        auction_index_component,
+        account_nft_component,
+        seller,
+        seller_nft_address,
+    } = setup();
+
+    // create auctions with different ending epochs
+    let mut nft = seller_nft_address;
+    let mut auction_components = vec![];
+    for epoch_period in [10, 20, 30] {
+        let auction = AuctionRequest {
+            marketplace: auction_index_component,
+            seller: seller.clone(),
+            nft: nft.clone(),
+            min_price: None,
+            buy_price: None,
+            epoch_period,
+            vickrey: false,
+        };
+        let (auction_component, _) = create_auction(&mut test, &auction);
+        auction_components.push((epoch_period, auction_component));
+        nft = mint_account_nft(&mut test, &seller, &account_nft_component);
+    }
+
+    // the page size is respected
+    let page: Vec<(u64, ComponentAddress)> =
+        test.call_method(auction_index_component, "get_auctions_page", args![0u64, 2usize], vec![]);
+    assert_eq!(page, auction_components[..2].to_vec());
+
+    // the page starts at the requested epoch
+    let page: Vec<(u64, ComponentAddress)> =
+        test.call_method(auction_index_component, "get_auctions_page", args![15u64, 10usize], vec![]);
+    assert_eq!(page, auction_components[1..].to_vec());
+
+    // an epoch after all auctions end returns an empty page
+    let page: Vec<(u64, ComponentAddress)> =
+        test.call_method(auction_index_component, "get_auctions_page", args![31u64, 10usize], vec![]);
+    assert!(page.is_empty());
+
+    // expired auctions are not considered active
+    let active: BTreeMap<u64, Vec<ComponentAddress>> =
+        test.call_method(auction_index_component, "get_auctions_ending_after", args![20u64], vec![]);
+    assert_eq!(active.len(), 1);
+    assert_eq!(active.get(&30), Some(&vec![auction_components[2].1]));
+}
+
+#[test]
+fn auction_index_queries_the_live_status_of_auctions() {
+    let TestSetup {
+        mut test,
+        auction_index_component,
+        account_nft_component,
+        seller,
+        seller_nft_address,
+    } = setup();
+
+    let mut nft = seller_nft_address;
+    let mut auction_components = vec![];
+    for epoch_period in [10, 20, 30] {
+        let auction = AuctionRequest {
+            marketplace: auction_index_component,
+            seller: seller.clone(),
+            nft: nft.clone(),
+            min_price: None,
+            buy_price: None,
+            epoch_period,
+            vickrey: false,
+        };
+        let (auction_component, _) = create_auction(&mut test, &auction);
+        auction_components.push(auction_component);
+        nft = mint_account_nft(&mut test, &seller, &account_nft_component);
+    }
+
+    // the first auction expires
+    advance_epochs(&mut test, 15);
+    let active: Vec<ComponentAddress> =
+        test.call_method(auction_index_component, "get_active_auctions", args![None::<usize>], vec![]);
+    assert_eq!(active, auction_components[1..].to_vec());
+
+    // the results can be limited
+    let active: Vec<ComponentAddress> =
+        test.call_method(auction_index_component, "get_active_auctions", args![Some(1usize)], vec![]);
+    assert_eq!(active, vec![auction_components[1]]);
+}
+
+#[test]
+fn auction_index_sums_the_total_locked_value() {
+    let TestSetup {
+        mut test,
+        auction_index_component,
+        account_nft_component,
+        seller,
+        seller_nft_address,
+    } = setup();
+
+    // create three auctions, only two of them will get bids
+    let mut nft = seller_nft_address;
+    let mut auction_components = vec![];
+    for _ in 0..3 {
+        let auction = AuctionRequest {
+            marketplace: auction_index_component,
+            seller: seller.clone(),
+            nft: nft.clone(),
+            min_price: None,
+            buy_price: None,
+            epoch_period: 10,
+            vickrey: false,
+        };
+        let (auction_component, _) = create_auction(&mut test, &auction);
+        auction_components.push(auction_component);
+        nft = mint_account_nft(&mut test, &seller, &account_nft_component);
+    }
+
+    let bidder = create_account(&mut test);
+    for (auction_component, amount) in auction_components.iter().zip([100, 200]) {
+        let bid_req = BidRequest {
+            auction: *auction_component,
+            bidder: bidder.clone(),
+            bid: Amount(amount),
+        };
+        bid(&mut test, &bid_req);
+    }
+
+    let total_locked_value: Amount =
+        test.call_method(auction_index_component, "get_total_locked_value", args![None::<usize>], vec![]);
+    assert_eq!(total_locked_value, Amount(300));
+
+    // the amount of auctions visited can be limited
+    let total_locked_value: Amount =
+        test.call_method(auction_index_component, "get_total_locked_value", args![Some(1usize)], vec![]);
+    assert_eq!(total_locked_value, Amount(100));
+}
+
+#[test]
+fn auction_index_finishes_expired_auctions_in_batch() {
+    let TestSetup {
+        mut test,
+        auction_index_component,
+        account_nft_component,
+        seller,
+        seller_nft_address,
+    } = setup();
+
+    // create four auctions with the same period, one of them gets a bid and another one is extended
+    let epoch_period = 10;
+    let mut nft = seller_nft_address;
+    let mut auction_components = vec![];
+    let mut seller_badges = vec![];
+    for _ in 0..4 {
+        let auction = AuctionRequest {
+            marketplace: auction_index_component,
+            seller: seller.clone(),
+            nft: nft.clone(),
+            min_price: None,
+            buy_price: None,
+            epoch_period,
+            vickrey: false,
+        };
+        let (auction_component, seller_badge) = create_auction(&mut test, &auction);
+        auction_components.push(auction_component);
+        seller_badges.push(seller_badge);
+        nft = mint_account_nft(&mut test, &seller, &account_nft_component);
+    }
+
+    let bidder = create_account(&mut test);
+    let bid_req = BidRequest {
+        auction: auction_components[0],
+        bidder: bidder.clone(),
+        bid: Amount(100),
+    };
+    bid(&mut test, &bid_req);
+
+    // the extension is done directly in the auction, so the index still has the original ending epoch
+    let extra_epochs = 5;
+    extend_auction(&mut test, &ExtendRequest {
+        auction: auction_components[3],
+        account: seller.clone(),
+        seller_badge: seller_badges[3].clone(),
+        extra_epochs,
+    });
+
+    // the expired auctions are settled in a single transaction, the extended one is skipped as it's still live
+    set_epoch(&mut test, epoch_period);
+    let finish_expired = |test: &mut TemplateTest, current_epoch: u64| {
+        let result = test.execute_expect_success(
+            Transaction::builder()
+                .call_method(auction_index_component, "finish_expired", args![current_epoch, 10usize])
+                .sign(&seller.key)
+                .build(),
+            vec![seller.owner_token.clone()],
+        );
+        result.finalize.execution_results[0].decode::<u64>().unwrap()
+    };
+    assert_eq!(finish_expired(&mut test, epoch_period), 3);
+    for auction_component in &auction_components[..3] {
+        let is_closed: bool = test.call_method(*auction_component, "is_closed", args![], vec![]);
+        assert!(is_closed);
+    }
+    let is_closed: bool = test.call_method(auction_components[3], "is_closed", args![], vec![]);
+    assert!(!is_closed);
+    let winner: Option<ComponentAddress> =
+        test.call_method(auction_components[0], "get_winner", args![], vec![]);
+    assert_eq!(winner, Some(bidder.component));
+
+    // settled auctions are skipped, and the extended one is settled once it really expires
+    set_epoch(&mut test, epoch_period + extra_epochs);
+    assert_eq!(finish_expired(&mut test, epoch_period + extra_epochs), 1);
+    let is_closed: bool = test.call_method(auction_components[3], "is_closed", args![], vec![]);
+    assert!(is_closed);
+}
+
+#[test]
+fn auction_index_quotes_the_min_next_bid() {
+    let TestSetup {
+        mut test,
+        auction_index_component,
+        seller,
+        seller_nft_address,
+        ..
+    } = setup();
+
+    let auction = AuctionRequest {
+        marketplace: auction_index_component,
+        seller: seller.clone(),
+        nft: seller_nft_address.clone(),
+        min_price: Some(Amount(50)),
+        buy_price: None,
+        epoch_period: 10,
+        vickrey: false,
+    };
+    let (auction_component, _) = create_auction(&mut test, &auction);
+
+    // without bids, the minimum price must be paid
+    let min_next_bid: Option<Amount> = test.call_method(
+        auction_index_component,
+        "get_min_next_bid_for",
+        args![auction_component],
+        vec![],
+    );
+    assert_eq!(min_next_bid, auction.min_price);
+
+    let bidder = create_account(&mut test);
+    let bid1 = BidRequest {
+        auction: auction_component,
+        bidder: bidder.clone(),
+        bid: Amount(100),
+    };
+    bid(&mut test, &bid1);
+    let min_next_bid: Option<Amount> = test.call_method(
+        auction_index_component,
+        "get_min_next_bid_for",
+        args![auction_component],
+        vec![],
+    );
+    assert_eq!(min_next_bid, Some(bid1.bid + Amount(1)));
+
+    // components that are not indexed auctions have no quote
+    let min_next_bid: Option<Amount> = test.call_method(
+        auction_index_component,
+        "get_min_next_bid_for",
+        args![bidder.component],
+        vec![],
+    );
+    assert_eq!(min_next_bid, None);
+
+    // nor settled auctions
+    advance_epochs(&mut test, auction.epoch_period + 1);
+    let finish = FinishRequest {
+        auction: auction_component,
+        account: bidder.clone(),
+    };
+    finish_auction(&mut test, &finish);
+    let min_next_bid: Option<Amount> = test.call_method(
+        auction_index_component,
+        "get_min_next_bid_for",
+        args![auction_component],
+        vec![],
+    );
+    assert_eq!(min_next_bid, None);
+}
+
+#[test]
+fn auction_index_tracks_updated_auctions() {
+    let TestSetup {
+        mut test,
+        auction_index_component,
+        account_nft_component,
+        seller,
+        seller_nft_address,
+    } = setup();
+
+    let epoch_period = 10;
+    let mut nft = seller_nft_address;
+    let mut auction_components = vec![];
+    let mut seller_badges = vec![];
+    for _ in 0..2 {
+        let auction = AuctionRequest {
+            marketplace: auction_index_component,
+            seller: seller.clone(),
+            nft: nft.clone(),
+            min_price: None,
+            buy_price: None,
+            epoch_period,
+            vickrey: false,
+        };
+        let (auction_component, seller_badge) = create_auction(&mut test, &auction);
+        auction_components.push(auction_component);
+        seller_badges.push(seller_badge);
+        nft = mint_account_nft(&mut test, &seller, &account_nft_component);
+    }
+
+    // extensions done through the index move the auction to its new ending epoch
+    let extra_epochs = 5;
+    test.execute_expect_success(
+        seller_badge_transaction(
+            &seller,
+            &seller_badges[0],
+            auction_index_component,
+            "extend_auction",
+            args![auction_components[0], Workspace("seller_badge"), extra_epochs],
+        ),
+        vec![seller.owner_token.clone()],
+    );
+    let auctions: BTreeMap<u64, Vec<ComponentAddress>> =
+        test.call_method(auction_index_component, "get_auctions", args![], vec![]);
+    assert_eq!(auctions.get(&epoch_period), Some(&vec![auction_components[1]]));
+    assert_eq!(
+        auctions.get(&(epoch_period + extra_epochs)),
+        Some(&vec![auction_components[0]])
+    );
+
+    // and so do the buying prices
+    test.execute_expect_success(
+        seller_badge_transaction(
+            &seller,
+            &seller_badges[0],
+            auction_index_component,
+            "set_auction_buy_price",
+            args![auction_components[0], Workspace("seller_badge"), Amount(500)],
+        ),
+        vec![seller.owner_token.clone()],
+    );
+    let auctions: Vec<ComponentAddress> = test.call_method(
+        auction_index_component,
+        "get_auctions_in_price_range",
+        args![Amount(400), Amount(600), false],
+        vec![],
+    );
+    assert_eq!(auctions, vec![auction_components[0]]);
+
+    // extensions done directly in the auction are picked up once the auction is refreshed
+    extend_auction(&mut test, &ExtendRequest {
+        auction: auction_components[1],
+        account: seller.clone(),
+        seller_badge: seller_badges[1].clone(),
+        extra_epochs,
+    });
+    let auctions: BTreeMap<u64, Vec<ComponentAddress>> =
+        test.call_method(auction_index_component, "get_auctions", args![], vec![]);
+    assert_eq!(auctions.get(&epoch_period), Some(&vec![auction_components[1]]));
+
+    let bidder = create_account(&mut test);
+    test.execute_expect_success(
+        Transaction::builder()
+            .call_method(auction_index_component, "refresh_auction", args![auction_components[1]])
+            .sign(&bidder.key)
+            .build(),
+        vec![bidder.owner_token.clone()],
+    );
+    let auctions: BTreeMap<u64, Vec<ComponentAddress>> =
+        test.call_method(auction_index_component, "get_auctions", args![], vec![]);
+    assert_eq!(auctions.get(&epoch_period), None);
+    assert_eq!(
+        auctions.get(&(epoch_period + extra_epochs)),
+        Some(&auction_components)
+    );
+
+    // only indexed auctions can be updated through the index
+    let reason = test.execute_expect_failure(
+        Transaction::builder()
+            .call_method(auction_index_component, "refresh_auction", args![bidder.component])
+            .sign(&bidder.key)
+            .build(),
+        vec![bidder.owner_token.clone()],
+    );
+    assert_reject_reason(reason, "is not in the index");
+}
+
+#[test]
+fn auction_index_filters_by_ending_epoch() {
+    let TestSetup {
+        mut test,
+        auction_index_component,
+        account_nft_component,
+        seller,
+        seller_nft_address,
+    } = setup();
+
+    // create auctions with different ending epochs
+    let mut nft = seller_nft_address;
+    let mut auction_components = vec![];
+    for epoch_period in [10, 20, 30] {
+        let auction = AuctionRequest {
+            marketplace: auction_index_component,
+            seller: seller.clone(),
+            nft: nft.clone(),
+            min_price: None,
+            buy_price: None,
+            epoch_period,
+            vickrey: false,
+        };
+        let (auction_component, _) = create_auction(&mut test, &auction);
+        auction_components.push(auction_component);
+        nft = mint_account_nft(&mut test, &seller, &account_nft_component);
+    }
+
+    // the upper bound is exclusive
+    let ending: BTreeMap<u64, Vec<ComponentAddress>> =
+        test.call_method(auction_index_component, "get_auctions_ending_before", args![20u64], vec![]);
+    assert_eq!(ending.len(), 1);
+    assert_eq!(ending.get(&10), Some(&vec![auction_components[0]]));
+
+    let ending: BTreeMap<u64, Vec<ComponentAddress>> =
+        test.call_method(auction_index_component, "get_auctions_ending_before", args![21u64], vec![]);
+    assert_eq!(ending.len(), 2);
+    assert_eq!(ending.get(&20), Some(&vec![auction_components[1]]));
+
+    // no auctions end before the earliest ending epoch
+    let ending: BTreeMap<u64, Vec<ComponentAddress>> =
+        test.call_method(auction_index_component, "get_auctions_ending_before", args![10u64], vec![]);
+    assert!(ending.is_empty());
+}
+
+#[test]
+fn auction_index_prunes_settled_auctions() {
+    let TestSetup {
+        mut test,
+        auction_index_component,
+        seller,
+        seller_nft_address,
+        ..
+    } = setup();
+
+    // create an auction for the NFT
+    let auction = AuctionRequest {
+        marketplace: auction_index_component,
+        seller: seller.clone(),
+        nft: seller_nft_address.clone(),
+        min_price: None,
+        buy_price: None,
+        epoch_period: 10,
+        vickrey: false,
+    };
+    let (auction_component, _) = create_auction(&mut test, &auction);
+
+    // live auctions cannot be removed
     let reason = test.execute_expect_failure(
         Transaction::builder()
-            .call_method(
-                seller.component,
-                "withdraw",
-                args![seller_nft_address.resource_address(), Amount(2)],
-            ) // invalid bucket
-            .put_last_instruction_output_on_workspace("nft_bucket")
             .call_method(
                 auction_index_component,
-                "create_auction",
-                args![
-                    Workspace("nft_bucket"),
-                    seller.component,
-                    None::<Amount>,
-                    None::<Amount>,
-                    10
-                ],
-            )
-            .put_last_instruction_output_on_workspace("seller_badge")
-            .call_method(
-                seller.component,
-                "deposit",
-                args![Workspace("seller_badge")],
+                "remove_auction",
+                args![auction.epoch_period, auction_component],
             )
             .sign(&seller.key)
             .build(),
         vec![seller.owner_token.clone()],
     );
-    assert_reject_reason(reason, "Can only start an auction of a single NFT");
+    assert_reject_reason(reason, "The auction is still open");
+
+    // settle the auction
+    set_epoch(&mut test, auction.epoch_period + 1);
+    let finish = FinishRequest {
+        auction: auction_component,
+        account: seller.clone(),
+    };
+    finish_auction(&mut test, &finish);
+
+    // prune the auction from the index
+    test.call_method::<()>(
+        auction_index_component,
+        "remove_auction",
+        args![auction.epoch_period, auction_component],
+        vec![],
+    );
+    let auctions: BTreeMap<u64, Vec<ComponentAddress>> =
+        test.call_method(auction_index_component, "get_auctions", args![], vec![]);
+    assert!(auctions.is_empty());
+}
+
+#[test]
+fn auction_index_lookup_by_resource() {
+    let TestSetup {
+        mut test,
+        auction_index_component,
+        account_nft_component,
+        seller,
+        seller_nft_address,
+    } = setup();
+
+    // mint a second NFT of the same resource
+    let other_nft_address = mint_account_nft(&mut test, &seller, &account_nft_component);
+    assert_eq!(other_nft_address.resource_address(), seller_nft_address.resource_address());
+
+    // auction both NFTs concurrently
+    let mut auction_components = vec![];
+    for nft in [seller_nft_address.clone(), other_nft_address] {
+        let auction = AuctionRequest {
+            marketplace: auction_index_component,
+            seller: seller.clone(),
+            nft,
+            min_price: None,
+            buy_price: None,
+            epoch_period: 10,
+            vickrey: false,
+        };
+        let (auction_component, _) = create_auction(&mut test, &auction);
+        auction_components.push(auction_component);
+    }
+
+    // both auctions are found by the NFT resource
+    let resource_auctions: Vec<ComponentAddress> = test.call_method(
+        auction_index_component,
+        "get_auctions_for_resource",
+        args![seller_nft_address.resource_address()],
+        vec![],
+    );
+    assert_eq!(resource_auctions, auction_components);
+
+    // other resources have no auctions
+    let resource_auctions: Vec<ComponentAddress> = test.call_method(
+        auction_index_component,
+        "get_auctions_for_resource",
+        args![XTR],
+        vec![],
+    );
+    assert!(resource_auctions.is_empty());
+}
+
+#[test]
+fn auction_index_creates_auctions_in_batch() {
+    let TestSetup {
+        mut test,
+        auction_index_component,
+        account_nft_component,
+        seller,
+        seller_nft_address,
+    } = setup();
+
+    // the seller holds three NFTs of the same resource
+    mint_account_nft(&mut test, &seller, &account_nft_component);
+    mint_account_nft(&mut test, &seller, &account_nft_component);
+    let nft_resource = seller_nft_address.resource_address();
 
-    // reject if the auction period is invalid
-    let reason = test.execute_expect_failure(
+    let result = test.execute_expect_success(
         Transaction::builder()
-            .call_method(
-                seller.component,
-                "withdraw",
-                args![seller_nft_address.resource_address(), Amount(1)],
-            )
+            .call_method(seller.component, "withdraw", args![nft_resource, Amount(3)])
             .put_last_instruction_output_on_workspace("nft_bucket")
             .call_method(
                 auction_index_component,
-                "create_auction",
+                "create_auctions_batch",
                 args![
                     Workspace("nft_bucket"),
                     seller.component,
                     None::<Amount>,
                     None::<Amount>,
-                    0
+                    10u64
                 ],
-            ) // invalid period
-            .put_last_instruction_output_on_workspace("seller_badge")
-            .call_method(
-                seller.component,
-                "deposit",
-                args![Workspace("seller_badge")],
             )
+            .put_last_instruction_output_on_workspace("batch")
+            .call_method(seller.component, "deposit", args![Workspace("batch.0.1")])
+            .call_method(seller.component, "deposit", args![Workspace("batch.1.1")])
+            .call_method(seller.component, "deposit", args![Workspace("batch.2.1")])
             .sign(&seller.key)
             .build(),
         vec![seller.owner_token.clone()],
     );
-    assert_reject_reason(reason, "Invalid auction period");
+    let batch = result.finalize.execution_results[2]
+        .decode::<Vec<(ComponentAddress, Bucket)>>()
+        .unwrap();
+
+    // three independent auctions, each one with its own seller badge
+    assert_eq!(batch.len(), 3);
+    let auction_components: Vec<ComponentAddress> = batch.iter().map(|(component, _)| *component).collect();
+    for (i, (component, badge)) in batch.iter().enumerate() {
+        assert!(batch[i + 1..].iter().all(|(other_component, other_badge)| {
+            other_component != component && other_badge.id() != badge.id()
+        }));
+    }
+
+    // all of them are indexed
+    let resource_auctions: Vec<ComponentAddress> = test.call_method(
+        auction_index_component,
+        "get_auctions_for_resource",
+        args![nft_resource],
+        vec![],
+    );
+    assert_eq!(resource_auctions, auction_components);
+    let seller_auctions: Vec<ComponentAddress> = test.call_method(
+        auction_index_component,
+        "get_auctions_by_seller",
+        args![seller.component, None::<usize>],
+        vec![],
+    );
+    assert_eq!(seller_auctions, auction_components);
+
+    // the NFTs are no longer in the seller account
+    assert_eq!(get_account_balance(&mut test, &seller, &nft_resource), Amount(0));
+}
+
+#[test]
+fn auction_index_lookup_by_seller() {
+    let TestSetup {
+        mut test,
+        auction_index_component,
+        account_nft_component,
+        seller,
+        seller_nft_address,
+    } = setup();
+
+    // the first seller creates two auctions
+    let other_nft_address = mint_account_nft(&mut test, &seller, &account_nft_component);
+    let mut seller_auctions = vec![];
+    for nft in [seller_nft_address, other_nft_address] {
+        let auction = AuctionRequest {
+            marketplace: auction_index_component,
+            seller: seller.clone(),
+            nft,
+            min_price: None,
+            buy_price: None,
+            epoch_period: 10,
+            vickrey: false,
+        };
+        let (auction_component, _) = create_auction(&mut test, &auction);
+        seller_auctions.push(auction_component);
+    }
+
+    // a second seller creates one auction
+    let seller2 = create_account(&mut test);
+    let seller2_nft_component = create_account_nft_component(&mut test, &seller2);
+    let seller2_nft_address = mint_account_nft(&mut test, &seller2, &seller2_nft_component);
+    let auction = AuctionRequest {
+        marketplace: auction_index_component,
+        seller: seller2.clone(),
+        nft: seller2_nft_address,
+        min_price: None,
+        buy_price: None,
+        epoch_period: 10,
+        vickrey: false,
+    };
+    let (seller2_auction, _) = create_auction(&mut test, &auction);
+
+    // each seller only sees their own auctions
+    let auctions: Vec<ComponentAddress> = test.call_method(
+        auction_index_component,
+        "get_auctions_by_seller",
+        args![seller.component, None::<usize>],
+        vec![],
+    );
+    assert_eq!(auctions, seller_auctions);
+    let auctions: Vec<ComponentAddress> = test.call_method(
+        auction_index_component,
+        "get_auctions_by_seller",
+        args![seller2.component, None::<usize>],
+        vec![],
+    );
+    assert_eq!(auctions, vec![seller2_auction]);
+
+    // the results can be limited
+    let auctions: Vec<ComponentAddress> = test.call_method(
+        auction_index_component,
+        "get_auctions_by_seller",
+        args![seller.component, Some(1usize)],
+        vec![],
+    );
+    assert_eq!(auctions, seller_auctions[..1].to_vec());
+}
+
+#[test]
+fn auction_index_filters_by_price_range() {
+    let TestSetup {
+        mut test,
+        auction_index_component,
+        account_nft_component,
+        seller,
+        seller_nft_address,
+    } = setup();
+
+    // create auctions with different price bounds
+    let price_bounds = [
+        (Some(Amount(100)), None),
+        (None, Some(Amount(500))),
+        (None, None),
+        (Some(Amount(1000)), Some(Amount(2000))),
+    ];
+    let mut nft = seller_nft_address;
+    let mut auction_components = vec![];
+    for (min_price, buy_price) in price_bounds {
+        let auction = AuctionRequest {
+            marketplace: auction_index_component,
+            seller: seller.clone(),
+            nft: nft.clone(),
+            min_price,
+            buy_price,
+            epoch_period: 10,
+            vickrey: false,
+        };
+        let (auction_component, _) = create_auction(&mut test, &auction);
+        auction_components.push(auction_component);
+        nft = mint_account_nft(&mut test, &seller, &account_nft_component);
+    }
+
+    // only the auctions with a price inside the range are returned
+    let mut auctions: Vec<ComponentAddress> = test.call_method(
+        auction_index_component,
+        "get_auctions_in_price_range",
+        args![Amount(50), Amount(600), false],
+        vec![],
+    );
+    auctions.sort();
+    let mut expected = vec![auction_components[0], auction_components[1]];
+    expected.sort();
+    assert_eq!(auctions, expected);
+
+    // auctions without price bounds can be included
+    let mut auctions: Vec<ComponentAddress> = test.call_method(
+        auction_index_component,
+        "get_auctions_in_price_range",
+        args![Amount(50), Amount(600), true],
+        vec![],
+    );
+    auctions.sort();
+    let mut expected = auction_components[..3].to_vec();
+    expected.sort();
+    assert_eq!(auctions, expected);
+
+    // the range bounds are inclusive
+    let auctions: Vec<ComponentAddress> = test.call_method(
+        auction_index_component,
+        "get_auctions_in_price_range",
+        args![Amount(2000), Amount(3000), false],
+        vec![],
+    );
+    assert_eq!(auctions, vec![auction_components[3]]);
+}
+
+#[test]
+fn it_rejects_invalid_auctions() {
+    let TestSetup {
+        mut test,
+        auction_index_component,
+        account_nft_component,
+        seller,
+        seller_nft_address,
+        ..
+    } = setup();
+
+    // reject if resource is not an nft
+    // we test it by trying to auction a Tari fungible token
+    let reason = test.execute_expect_failure(
+        AuctionTxBuilder::new(auction_index_component)
+            .seller(&seller)
+            .bucket(XTR, Amount(1)) // invalid resource
+            .build(),
+        vec![seller.owner_token.clone()],
+    );
+    assert_reject_reason(reason, "The resource is not a NFT");
+
+    // reject if multiple nfts in the bucket
+    mint_account_nft(&mut test, &seller, &account_nft_component);
+    let reason = test.execute_expect_failure(
+        AuctionTxBuilder::new(auction_index_component)
+            .seller(&seller)
+            .bucket(*seller_nft_address.resource_address(), Amount(2)) // invalid bucket
+            .build(),
+        vec![seller.owner_token.clone()],
+    );
+    assert_reject_reason(reason, "Can only start an auction of a single NFT");
+
+    // reject if the auction period is outside of the bounds of the index
+    let reason = test.execute_expect_failure(
+        AuctionTxBuilder::new(auction_index_component)
+            .seller(&seller)
+            .nft(&seller_nft_address)
+            .period(MIN_AUCTION_PERIOD - 1) // too short
+            .build(),
+        vec![seller.owner_token.clone()],
+    );
+    assert_reject_reason(reason, "The auction period must be at least 2 epochs");
+
+    let reason = test.execute_expect_failure(
+        AuctionTxBuilder::new(auction_index_component)
+            .seller(&seller)
+            .nft(&seller_nft_address)
+            .period(MAX_AUCTION_PERIOD + 1) // too long
+            .build(),
+        vec![seller.owner_token.clone()],
+    );
+    assert_reject_reason(reason, "The auction period must be at most 100 epochs");
 
     // reject if the seller account is not an account component
     let reason = test.execute_expect_failure(
+        AuctionTxBuilder::new(auction_index_component)
+            .seller(&seller)
+            .nft(&seller_nft_address)
+            .seller_address(account_nft_component) // invalid component, it's not an account
+            .build(),
+        vec![seller.owner_token.clone()],
+    );
+    assert_reject_reason(reason, "Invalid bidder account");
+
+    // periods inside the bounds are accepted
+    test.execute_expect_success(
+        AuctionTxBuilder::new(auction_index_component)
+            .seller(&seller)
+            .nft(&seller_nft_address)
+            .period(MAX_AUCTION_PERIOD)
+            .build(),
+        vec![seller.owner_token.clone()],
+    );
+
+    // reject auctioning the seller badge of an auction
+    let seller_badge = test
+        .get_previous_output_address(SubstateType::NonFungible)
+        .as_non_fungible_address()
+        .unwrap()
+        .clone();
+    let reason = test.execute_expect_failure(
+        AuctionTxBuilder::new(auction_index_component)
+            .seller(&seller)
+            .nft(&seller_badge)
+            .build(),
+        vec![seller.owner_token.clone()],
+    );
+    assert_reject_reason(reason, "Seller badges cannot be auctioned");
+}
+
+#[test]
+fn auction_index_accepts_a_custom_payment_resource() {
+    // the marketplace is launched for the token of a faucet
+    let mut test = TemplateTest::new(["./templates/index", "./templates/auction", "../faucet"]);
+    let faucet_component: ComponentAddress =
+        test.call_function("TestFaucet", "mint", args![Amount(1_000_000)], vec![]);
+    let payment_resource = test
+        .get_previous_output_address(SubstateType::Resource)
+        .as_resource_address()
+        .unwrap();
+
+    let TestSetup {
+        mut test,
+        auction_index_component,
+        seller,
+        seller_nft_address,
+        ..
+    } = setup_with_payment(test, payment_resource);
+
+    let accepted_payment: ResourceAddress =
+        test.call_method(auction_index_component, "get_accepted_payment", args![], vec![]);
+    assert_eq!(accepted_payment, payment_resource);
+
+    let auction = AuctionRequest {
+        marketplace: auction_index_component,
+        seller: seller.clone(),
+        nft: seller_nft_address.clone(),
+        min_price: None,
+        buy_price: None,
+        epoch_period: 10,
+        vickrey: false,
+    };
+    let (auction_component, _) = create_auction(&mut test, &auction);
+
+    // Tari bids are rejected
+    let bidder = create_account(&mut test);
+    let bid_req = BidRequest {
+        auction: auction_component,
+        bidder: bidder.clone(),
+        bid: Amount(100),
+    };
+    let reason = test.execute_expect_failure(bid_transaction(&bid_req), vec![bidder.owner_token.clone()]);
+    assert_reject_reason(reason, "Invalid payment resource");
+
+    // but the project token is accepted
+    test.execute_expect_success(
         Transaction::builder()
+            .call_method(faucet_component, "take_free_coins", args![])
+            .put_last_instruction_output_on_workspace("coins")
+            .call_method(bidder.component, "deposit", args![Workspace("coins")])
+            .call_method(bidder.component, "withdraw", args![payment_resource, Amount(100)])
+            .put_last_instruction_output_on_workspace("payment")
             .call_method(
-                seller.component,
-                "withdraw",
-                args![seller_nft_address.resource_address(), Amount(1)],
-            )
-            .put_last_instruction_output_on_workspace("nft_bucket")
-            .call_method(
-                auction_index_component,
-                "create_auction",
-                args![
-                    Workspace("nft_bucket"),
-                    account_nft_component, // invalid component, it's not an account
-                    None::<Amount>,
-                    None::<Amount>,
-                    10
-                ],
-            )
-            .put_last_instruction_output_on_workspace("seller_badge")
-            .call_method(
-                seller.component,
-                "deposit",
-                args![Workspace("seller_badge")],
+                auction_component,
+                "bid",
+                args![bidder.component, Workspace("payment")],
             )
+            .sign(&bidder.key)
+            .build(),
+        vec![bidder.owner_token.clone()],
+    );
+    let bid_history: Vec<(ComponentAddress, Amount, u64)> =
+        test.call_method(auction_component, "get_bid_history", args![], vec![]);
+    assert_eq!(bid_history.len(), 1);
+}
+
+#[test]
+fn auction_accepts_bids_in_multiple_currencies() {
+    let mut test = TemplateTest::new(["./templates/index", "./templates/auction", "../faucet"]);
+    let mint_token = |test: &mut TemplateTest| {
+        let faucet_component: ComponentAddress =
+            test.call_function("TestFaucet", "mint", args![Amount(1_000_000)], vec![]);
+        let token_resource = test
+            .get_previous_output_address(SubstateType::Resource)
+            .as_resource_address()
+            .unwrap();
+        (faucet_component, token_resource)
+    };
+    let (faucet_component, token_resource) = mint_token(&mut test);
+    let (other_faucet_component, other_token_resource) = mint_token(&mut test);
+
+    let TestSetup {
+        mut test,
+        auction_index_component,
+        seller,
+        seller_nft_address,
+        ..
+    } = setup_with_payment(test, XTR);
+
+    // the auction accepts both Tari and the faucet token, but not the other token
+    let result = test.execute_expect_success(
+        AuctionTxBuilder::new(auction_index_component)
+            .seller(&seller)
+            .nft(&seller_nft_address)
+            .allowed_payments(vec![XTR, token_resource])
+            .build(),
+        vec![seller.owner_token.clone()],
+    );
+    let (auction_component, _, _) = result.finalize.execution_results[2]
+        .decode::<(ComponentAddress, Bucket, u64)>()
+        .unwrap();
+    let allowed_payments: Vec<ResourceAddress> =
+        test.call_method(auction_component, "get_allowed_payments", args![], vec![]);
+    assert_eq!(allowed_payments, vec![XTR, token_resource]);
+
+    let token_bid_transaction = |faucet: ComponentAddress, resource: ResourceAddress, bidder: &Account, bid: Amount| {
+        Transaction::builder()
+            .call_method(faucet, "take_free_coins", args![])
+            .put_last_instruction_output_on_workspace("coins")
+            .call_method(bidder.component, "deposit", args![Workspace("coins")])
+            .call_method(bidder.component, "withdraw", args![resource, bid])
+            .put_last_instruction_output_on_workspace("payment")
+            .call_method(auction_component, "bid", args![bidder.component, Workspace("payment")])
+            .sign(&bidder.key)
+            .build()
+    };
+
+    // a disallowed currency is rejected
+    let bidder1 = create_account(&mut test);
+    let reason = test.execute_expect_failure(
+        token_bid_transaction(other_faucet_component, other_token_resource, &bidder1, Amount(100)),
+        vec![bidder1.owner_token.clone()],
+    );
+    assert_reject_reason(reason, "Invalid payment resource");
+
+    // the first bid is placed in the faucet token
+    test.execute_expect_success(
+        token_bid_transaction(faucet_component, token_resource, &bidder1, Amount(100)),
+        vec![bidder1.owner_token.clone()],
+    );
+
+    // so Tari bids cannot be compared with it anymore
+    let bidder2 = create_account(&mut test);
+    let bid_req = BidRequest {
+        auction: auction_component,
+        bidder: bidder2.clone(),
+        bid: Amount(200),
+    };
+    let reason = test.execute_expect_failure(bid_transaction(&bid_req), vec![bidder2.owner_token.clone()]);
+    assert_reject_reason(reason, "the resource of the first bid");
+
+    // a higher bid in the faucet token refunds the first bidder in the same resource
+    test.execute_expect_success(
+        token_bid_transaction(faucet_component, token_resource, &bidder2, Amount(200)),
+        vec![bidder2.owner_token.clone()],
+    );
+    let bidder1_balance = get_account_balance(&mut test, &bidder1, &token_resource);
+    assert_eq!(bidder1_balance, Amount(1000));
+}
+
+#[test]
+fn auction_only_accepts_bids_from_allowed_bidders() {
+    let TestSetup {
+        mut test,
+        auction_index_component,
+        seller,
+        seller_nft_address,
+        ..
+    } = setup();
+
+    let bidders = create_funded_accounts(&mut test, 2, Amount(1000));
+    let (allowed_bidder, other_bidder) = (&bidders[0], &bidders[1]);
+
+    let result = test.execute_expect_success(
+        AuctionTxBuilder::new(auction_index_component)
+            .seller(&seller)
+            .nft(&seller_nft_address)
+            .allowed_bidders(vec![allowed_bidder.component])
+            .build(),
+        vec![seller.owner_token.clone()],
+    );
+    let (auction_component, _, _) = result.finalize.execution_results[2]
+        .decode::<(ComponentAddress, Bucket, u64)>()
+        .unwrap();
+
+    // accounts outside of the list cannot bid
+    let other_bid = BidRequest {
+        auction: auction_component,
+        bidder: other_bidder.clone(),
+        bid: Amount(100),
+    };
+    let reason = test.execute_expect_failure(bid_transaction(&other_bid), vec![other_bidder.owner_token.clone()]);
+    assert_reject_reason(reason, "Bidder not allowed");
+
+    // but the allowed ones can
+    let allowed_bid = BidRequest {
+        auction: auction_component,
+        bidder: allowed_bidder.clone(),
+        bid: Amount(100),
+    };
+    bid(&mut test, &allowed_bid);
+    let bid_history: Vec<(ComponentAddress, Amount, u64)> =
+        test.call_method(auction_component, "get_bid_history", args![], vec![]);
+    assert_eq!(bid_history, vec![(allowed_bidder.component, Amount(100), 0)]);
+}
+
+#[test]
+fn auction_below_the_reserve_price_returns_the_nft() {
+    let TestSetup {
+        mut test,
+        auction_index_component,
+        seller,
+        seller_nft_address,
+        ..
+    } = setup();
+
+    let epoch_period = 10;
+    let reserve_price = Amount(500);
+    let result = test.execute_expect_success(
+        AuctionTxBuilder::new(auction_index_component)
+            .seller(&seller)
+            .nft(&seller_nft_address)
+            .period(epoch_period)
+            .reserve_price(reserve_price)
+            .build(),
+        vec![seller.owner_token.clone()],
+    );
+    let (auction_component, _, _) = result.finalize.execution_results[2]
+        .decode::<(ComponentAddress, Bucket, u64)>()
+        .unwrap();
+
+    // bids below the reserve are accepted while the auction is running
+    let bidder = create_account(&mut test);
+    let bid1 = BidRequest {
+        auction: auction_component,
+        bidder: bidder.clone(),
+        bid: Amount(100),
+    };
+    bid(&mut test, &bid1);
+
+    // the auction finishes without a sale
+    let seller_balance = get_account_tari_balance(&mut test, &seller);
+    let bidder_balance = get_account_tari_balance(&mut test, &bidder);
+    advance_epochs(&mut test, epoch_period + 1);
+    let result = test.execute_expect_success(
+        Transaction::builder()
+            .call_method(auction_component, "finish", args![])
             .sign(&seller.key)
             .build(),
         vec![seller.owner_token.clone()],
     );
-    assert_reject_reason(reason, "Invalid bidder account");
+
+    let event = result
+        .finalize
+        .events
+        .iter()
+        .find(|e| e.topic() == "reserve_not_met")
+        .expect("reserve_not_met event not found");
+    assert_eq!(event.get_payload("top_bid"), Some(bid1.bid.to_string()));
+    assert_eq!(event.get_payload("reserve_price"), Some(reserve_price.to_string()));
+
+    // the NFT goes back to the seller and the top bid back to the bidder
+    let seller_nft_balance = get_account_balance(&mut test, &seller, &seller_nft_address.resource_address());
+    assert_eq!(seller_nft_balance, Amount(1));
+    assert_eq!(get_account_tari_balance(&mut test, &bidder), bidder_balance + bid1.bid);
+    assert_eq!(get_account_tari_balance(&mut test, &seller), seller_balance);
+    let winner: Option<ComponentAddress> = test.call_method(auction_component, "get_winner", args![], vec![]);
+    assert_eq!(winner, None);
+}
+
+#[test]
+fn auction_without_enough_bidders_returns_the_nft() {
+    let TestSetup {
+        mut test,
+        auction_index_component,
+        seller,
+        seller_nft_address,
+        ..
+    } = setup();
+
+    let epoch_period = 10;
+    let result = test.execute_expect_success(
+        AuctionTxBuilder::new(auction_index_component)
+            .seller(&seller)
+            .nft(&seller_nft_address)
+            .period(epoch_period)
+            .min_bidders(2)
+            .build(),
+        vec![seller.owner_token.clone()],
+    );
+    let (auction_component, _, _) = result.finalize.execution_results[2]
+        .decode::<(ComponentAddress, Bucket, u64)>()
+        .unwrap();
+
+    // a single account bids
+    let bidder = create_account(&mut test);
+    let bid1 = BidRequest {
+        auction: auction_component,
+        bidder: bidder.clone(),
+        bid: Amount(100),
+    };
+    bid(&mut test, &bid1);
+    let bidder_count: u32 = test.call_method(auction_component, "get_bidder_count", args![], vec![]);
+    assert_eq!(bidder_count, 1);
+
+    // the auction finishes without a sale, the bidder gets the bid back
+    let seller_balance = get_account_tari_balance(&mut test, &seller);
+    advance_epochs(&mut test, epoch_period + 1);
+    let finish = FinishRequest {
+        auction: auction_component,
+        account: seller.clone(),
+    };
+    assert_balance_delta(&mut test, &bidder, &XTR, bid1.bid, |test| finish_auction(test, &finish));
+
+    let seller_nft_balance = get_account_balance(&mut test, &seller, &seller_nft_address.resource_address());
+    assert_eq!(seller_nft_balance, Amount(1));
+    let bidder_nft_balance = get_account_balance(&mut test, &bidder, &seller_nft_address.resource_address());
+    assert_eq!(bidder_nft_balance, Amount(0));
+    assert_eq!(get_account_tari_balance(&mut test, &seller), seller_balance);
 }
 
 #[test]
@@ -371,6 +2169,7 @@ fn it_rejects_invalid_bids() {
         min_price: Some(min_price),
         buy_price: Some(buy_price),
         epoch_period: auction_period,
+        vickrey: false,
     };
     let (auction_component, _) = create_auction(&mut test, &auction);
 
@@ -395,10 +2194,7 @@ fn it_rejects_invalid_bids() {
             .build(),
         vec![bidder.owner_token.clone()],
     );
-    assert_reject_reason(
-        reason,
-        "Invalid payment resource, the marketplace only accepts Tari (XTR) tokens",
-    );
+    assert_reject_reason(reason, "Invalid payment resource");
 
     // reject if buy price is too low
     let reason = test.execute_expect_failure(
@@ -416,22 +2212,6 @@ fn it_rejects_invalid_bids() {
     );
     assert_reject_reason(reason, "Minimum price not met");
 
-    // reject if buy price is too high (higher than the buy price)
-    let reason = test.execute_expect_failure(
-        Transaction::builder()
-            .call_method(bidder.component, "withdraw", args![XTR, buy_price + 1])
-            .put_last_instruction_output_on_workspace("payment")
-            .call_method(
-                auction_component,
-                "bid",
-                args![bidder.component, Workspace("payment")],
-            )
-            .sign(&bidder.key)
-            .build(),
-        vec![bidder.owner_token.clone()],
-    );
-    assert_reject_reason(reason, "Payment exceeds the buying price");
-
     // reject if the bidder account is not an account component
     let reason = test.execute_expect_failure(
         Transaction::builder()
@@ -451,6 +2231,21 @@ fn it_rejects_invalid_bids() {
     );
     assert_reject_reason(reason, "Invalid bidder account");
 
+    // reject if the bidder is already the highest bidder
+    let first_bid = BidRequest {
+        auction: auction_component,
+        bidder: bidder.clone(),
+        bid: min_price,
+    };
+    bid(&mut test, &first_bid);
+    let second_bid = BidRequest {
+        auction: auction_component,
+        bidder: bidder.clone(),
+        bid: min_price + Amount(50),
+    };
+    let reason = test.execute_expect_failure(bid_transaction(&second_bid), vec![bidder.owner_token.clone()]);
+    assert_reject_reason(reason, "You are already the highest bidder");
+
     // reject if the auction has expired
     set_epoch(&mut test, auction_period + 1);
     let reason = test.execute_expect_failure(
@@ -489,6 +2284,7 @@ fn it_rejects_invalid_auction_finish() {
         min_price: Some(min_price),
         buy_price: Some(buy_price),
         epoch_period: auction_period,
+        vickrey: false,
     };
     let (auction_component, _) = create_auction(&mut test, &auction);
 
@@ -516,6 +2312,54 @@ fn it_rejects_invalid_auction_finish() {
     assert_reject_reason(reason, "Auction is still in progress");
 }
 
+#[test]
+fn it_rejects_finishing_a_settled_auction() {
+    let TestSetup {
+        mut test,
+        auction_index_component,
+        seller,
+        seller_nft_address,
+        ..
+    } = setup();
+
+    let auction = AuctionRequest {
+        marketplace: auction_index_component,
+        seller: seller.clone(),
+        nft: seller_nft_address.clone(),
+        min_price: None,
+        buy_price: None,
+        epoch_period: 10,
+        vickrey: false,
+    };
+    let (auction_component, _) = create_auction(&mut test, &auction);
+
+    let bidder = create_account(&mut test);
+    let bid_req = BidRequest {
+        auction: auction_component,
+        bidder: bidder.clone(),
+        bid: Amount(100),
+    };
+    bid(&mut test, &bid_req);
+
+    // the winner settles the auction
+    advance_epochs(&mut test, auction.epoch_period + 1);
+    let finish = FinishRequest {
+        auction: auction_component,
+        account: bidder.clone(),
+    };
+    finish_auction(&mut test, &finish);
+
+    // the auction cannot be settled twice
+    let reason = test.execute_expect_failure(
+        Transaction::builder()
+            .call_method(auction_component, "finish", args![])
+            .sign(&bidder.key)
+            .build(),
+        vec![bidder.owner_token.clone()],
+    );
+    assert_reject_reason(reason, "Auction already settled");
+}
+
 #[test]
 fn it_rejects_invalid_auction_cancels() {
     let TestSetup {
@@ -536,6 +2380,7 @@ fn it_rejects_invalid_auction_cancels() {
         min_price: None,
         buy_price: None,
         epoch_period: auction_period,
+        vickrey: false,
     };
     let (auction_component, badge) = create_auction(&mut test, &auction);
 
@@ -581,6 +2426,29 @@ fn it_rejects_invalid_auction_cancels() {
         vec![seller.owner_token.clone()],
     );
     assert_reject_reason(reason, "Auction has ended");
+
+    // reject the cancel of an auction that does not exist (the index component is not an auction)
+    test.execute_expect_failure(
+        Transaction::builder()
+            .call_method(
+                seller.component,
+                "withdraw_non_fungible",
+                args![badge.resource_address(), badge.id()],
+            )
+            .put_last_instruction_output_on_workspace("badge")
+            .call_method(
+                auction_index_component,
+                "cancel",
+                args![Workspace("badge")],
+            )
+            .sign(&seller.key)
+            .build(),
+        vec![seller.owner_token.clone()],
+    );
+
+    // the badge is still in the seller account after all the failed cancellations
+    let seller_badge_balance = get_account_balance(&mut test, &seller, &badge.resource_address());
+    assert_eq!(seller_badge_balance, Amount(1));
 }
 
 #[derive(Clone, Debug)]
@@ -599,7 +2467,23 @@ struct TestSetup {
 }
 
 fn setup() -> TestSetup {
-    let mut test = TemplateTest::new(["./templates/index", "./templates/auction"]);
+    let test = TemplateTest::new(["./templates/index", "./templates/auction"]);
+    setup_with_payment(test, XTR)
+}
+
+// creates the auction index accepting bids in "accepted_payment" tokens
+fn setup_with_payment(test: TemplateTest, accepted_payment: ResourceAddress) -> TestSetup {
+    let (setup, _) = setup_with_fee(test, accepted_payment, 0);
+    setup
+}
+
+// same as "setup_with_payment", but the index charges a marketplace fee on each sale
+// returns the account that receives the fees
+fn setup_with_fee(
+    mut test: TemplateTest,
+    accepted_payment: ResourceAddress,
+    fee_per_thousand: u16,
+) -> (TestSetup, Account) {
     let auction_index_template = test.get_template_address("AuctionIndex");
     let auction_template = test.get_template_address("Auction");
 
@@ -610,11 +2494,24 @@ fn setup() -> TestSetup {
         owner_token: seller_owner_token,
         key: seller_key,
     };
+    let fee_recipient = create_account(&mut test);
 
     // create the auction index component
     let result = test.execute_expect_success(
         Transaction::builder()
-            .call_function(auction_index_template, "new", args![auction_template])
+            .call_function(
+                auction_index_template,
+                "new",
+                args![
+                    auction_template,
+                    accepted_payment,
+                    MAX_BID_HISTORY,
+                    MIN_AUCTION_PERIOD,
+                    MAX_AUCTION_PERIOD,
+                    fee_recipient.component,
+                    fee_per_thousand
+                ],
+            )
             .sign(&seller.key)
             .build(),
         vec![seller.owner_token.clone()],
@@ -627,13 +2524,14 @@ fn setup() -> TestSetup {
     let account_nft_component = create_account_nft_component(&mut test, &seller);
     let seller_nft_address = mint_account_nft(&mut test, &seller, &account_nft_component);
 
-    TestSetup {
+    let setup = TestSetup {
         test,
         auction_index_component,
         account_nft_component,
         seller,
         seller_nft_address,
-    }
+    };
+    (setup, fee_recipient)
 }
 
 fn create_account(test: &mut TemplateTest) -> Account {
@@ -645,6 +2543,35 @@ fn create_account(test: &mut TemplateTest) -> Account {
     }
 }
 
+// creates "n" accounts, each one funded with exactly "amount" XTR transferred from a single funding account
+fn create_funded_accounts(test: &mut TemplateTest, n: usize, amount: Amount) -> Vec<Account> {
+    let funder = create_account(test);
+
+    (0..n)
+        .map(|_| {
+            let (component, owner_token, key) = test.create_empty_account();
+            let account = Account {
+                component,
+                owner_token,
+                key,
+            };
+
+            test.execute_expect_success(
+                Transaction::builder()
+                    .call_method(funder.component, "withdraw", args![XTR, amount])
+                    .put_last_instruction_output_on_workspace("funds")
+                    .call_method(account.component, "deposit", args![Workspace("funds")])
+                    .sign(&funder.key)
+                    .build(),
+                vec![funder.owner_token.clone()],
+            );
+            assert_eq!(get_account_tari_balance(test, &account), amount);
+
+            account
+        })
+        .collect()
+}
+
 fn get_account_balance(
     test: &mut TemplateTest,
     account: &Account,
@@ -667,6 +2594,26 @@ fn get_account_tari_balance(test: &mut TemplateTest, account: &Account) -> Amoun
     return get_account_balance(test, account, &XTR);
 }
 
+// asserts that running "f" changes the balance of the account by exactly "expected_delta" (that can be negative)
+fn assert_balance_delta<F: FnOnce(&mut TemplateTest)>(
+    test: &mut TemplateTest,
+    account: &Account,
+    resource: &ResourceAddress,
+    expected_delta: Amount,
+    f: F,
+) {
+    let balance_before = get_account_balance(test, account, resource);
+    f(test);
+    let balance_after = get_account_balance(test, account, resource);
+    assert_eq!(
+        balance_after - balance_before,
+        expected_delta,
+        "Unexpected balance change of {} for account {}",
+        resource,
+        account.component
+    );
+}
+
 fn create_account_nft_component(test: &mut TemplateTest, account: &Account) -> ComponentAddress {
     let account_nft_template = test.get_template_address("AccountNonFungible");
     let result = test.execute_expect_success(
@@ -704,6 +2651,114 @@ fn mint_account_nft(
     minted_nft_address
 }
 
+// builds the withdraw -> create_auction -> deposit transaction used to put an NFT on auction
+struct AuctionTxBuilder {
+    marketplace: ComponentAddress,
+    seller: Option<Account>,
+    // the account passed to the auction, defaults to the seller account
+    seller_address: Option<ComponentAddress>,
+    // the resource and amount withdrawn from the seller account to be auctioned
+    bucket: Option<(ResourceAddress, Amount)>,
+    config: AuctionConfig,
+}
+
+impl AuctionTxBuilder {
+    fn new(marketplace: ComponentAddress) -> Self {
+        Self {
+            marketplace,
+            seller: None,
+            seller_address: None,
+            bucket: None,
+            config: AuctionConfig {
+                min_price: None,
+                buy_price: None,
+                epoch_period: 10,
+                vickrey: false,
+                // the index defaults to its own payment resource
+                allowed_payments: vec![],
+                allowed_bidders: None,
+                min_bidders: None,
+                reserve_price: None,
+            },
+        }
+    }
+
+    fn seller(mut self, seller: &Account) -> Self {
+        self.seller = Some(seller.clone());
+        self
+    }
+
+    fn seller_address(mut self, seller_address: ComponentAddress) -> Self {
+        self.seller_address = Some(seller_address);
+        self
+    }
+
+    fn nft(self, nft: &NonFungibleAddress) -> Self {
+        self.bucket(*nft.resource_address(), Amount(1))
+    }
+
+    // allows auctioning arbitrary buckets, useful to test invalid auctions
+    fn bucket(mut self, resource: ResourceAddress, amount: Amount) -> Self {
+        self.bucket = Some((resource, amount));
+        self
+    }
+
+    fn prices(mut self, min_price: Option<Amount>, buy_price: Option<Amount>) -> Self {
+        self.config.min_price = min_price;
+        self.config.buy_price = buy_price;
+        self
+    }
+
+    fn period(mut self, epoch_period: u64) -> Self {
+        self.config.epoch_period = epoch_period;
+        self
+    }
+
+    fn vickrey(mut self, vickrey: bool) -> Self {
+        self.config.vickrey = vickrey;
+        self
+    }
+
+    fn allowed_payments(mut self, allowed_payments: Vec<ResourceAddress>) -> Self {
+        self.config.allowed_payments = allowed_payments;
+        self
+    }
+
+    fn allowed_bidders(mut self, allowed_bidders: Vec<ComponentAddress>) -> Self {
+        self.config.allowed_bidders = Some(allowed_bidders);
+        self
+    }
+
+    fn min_bidders(mut self, min_bidders: u32) -> Self {
+        self.config.min_bidders = Some(min_bidders);
+        self
+    }
+
+    fn reserve_price(mut self, reserve_price: Amount) -> Self {
+        self.config.reserve_price = Some(reserve_price);
+        self
+    }
+
+    fn build(self) -> Transaction {
+        let seller = self.seller.expect("The seller is required to build an auction transaction");
+        let (resource, amount) = self.bucket.expect("The NFT is required to build an auction transaction");
+        let seller_address = self.seller_address.unwrap_or(seller.component);
+
+        Transaction::builder()
+            .call_method(seller.component, "withdraw", args![resource, amount])
+            .put_last_instruction_output_on_workspace("nft_bucket")
+            .call_method(
+                self.marketplace,
+                "create_auction",
+                args![Workspace("nft_bucket"), seller_address, self.config],
+            )
+            .put_last_instruction_output_on_workspace("ret")
+            .call_method(seller.component, "deposit", args![Workspace("ret.1")])
+            .sign(&seller.key)
+            .build()
+    }
+}
+
 #[derive(Clone, Debug)]
 struct AuctionRequest {
     marketplace: ComponentAddress,
@@ -712,44 +2767,39 @@ struct AuctionRequest {
     min_price: Option<Amount>,
     buy_price: Option<Amount>,
     epoch_period: u64,
+    vickrey: bool,
 }
 
 // returns the seller badge
 fn create_auction(test: &mut TemplateTest, req: &AuctionRequest) -> (ComponentAddress, NonFungibleAddress) {
     let result = test.execute_expect_success(
-        Transaction::builder()
-            .call_method(
-                req.seller.component,
-                "withdraw",
-                args![req.nft.resource_address(), Amount(1)],
-            )
-            .put_last_instruction_output_on_workspace("nft_bucket")
-            .call_method(
-                req.marketplace,
-                "create_auction",
-                args![
-                    Workspace("nft_bucket"),
-                    req.seller.component,
-                    req.min_price,
-                    req.buy_price,
-                    req.epoch_period
-                ],
-            )
-            .put_last_instruction_output_on_workspace("ret")
-            .call_method(
-                req.seller.component,
-                "deposit",
-                args![Workspace("ret.1")],
-            )
-            .sign(&req.seller.key)
+        AuctionTxBuilder::new(req.marketplace)
+            .seller(&req.seller)
+            .nft(&req.nft)
+            .prices(req.min_price, req.buy_price)
+            .period(req.epoch_period)
+            .vickrey(req.vickrey)
             .build(),
         vec![req.seller.owner_token.clone()],
     );
 
-    let (auction_component, _) = result.finalize.execution_results[2]
-        .decode::<(ComponentAddress, Bucket)>()
+    let (auction_component, _, ending_epoch) = result.finalize.execution_results[2]
+        .decode::<(ComponentAddress, Bucket, u64)>()
         .unwrap();
 
+    // the index notifies about the new auction
+    let event = result
+        .finalize
+        .events
+        .iter()
+        .find(|e| e.topic() == "auction_created")
+        .expect("auction_created event not found");
+    assert_eq!(
+        event.get_payload("auction_component"),
+        Some(auction_component.to_string())
+    );
+    assert_eq!(event.get_payload("ending_epoch"), Some(ending_epoch.to_string()));
+
     let output = test.get_previous_output_address(SubstateType::NonFungible);
     let seller_badge = output.as_non_fungible_address().unwrap().clone();
 
@@ -764,19 +2814,29 @@ struct BidRequest {
 }
 
 fn bid(test: &mut TemplateTest, req: &BidRequest) {
-    test.execute_expect_success(
-        Transaction::builder()
-            .call_method(req.bidder.component, "withdraw", args![XTR, req.bid])
-            .put_last_instruction_output_on_workspace("payment")
-            .call_method(
-                req.auction,
-                "bid",
-                args![req.bidder.component, Workspace("payment")],
-            )
-            .sign(&req.bidder.key)
-            .build(),
-        vec![req.bidder.owner_token.clone()],
-    );
+    test.execute_expect_success(bid_transaction(req), vec![req.bidder.owner_token.clone()]);
+}
+
+fn bid_transaction(req: &BidRequest) -> Transaction {
+    Transaction::builder()
+        .call_method(req.bidder.component, "withdraw", args![XTR, req.bid])
+        .put_last_instruction_output_on_workspace("payment")
+        .call_method(
+            req.auction,
+            "bid",
+            args![req.bidder.component, Workspace("payment")],
+        )
+        .sign(&req.bidder.key)
+        .build()
+}
+
+fn increase_bid_transaction(auction: ComponentAddress, bidder: &Account, amount: Amount) -> Transaction {
+    Transaction::builder()
+        .call_method(bidder.component, "withdraw", args![XTR, amount])
+        .put_last_instruction_output_on_workspace("payment")
+        .call_method(auction, "increase_bid", args![bidder.component, Workspace("payment")])
+        .sign(&bidder.key)
+        .build()
 }
 
 fn set_epoch(test: &mut TemplateTest, new_epoch: u64) {
@@ -786,6 +2846,15 @@ fn set_epoch(test: &mut TemplateTest, new_epoch: u64) {
     );
 }
 
+// moves the epoch forward relative to the current one, the epoch is 0 if it was never set
+fn advance_epochs(test: &mut TemplateTest, epochs: u64) {
+    let current_epoch = match test.virtual_substates().get(&VirtualSubstateId::CurrentEpoch) {
+        Some(VirtualSubstate::CurrentEpoch(epoch)) => *epoch,
+        _ => 0,
+    };
+    set_epoch(test, current_epoch + epochs);
+}
+
 #[derive(Clone, Debug)]
 struct FinishRequest {
     auction: ComponentAddress,
@@ -809,6 +2878,20 @@ struct CancelRequest {
     seller_badge: NonFungibleAddress,
 }
 
+fn claim_cancelled_refund(test: &mut TemplateTest, auction: ComponentAddress, bidder: &Account) {
+    test.execute_expect_success(
+        claim_cancelled_refund_transaction(auction, bidder),
+        vec![bidder.owner_token.clone()],
+    );
+}
+
+fn claim_cancelled_refund_transaction(auction: ComponentAddress, bidder: &Account) -> Transaction {
+    Transaction::builder()
+        .call_method(auction, "claim_cancelled_refund", args![bidder.component])
+        .sign(&bidder.key)
+        .build()
+}
+
 fn cancel_auction(test: &mut TemplateTest, req: &CancelRequest) {
     test.execute_expect_success(
         Transaction::builder()
@@ -828,3 +2911,79 @@ fn cancel_auction(test: &mut TemplateTest, req: &CancelRequest) {
         vec![req.account.owner_token.clone()],
     );
 }
+
+fn set_buy_price_transaction(
+    auction: ComponentAddress,
+    seller: &Account,
+    seller_badge: &NonFungibleAddress,
+    buy_price: Amount,
+) -> Transaction {
+    Transaction::builder()
+        .call_method(
+            seller.component,
+            "withdraw_non_fungible",
+            args![seller_badge.resource_address(), seller_badge.id()],
+        )
+        .put_last_instruction_output_on_workspace("seller_badge")
+        .call_method(auction, "set_buy_price", args![Workspace("seller_badge"), buy_price])
+        .put_last_instruction_output_on_workspace("returned_badge")
+        .call_method(seller.component, "deposit", args![Workspace("returned_badge")])
+        .sign(&seller.key)
+        .build()
+}
+
+// withdraws the seller badge into the "seller_badge" workspace entry for the call, and deposits it back afterwards
+fn seller_badge_transaction(
+    seller: &Account,
+    seller_badge: &NonFungibleAddress,
+    component: ComponentAddress,
+    method: &str,
+    args: Vec<Arg>,
+) -> Transaction {
+    Transaction::builder()
+        .call_method(
+            seller.component,
+            "withdraw_non_fungible",
+            args![seller_badge.resource_address(), seller_badge.id()],
+        )
+        .put_last_instruction_output_on_workspace("seller_badge")
+        .call_method(component, method, args)
+        .put_last_instruction_output_on_workspace("returned_badge")
+        .call_method(seller.component, "deposit", args![Workspace("returned_badge")])
+        .sign(&seller.key)
+        .build()
+}
+
+#[derive(Clone, Debug)]
+struct ExtendRequest {
+    auction: ComponentAddress,
+    account: Account,
+    seller_badge: NonFungibleAddress,
+    extra_epochs: u64,
+}
+
+fn extend_auction(test: &mut TemplateTest, req: &ExtendRequest) {
+    test.execute_expect_success(
+        Transaction::builder()
+            .call_method(
+                req.account.component,
+                "withdraw_non_fungible",
+                args![req.seller_badge.resource_address(), req.seller_badge.id()],
+            )
+            .put_last_instruction_output_on_workspace("seller_badge")
+            .call_method(
+                req.auction,
+                "extend",
+                args![Workspace("seller_badge"), req.extra_epochs],
+            )
+            .put_last_instruction_output_on_workspace("returned_badge")
+            .call_method(
+                req.account.component,
+                "deposit",
+                args![Workspace("returned_badge")],
+            )
+            .sign(&req.account.key)
+            .build(),
+        vec![req.account.owner_token.clone()],
+    );
+}
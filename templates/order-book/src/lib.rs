@@ -0,0 +1,273 @@
+//   Copyright 2024. The Tari Project
+//
+//   Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//   following conditions are met:
+//
+//   1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//   disclaimer.
+//
+//   2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//   following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//   3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//   products derived from this software without specific prior written permission.
+//
+//   THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//   INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//   DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//   SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//   SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//   WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//   USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+
+use std::collections::BTreeMap;
+
+use account_utils::assert_component_is_account;
+use tari_template_lib::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Order {
+    account: ComponentAddress,
+    side: Side,
+    // amount of quote tokens paid for each base token
+    price: Amount,
+    // amount of base tokens still to be filled
+    remaining: Amount,
+    // buy orders escrow quote tokens, sell orders escrow base tokens
+    vault: Vault,
+}
+
+#[template]
+mod order_book {
+    use super::*;
+
+    /// Limit order book for a single pair of resources
+    /// Orders escrow their funds when placed, and anyone can call "match_orders" to settle the crossing orders.
+    /// Fills are executed at the price of the sell order, the buyer gets back the difference with its own price.
+    pub struct OrderBook {
+        base_resource: ResourceAddress,
+        quote_resource: ResourceAddress,
+
+        // all the orders ever placed, the filled or cancelled ones are kept with no remaining amount
+        orders: BTreeMap<u64, Order>,
+        next_order_id: u64,
+
+        // ids of the open orders at each price level, in placement order
+        bids: BTreeMap<Amount, Vec<u64>>,
+        asks: BTreeMap<Amount, Vec<u64>>,
+
+        // the badge of each order has the order id, and it's needed to cancel the order
+        badge_resource: ResourceAddress,
+    }
+
+    impl OrderBook {
+        pub fn new(base_resource: ResourceAddress, quote_resource: ResourceAddress) -> Component<Self> {
+            assert!(base_resource != quote_resource, "The base and quote resources must be different");
+
+            // the address of the component is allocated beforehand, so the badges can be restricted to it
+            let component_alloc = CallerContext::allocate_component_address(None);
+            let component_rule = AccessRule::Restricted(RestrictedAccessRule::Require(RequireRule::Require(
+                RuleRequirement::ScopedToComponent(*component_alloc.address()),
+            )));
+
+            // only this component can mint and burn the badges, as each one controls an escrowed order
+            let badge_resource = ResourceBuilder::non_fungible()
+                .mintable(component_rule.clone())
+                .burnable(component_rule)
+                .build();
+
+            Component::new(Self {
+                base_resource,
+                quote_resource,
+                orders: BTreeMap::new(),
+                next_order_id: 0,
+                bids: BTreeMap::new(),
+                asks: BTreeMap::new(),
+                badge_resource,
+            })
+            .with_access_rules(AccessRules::allow_all())
+            .with_address_allocation(component_alloc)
+            .create()
+        }
+
+        // buy orders pay "price" quote tokens for each base token, so the bucket must hold a multiple of the price
+        // returns the badge needed to cancel the order
+        pub fn place_limit_order(
+            &mut self,
+            account_address: ComponentAddress,
+            side: Side,
+            price: Amount,
+            bucket: Bucket,
+        ) -> Bucket {
+            assert!(price.is_positive(), "Invalid price");
+
+            // the fills are deposited into the account of the order
+            assert_component_is_account(account_address);
+
+            let amount = match side {
+                Side::Buy => {
+                    assert!(
+                        bucket.resource_address() == self.quote_resource,
+                        "Buy orders must be paid in {}",
+                        self.quote_resource
+                    );
+                    assert!(
+                        bucket.amount().value() % price.value() == 0,
+                        "The payment must be a multiple of the price"
+                    );
+                    bucket.amount() / price
+                },
+                Side::Sell => {
+                    assert!(
+                        bucket.resource_address() == self.base_resource,
+                        "Sell orders must escrow {}",
+                        self.base_resource
+                    );
+                    bucket.amount()
+                },
+            };
+            assert!(amount.is_positive(), "Invalid order amount");
+
+            let order_id = self.next_order_id;
+            self.next_order_id += 1;
+            self.orders.insert(order_id, Order {
+                account: account_address,
+                side,
+                price,
+                remaining: amount,
+                vault: Vault::from_bucket(bucket),
+            });
+            self.book_mut(side).entry(price).or_default().push(order_id);
+
+            ResourceManager::get(self.badge_resource).mint_non_fungible(NonFungibleId::from_u64(order_id), &(), &())
+        }
+
+        // returns the funds still escrowed by the order, the badge is burned
+        pub fn cancel_order(&mut self, order_id: u64, badge: Bucket) -> Bucket {
+            assert!(
+                badge.resource_address() == self.badge_resource,
+                "Invalid order badge"
+            );
+            assert!(
+                badge.get_non_fungible_ids() == vec![NonFungibleId::from_u64(order_id)],
+                "The badge does not belong to order {}",
+                order_id
+            );
+
+            let order = self
+                .orders
+                .get_mut(&order_id)
+                .unwrap_or_else(|| panic!("Order {} does not exist", order_id));
+            assert!(!order.remaining.is_zero(), "The order is already closed");
+            order.remaining = Amount::zero();
+            let (side, price) = (order.side, order.price);
+            let escrow_bucket = order.vault.withdraw_all();
+
+            self.remove_from_book(side, price, order_id);
+            badge.burn();
+
+            escrow_bucket
+        }
+
+        // settles the best bid against the best ask until they do not cross anymore
+        // returns the amount of fills
+        pub fn match_orders(&mut self) -> u64 {
+            let mut fills = 0;
+
+            while let (Some(bid_id), Some(ask_id)) = (self.best_order_id(Side::Buy), self.best_order_id(Side::Sell)) {
+                if self.orders[&bid_id].price < self.orders[&ask_id].price {
+                    break;
+                }
+                self.fill(bid_id, ask_id);
+                fills += 1;
+            }
+
+            fills
+        }
+
+        // highest price of the open buy orders
+        pub fn get_best_bid(&self) -> Option<Amount> {
+            self.bids.keys().next_back().copied()
+        }
+
+        // lowest price of the open sell orders
+        pub fn get_best_ask(&self) -> Option<Amount> {
+            self.asks.keys().next().copied()
+        }
+
+        // amount of base tokens still to be filled, zero for filled or cancelled orders
+        pub fn get_order_remaining(&self, order_id: u64) -> Amount {
+            self.orders
+                .get(&order_id)
+                .map(|order| order.remaining)
+                .unwrap_or_else(|| panic!("Order {} does not exist", order_id))
+        }
+
+        pub fn get_badge_resource(&self) -> ResourceAddress {
+            self.badge_resource
+        }
+
+        fn book_mut(&mut self, side: Side) -> &mut BTreeMap<Amount, Vec<u64>> {
+            match side {
+                Side::Buy => &mut self.bids,
+                Side::Sell => &mut self.asks,
+            }
+        }
+
+        // the oldest order at the best price of the side
+        fn best_order_id(&self, side: Side) -> Option<u64> {
+            let best_level = match side {
+                Side::Buy => self.bids.values().next_back(),
+                Side::Sell => self.asks.values().next(),
+            };
+            best_level.map(|order_ids| order_ids[0])
+        }
+
+        fn remove_from_book(&mut self, side: Side, price: Amount, order_id: u64) {
+            let book = self.book_mut(side);
+            if let Some(order_ids) = book.get_mut(&price) {
+                order_ids.retain(|id| *id != order_id);
+                if order_ids.is_empty() {
+                    book.remove(&price);
+                }
+            }
+        }
+
+        fn fill(&mut self, bid_id: u64, ask_id: u64) {
+            let mut bid = self.orders.remove(&bid_id).unwrap();
+            let mut ask = self.orders.remove(&ask_id).unwrap();
+            let quantity = bid.remaining.min(ask.remaining);
+
+            // the seller gets its own price, and the buyer is refunded the difference with the bid price
+            let base_bucket = ask.vault.withdraw(quantity);
+            let payment_bucket = bid.vault.withdraw(ask.price * quantity);
+            ComponentManager::get(bid.account).call::<_, ()>("deposit".to_string(), args![base_bucket]);
+            ComponentManager::get(ask.account).call::<_, ()>("deposit".to_string(), args![payment_bucket]);
+
+            let refund = (bid.price - ask.price) * quantity;
+            if refund.is_positive() {
+                let refund_bucket = bid.vault.withdraw(refund);
+                ComponentManager::get(bid.account).call::<_, ()>("deposit".to_string(), args![refund_bucket]);
+            }
+
+            bid.remaining = bid.remaining - quantity;
+            ask.remaining = ask.remaining - quantity;
+            if bid.remaining.is_zero() {
+                self.remove_from_book(Side::Buy, bid.price, bid_id);
+            }
+            if ask.remaining.is_zero() {
+                self.remove_from_book(Side::Sell, ask.price, ask_id);
+            }
+
+            self.orders.insert(bid_id, bid);
+            self.orders.insert(ask_id, ask);
+        }
+    }
+}
@@ -0,0 +1,219 @@
+//   Copyright 2024 The Tari Project
+//   SPDX-License-Identifier: BSD-3-Clause
+
+use tari_template_lib::args;
+use tari_template_lib::constants::XTR;
+use tari_template_lib::models::{Amount, ComponentAddress, NonFungibleAddress, ResourceAddress};
+use tari_template_test_tooling::crypto::RistrettoSecretKey;
+use tari_template_test_tooling::support::assert_error::assert_reject_reason;
+use tari_template_test_tooling::{SubstateType, TemplateTest};
+use tari_transaction::Transaction;
+
+// mirrors the "Side" enum of the order book template
+#[derive(Clone, Copy, Debug, serde::Serialize)]
+enum Side {
+    Buy,
+    Sell,
+}
+
+#[test]
+fn it_fills_crossing_orders() {
+    let OrderBookTest {
+        mut test,
+        order_book_component,
+        faucet_component,
+        base_resource,
+    } = setup();
+
+    // the seller sells 10 tokens at 5 XTR each
+    let seller = create_account(&mut test);
+    test.execute_expect_success(
+        Transaction::builder()
+            .call_method(faucet_component, "take_free_coins", args![])
+            .put_last_instruction_output_on_workspace("coins")
+            .call_method(seller.component, "deposit", args![Workspace("coins")])
+            .sign(&seller.key)
+            .build(),
+        vec![seller.owner_token.clone()],
+    );
+    test.execute_expect_success(
+        place_order_transaction(order_book_component, &seller, Side::Sell, Amount(5), base_resource, Amount(10)),
+        vec![seller.owner_token.clone()],
+    );
+
+    // the buyer is willing to pay up to 6 XTR for each token
+    let buyer = create_account(&mut test);
+    test.execute_expect_success(
+        place_order_transaction(order_book_component, &buyer, Side::Buy, Amount(6), XTR, Amount(60)),
+        vec![buyer.owner_token.clone()],
+    );
+    let best_bid: Option<Amount> = test.call_method(order_book_component, "get_best_bid", args![], vec![]);
+    assert_eq!(best_bid, Some(Amount(6)));
+    let best_ask: Option<Amount> = test.call_method(order_book_component, "get_best_ask", args![], vec![]);
+    assert_eq!(best_ask, Some(Amount(5)));
+
+    // the orders are filled at the price of the seller
+    let seller_balance = get_account_balance(&mut test, &seller, &XTR);
+    let buyer_balance = get_account_balance(&mut test, &buyer, &XTR);
+    let result = test.execute_expect_success(
+        Transaction::builder()
+            .call_method(order_book_component, "match_orders", args![])
+            .sign(&buyer.key)
+            .build(),
+        vec![buyer.owner_token.clone()],
+    );
+    let fills = result.finalize.execution_results[0].decode::<u64>().unwrap();
+    assert_eq!(fills, 1);
+
+    assert_eq!(get_account_balance(&mut test, &buyer, &base_resource), Amount(10));
+    assert_eq!(get_account_balance(&mut test, &buyer, &XTR), buyer_balance + Amount(10));
+    assert_eq!(get_account_balance(&mut test, &seller, &XTR), seller_balance + Amount(50));
+    for order_id in [0u64, 1] {
+        let remaining: Amount =
+            test.call_method(order_book_component, "get_order_remaining", args![order_id], vec![]);
+        assert_eq!(remaining, Amount(0));
+    }
+    let best_bid: Option<Amount> = test.call_method(order_book_component, "get_best_bid", args![], vec![]);
+    assert_eq!(best_bid, None);
+    let best_ask: Option<Amount> = test.call_method(order_book_component, "get_best_ask", args![], vec![]);
+    assert_eq!(best_ask, None);
+}
+
+#[test]
+fn it_returns_the_escrow_of_cancelled_orders() {
+    let OrderBookTest {
+        mut test,
+        order_book_component,
+        ..
+    } = setup();
+    let badge_resource: ResourceAddress =
+        test.call_method(order_book_component, "get_badge_resource", args![], vec![]);
+
+    let buyer = create_account(&mut test);
+    let buyer_balance = get_account_balance(&mut test, &buyer, &XTR);
+
+    // buy orders must pay for whole tokens
+    let reason = test.execute_expect_failure(
+        place_order_transaction(order_book_component, &buyer, Side::Buy, Amount(6), XTR, Amount(31)),
+        vec![buyer.owner_token.clone()],
+    );
+    assert_reject_reason(reason, "The payment must be a multiple of the price");
+
+    test.execute_expect_success(
+        place_order_transaction(order_book_component, &buyer, Side::Buy, Amount(6), XTR, Amount(30)),
+        vec![buyer.owner_token.clone()],
+    );
+    assert_eq!(get_account_balance(&mut test, &buyer, &XTR), buyer_balance - Amount(30));
+
+    // the badge only cancels its own order
+    let reason = test.execute_expect_failure(
+        cancel_order_transaction(order_book_component, &buyer, badge_resource, 5),
+        vec![buyer.owner_token.clone()],
+    );
+    assert_reject_reason(reason, "The badge does not belong to order 5");
+
+    test.execute_expect_success(
+        cancel_order_transaction(order_book_component, &buyer, badge_resource, 0),
+        vec![buyer.owner_token.clone()],
+    );
+    assert_eq!(get_account_balance(&mut test, &buyer, &XTR), buyer_balance);
+    assert_eq!(get_account_balance(&mut test, &buyer, &badge_resource), Amount(0));
+    let best_bid: Option<Amount> = test.call_method(order_book_component, "get_best_bid", args![], vec![]);
+    assert_eq!(best_bid, None);
+}
+
+#[derive(Clone, Debug)]
+struct Account {
+    pub component: ComponentAddress,
+    pub owner_token: NonFungibleAddress,
+    pub key: RistrettoSecretKey,
+}
+
+struct OrderBookTest {
+    test: TemplateTest,
+    order_book_component: ComponentAddress,
+    faucet_component: ComponentAddress,
+    base_resource: ResourceAddress,
+}
+
+// the order book trades the faucet token against XTR
+fn setup() -> OrderBookTest {
+    let mut test = TemplateTest::new([".", "../faucet"]);
+
+    let faucet_component: ComponentAddress =
+        test.call_function("TestFaucet", "mint", args![Amount(1_000_000)], vec![]);
+    let base_resource = test
+        .get_previous_output_address(SubstateType::Resource)
+        .as_resource_address()
+        .unwrap();
+
+    let order_book_component: ComponentAddress =
+        test.call_function("OrderBook", "new", args![base_resource, XTR], vec![]);
+
+    OrderBookTest {
+        test,
+        order_book_component,
+        faucet_component,
+        base_resource,
+    }
+}
+
+fn create_account(test: &mut TemplateTest) -> Account {
+    let (component, owner_token, key) = test.create_funded_account();
+    Account {
+        component,
+        owner_token,
+        key,
+    }
+}
+
+fn get_account_balance(test: &mut TemplateTest, account: &Account, resource: &ResourceAddress) -> Amount {
+    let result = test.execute_expect_success(
+        Transaction::builder()
+            .call_method(account.component, "balance", args![resource])
+            .sign(&account.key)
+            .build(),
+        vec![account.owner_token.clone()],
+    );
+    result.finalize.execution_results[0]
+        .decode::<Amount>()
+        .unwrap()
+}
+
+fn place_order_transaction(
+    order_book_component: ComponentAddress,
+    account: &Account,
+    side: Side,
+    price: Amount,
+    resource: ResourceAddress,
+    amount: Amount,
+) -> Transaction {
+    Transaction::builder()
+        .call_method(account.component, "withdraw", args![resource, amount])
+        .put_last_instruction_output_on_workspace("escrow")
+        .call_method(
+            order_book_component,
+            "place_limit_order",
+            args![account.component, side, price, Workspace("escrow")],
+        )
+        .put_last_instruction_output_on_workspace("badge")
+        .call_method(account.component, "deposit", args![Workspace("badge")])
+        .sign(&account.key)
+        .build()
+}
+
+fn cancel_order_transaction(
+    order_book_component: ComponentAddress,
+    account: &Account,
+    badge_resource: ResourceAddress,
+    order_id: u64,
+) -> Transaction {
+    Transaction::builder()
+        .call_method(account.component, "withdraw", args![badge_resource, Amount(1)])
+        .put_last_instruction_output_on_workspace("badge")
+        .call_method(order_book_component, "cancel_order", args![order_id, Workspace("badge")])
+        .put_last_instruction_output_on_workspace("escrow")
+        .call_method(account.component, "deposit", args![Workspace("escrow")])
+        .sign(&account.key)
+        .build()
+}
@@ -0,0 +1,153 @@
+//   Copyright 2024. The Tari Project
+//
+//   Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//   following conditions are met:
+//
+//   1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//   disclaimer.
+//
+//   2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//   following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//   3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//   products derived from this software without specific prior written permission.
+//
+//   THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//   INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//   DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//   SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//   SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//   WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//   USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+
+use std::collections::BTreeMap;
+
+use account_utils::assert_component_is_account;
+use tari_template_lib::prelude::*;
+
+#[template]
+mod raffle {
+    use super::*;
+
+    /// Raffle where each ticket is a NFT, and the whole pot goes to the holder of the drawn ticket
+    /// The randomness comes from the random NFT ids generated by the engine, which are deterministic for a given
+    /// transaction. Anyone who can predict or choose the transaction of the draw (e.g. the validators, or a caller
+    /// retrying until it wins) can bias the result, so this is not suitable for raffles with significant pots.
+    pub struct Raffle {
+        // price (in XTR) of each ticket
+        ticket_price: Amount,
+
+        // tickets can be bought until this epoch, and the raffle can be drawn afterwards
+        draw_epoch: u64,
+
+        // holds the payments of all the tickets
+        pot: Vault,
+
+        // the prize is paid to the account that bought the ticket, even if the ticket NFT is transferred afterwards
+        ticket_resource: ResourceAddress,
+        tickets: BTreeMap<NonFungibleId, ComponentAddress>,
+
+        // set once the raffle is drawn
+        winner: Option<ComponentAddress>,
+    }
+
+    impl Raffle {
+        pub fn new(ticket_price: Amount, draw_epoch: u64) -> Component<Self> {
+            assert!(ticket_price.is_positive(), "Invalid ticket price");
+            assert!(
+                draw_epoch > Consensus::current_epoch(),
+                "The draw must be in the future"
+            );
+
+            // the address of the component is allocated beforehand, so the tickets can be restricted to it
+            let component_alloc = CallerContext::allocate_component_address(None);
+            let component_rule = AccessRule::Restricted(RestrictedAccessRule::Require(RequireRule::Require(
+                RuleRequirement::ScopedToComponent(*component_alloc.address()),
+            )));
+
+            // only this component can mint tickets, so every ticket has been paid for
+            let ticket_resource = ResourceBuilder::non_fungible().mintable(component_rule).build();
+
+            Component::new(Self {
+                ticket_price,
+                draw_epoch,
+                pot: Vault::new_empty(XTR),
+                ticket_resource,
+                tickets: BTreeMap::new(),
+                winner: None,
+            })
+            .with_access_rules(AccessRules::allow_all())
+            .with_address_allocation(component_alloc)
+            .create()
+        }
+
+        // sends a ticket to the buyer account
+        pub fn buy_ticket(&mut self, buyer_account_address: ComponentAddress, payment: Bucket) {
+            assert!(
+                Consensus::current_epoch() < self.draw_epoch,
+                "The ticket sale has ended"
+            );
+
+            assert_eq!(
+                payment.resource_address(),
+                XTR,
+                "Invalid payment resource, the raffle only accepts Tari (XTR) tokens"
+            );
+            assert!(payment.amount() == self.ticket_price, "Payment does not match the ticket price");
+
+            // validate that the buyer account is really an account, so we can pay the prize
+            assert_component_is_account(buyer_account_address);
+
+            self.pot.deposit(payment);
+
+            let ticket_id = NonFungibleId::random();
+            self.tickets.insert(ticket_id.clone(), buyer_account_address);
+            let ticket_bucket = ResourceManager::get(self.ticket_resource).mint_non_fungible(ticket_id, &(), &());
+            ComponentManager::get(buyer_account_address).call::<_, ()>("deposit".to_string(), args![ticket_bucket]);
+        }
+
+        // pays the whole pot to the buyer of the drawn ticket
+        pub fn draw(&mut self) {
+            assert!(
+                Consensus::current_epoch() >= self.draw_epoch,
+                "The raffle can only be drawn after epoch {}",
+                self.draw_epoch
+            );
+            assert!(self.winner.is_none(), "The raffle was already drawn");
+            assert!(!self.tickets.is_empty(), "No tickets were sold");
+
+            // the ticket ids are random, so we pick the first ticket after a new random id (wrapping around)
+            let seed = NonFungibleId::random();
+            let winner = self
+                .tickets
+                .range(seed..)
+                .chain(self.tickets.iter())
+                .map(|(_, buyer)| *buyer)
+                .next()
+                .unwrap();
+
+            let prize_bucket = self.pot.withdraw_all();
+            ComponentManager::get(winner).call::<_, ()>("deposit".to_string(), args![prize_bucket]);
+
+            self.winner = Some(winner);
+        }
+
+        pub fn get_pot(&self) -> Amount {
+            self.pot.balance()
+        }
+
+        pub fn get_tickets_sold(&self) -> u64 {
+            self.tickets.len() as u64
+        }
+
+        pub fn get_ticket_resource(&self) -> ResourceAddress {
+            self.ticket_resource
+        }
+
+        // None until the raffle is drawn
+        pub fn get_winner(&self) -> Option<ComponentAddress> {
+            self.winner
+        }
+    }
+}
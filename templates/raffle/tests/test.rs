@@ -0,0 +1,174 @@
+//   Copyright 2024 The Tari Project
+//   SPDX-License-Identifier: BSD-3-Clause
+
+use tari_engine_types::virtual_substate::{VirtualSubstate, VirtualSubstateId};
+use tari_template_lib::args;
+use tari_template_lib::constants::XTR;
+use tari_template_lib::models::{Amount, ComponentAddress, NonFungibleAddress, ResourceAddress};
+use tari_template_test_tooling::crypto::RistrettoSecretKey;
+use tari_template_test_tooling::support::assert_error::assert_reject_reason;
+use tari_template_test_tooling::TemplateTest;
+use tari_transaction::Transaction;
+
+const TICKET_PRICE: Amount = Amount(50);
+const DRAW_EPOCH: u64 = 10;
+
+#[test]
+fn it_sells_tickets_into_the_pot() {
+    let RaffleTest {
+        mut test,
+        raffle_component,
+    } = setup();
+    let ticket_resource: ResourceAddress = test.call_method(raffle_component, "get_ticket_resource", args![], vec![]);
+
+    let buyer = create_account(&mut test);
+    for _ in 0..2 {
+        test.execute_expect_success(
+            buy_ticket_transaction(raffle_component, &buyer, TICKET_PRICE),
+            vec![buyer.owner_token.clone()],
+        );
+    }
+    assert_eq!(get_account_balance(&mut test, &buyer, &ticket_resource), Amount(2));
+    let tickets_sold: u64 = test.call_method(raffle_component, "get_tickets_sold", args![], vec![]);
+    assert_eq!(tickets_sold, 2);
+    let pot: Amount = test.call_method(raffle_component, "get_pot", args![], vec![]);
+    assert_eq!(pot, TICKET_PRICE * Amount(2));
+
+    // payments must match the ticket price
+    let reason = test.execute_expect_failure(
+        buy_ticket_transaction(raffle_component, &buyer, TICKET_PRICE + Amount(1)),
+        vec![buyer.owner_token.clone()],
+    );
+    assert_reject_reason(reason, "Payment does not match the ticket price");
+
+    // no more tickets are sold once the draw epoch is reached
+    set_epoch(&mut test, DRAW_EPOCH);
+    let reason = test.execute_expect_failure(
+        buy_ticket_transaction(raffle_component, &buyer, TICKET_PRICE),
+        vec![buyer.owner_token.clone()],
+    );
+    assert_reject_reason(reason, "The ticket sale has ended");
+}
+
+#[test]
+fn it_pays_the_pot_to_the_winner() {
+    let RaffleTest {
+        mut test,
+        raffle_component,
+    } = setup();
+
+    let buyer1 = create_account(&mut test);
+    let buyer2 = create_account(&mut test);
+    for buyer in [&buyer1, &buyer2, &buyer2] {
+        test.execute_expect_success(
+            buy_ticket_transaction(raffle_component, buyer, TICKET_PRICE),
+            vec![buyer.owner_token.clone()],
+        );
+    }
+    let pot = TICKET_PRICE * Amount(3);
+
+    // the raffle cannot be drawn before the deadline
+    set_epoch(&mut test, DRAW_EPOCH - 1);
+    let reason = test.execute_expect_failure(
+        draw_transaction(raffle_component, &buyer1),
+        vec![buyer1.owner_token.clone()],
+    );
+    assert_reject_reason(reason, "The raffle can only be drawn after epoch 10");
+
+    set_epoch(&mut test, DRAW_EPOCH);
+    let buyer1_balance = get_account_balance(&mut test, &buyer1, &XTR);
+    let buyer2_balance = get_account_balance(&mut test, &buyer2, &XTR);
+    test.execute_expect_success(
+        draw_transaction(raffle_component, &buyer1),
+        vec![buyer1.owner_token.clone()],
+    );
+
+    // the winner is one of the buyers, and gets the full pot
+    let winner: Option<ComponentAddress> = test.call_method(raffle_component, "get_winner", args![], vec![]);
+    let winner = winner.unwrap();
+    let (winner_account, winner_balance) = if winner == buyer1.component {
+        (&buyer1, buyer1_balance)
+    } else {
+        assert_eq!(winner, buyer2.component);
+        (&buyer2, buyer2_balance)
+    };
+    assert_eq!(get_account_balance(&mut test, winner_account, &XTR), winner_balance + pot);
+    let pot_after_draw: Amount = test.call_method(raffle_component, "get_pot", args![], vec![]);
+    assert_eq!(pot_after_draw, Amount(0));
+
+    // the raffle can only be drawn once
+    let reason = test.execute_expect_failure(
+        draw_transaction(raffle_component, &buyer1),
+        vec![buyer1.owner_token.clone()],
+    );
+    assert_reject_reason(reason, "The raffle was already drawn");
+}
+
+#[derive(Clone, Debug)]
+struct Account {
+    pub component: ComponentAddress,
+    pub owner_token: NonFungibleAddress,
+    pub key: RistrettoSecretKey,
+}
+
+struct RaffleTest {
+    test: TemplateTest,
+    raffle_component: ComponentAddress,
+}
+
+fn setup() -> RaffleTest {
+    let mut test = TemplateTest::new(["."]);
+    let raffle_component: ComponentAddress =
+        test.call_function("Raffle", "new", args![TICKET_PRICE, DRAW_EPOCH], vec![]);
+
+    RaffleTest { test, raffle_component }
+}
+
+fn create_account(test: &mut TemplateTest) -> Account {
+    let (component, owner_token, key) = test.create_funded_account();
+    Account {
+        component,
+        owner_token,
+        key,
+    }
+}
+
+fn get_account_balance(test: &mut TemplateTest, account: &Account, resource: &ResourceAddress) -> Amount {
+    let result = test.execute_expect_success(
+        Transaction::builder()
+            .call_method(account.component, "balance", args![resource])
+            .sign(&account.key)
+            .build(),
+        vec![account.owner_token.clone()],
+    );
+    result.finalize.execution_results[0]
+        .decode::<Amount>()
+        .unwrap()
+}
+
+fn set_epoch(test: &mut TemplateTest, new_epoch: u64) {
+    test.set_virtual_substate(
+        VirtualSubstateId::CurrentEpoch,
+        VirtualSubstate::CurrentEpoch(new_epoch),
+    );
+}
+
+fn buy_ticket_transaction(raffle_component: ComponentAddress, buyer: &Account, payment: Amount) -> Transaction {
+    Transaction::builder()
+        .call_method(buyer.component, "withdraw", args![XTR, payment])
+        .put_last_instruction_output_on_workspace("payment")
+        .call_method(
+            raffle_component,
+            "buy_ticket",
+            args![buyer.component, Workspace("payment")],
+        )
+        .sign(&buyer.key)
+        .build()
+}
+
+fn draw_transaction(raffle_component: ComponentAddress, caller: &Account) -> Transaction {
+    Transaction::builder()
+        .call_method(raffle_component, "draw", args![])
+        .sign(&caller.key)
+        .build()
+}
@@ -0,0 +1,149 @@
+//   Copyright 2024. The Tari Project
+//
+//   Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//   following conditions are met:
+//
+//   1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//   disclaimer.
+//
+//   2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//   following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//   3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//   products derived from this software without specific prior written permission.
+//
+//   THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//   INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//   DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//   SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//   SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//   WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//   USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+
+use account_utils::assert_component_is_account;
+use tari_template_lib::prelude::*;
+
+#[template]
+mod rental {
+    use super::*;
+
+    /// Time-bound rental of a single NFT
+    /// The NFT stays escrowed in the component, the renter gets an access badge that is only meaningful while
+    /// `is_rented` returns true. Other templates can gate features on holding the current renter badge.
+    pub struct Rental {
+        // The NFT will be locked until the owner reclaims it
+        vault: Vault,
+
+        // address of the account component of the owner, that receives the rental payments
+        owner_address: ComponentAddress,
+
+        // price (in XTR) of each rented epoch
+        price_per_epoch: Amount,
+        max_epochs: u64,
+
+        // badge of the current (or last) renter, a new resource is created on each rental
+        renter_badge_resource: Option<ResourceAddress>,
+        // epoch in which the current (or last) rental ends
+        rental_ends_at: Option<u64>,
+
+        // set when the owner reclaims the NFT, no further rentals are allowed afterwards
+        closed: bool,
+    }
+
+    impl Rental {
+        pub fn list(
+            nft_bucket: Bucket,
+            owner_address: ComponentAddress,
+            price_per_epoch: Amount,
+            max_epochs: u64,
+        ) -> Component<Self> {
+            assert!(
+                nft_bucket.resource_type() == ResourceType::NonFungible,
+                "The resource is not a NFT"
+            );
+
+            assert!(nft_bucket.amount() == Amount(1), "Can only rent a single NFT");
+
+            assert!(price_per_epoch.is_positive(), "Invalid price per epoch");
+            assert!(max_epochs > 0, "Invalid rental period");
+
+            // needed to ensure that we can send the payments and return the NFT
+            assert_component_is_account(owner_address);
+
+            Component::new(Self {
+                vault: Vault::from_bucket(nft_bucket),
+                owner_address,
+                price_per_epoch,
+                max_epochs,
+                renter_badge_resource: None,
+                rental_ends_at: None,
+                closed: false,
+            })
+            .with_access_rules(AccessRules::allow_all())
+            .create()
+        }
+
+        // the payment is sent to the owner right away, returns the access badge of the renter
+        pub fn rent(&mut self, renter_account_address: ComponentAddress, payment: Bucket, epochs: u64) -> Bucket {
+            assert!(!self.closed, "The NFT is no longer listed");
+            assert!(!self.is_rented(), "The NFT is already rented");
+            assert!(
+                epochs > 0 && epochs <= self.max_epochs,
+                "The NFT can be rented for at most {} epochs",
+                self.max_epochs
+            );
+
+            assert_eq!(
+                payment.resource_address(),
+                XTR,
+                "Invalid payment resource, the rental only accepts Tari (XTR) tokens"
+            );
+            assert!(
+                payment.amount() == self.price_per_epoch * Amount::new(epochs as i64),
+                "Payment does not match the rental price"
+            );
+
+            assert_component_is_account(renter_account_address);
+
+            ComponentManager::get(self.owner_address).call::<_, ()>("deposit".to_string(), args![payment]);
+
+            // we make sure that only the initial badge will be minted
+            let renter_badge_bucket = ResourceBuilder::non_fungible()
+                .mintable(AccessRule::DenyAll)
+                .initial_supply_with_data(Some((NonFungibleId::random(), (&(), &()))));
+            self.renter_badge_resource = Some(renter_badge_bucket.resource_address());
+            self.rental_ends_at = Some(Consensus::current_epoch() + epochs);
+
+            renter_badge_bucket
+        }
+
+        // returns the NFT to the owner, only allowed when there is no ongoing rental
+        pub fn reclaim(&mut self) {
+            assert!(!self.closed, "The NFT was already reclaimed");
+            assert!(!self.is_rented(), "The rental period has not ended");
+
+            let nft_bucket = self.vault.withdraw_all();
+            ComponentManager::get(self.owner_address).call::<_, ()>("deposit".to_string(), args![nft_bucket]);
+
+            self.closed = true;
+        }
+
+        pub fn is_rented(&self) -> bool {
+            self.rental_ends_at
+                .map_or(false, |ends_at| Consensus::current_epoch() < ends_at)
+        }
+
+        pub fn rental_ends_at(&self) -> Option<u64> {
+            self.rental_ends_at
+        }
+
+        pub fn renter_badge_resource(&self) -> Option<ResourceAddress> {
+            self.renter_badge_resource
+        }
+
+        pub fn get_price_per_epoch(&self) -> Amount {
+            self.price_per_epoch
+        }
+    }
+}
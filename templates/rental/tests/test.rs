@@ -0,0 +1,245 @@
+//   Copyright 2024 The Tari Project
+//   SPDX-License-Identifier: BSD-3-Clause
+
+use tari_engine_types::virtual_substate::{VirtualSubstate, VirtualSubstateId};
+use tari_template_lib::args;
+use tari_template_lib::constants::XTR;
+use tari_template_lib::models::{Amount, ComponentAddress, NonFungibleAddress, ResourceAddress};
+use tari_template_lib::prelude::Metadata;
+use tari_template_test_tooling::crypto::RistrettoSecretKey;
+use tari_template_test_tooling::support::assert_error::assert_reject_reason;
+use tari_template_test_tooling::{SubstateType, TemplateTest};
+use tari_transaction::Transaction;
+
+const PRICE_PER_EPOCH: Amount = Amount(10);
+const MAX_EPOCHS: u64 = 5;
+
+#[test]
+fn renter_gets_a_badge_and_the_owner_gets_paid() {
+    let RentalTest {
+        mut test,
+        rental_component,
+        owner,
+        ..
+    } = setup();
+
+    let renter = create_account(&mut test);
+    let owner_balance = get_account_balance(&mut test, &owner, &XTR);
+
+    set_epoch(&mut test, 1);
+    test.execute_expect_success(
+        rent_transaction(rental_component, &renter, PRICE_PER_EPOCH * Amount(3), 3),
+        vec![renter.owner_token.clone()],
+    );
+
+    assert_eq!(
+        get_account_balance(&mut test, &owner, &XTR),
+        owner_balance + PRICE_PER_EPOCH * Amount(3)
+    );
+    let renter_badge_resource: Option<ResourceAddress> =
+        test.call_method(rental_component, "renter_badge_resource", args![], vec![]);
+    assert_eq!(
+        get_account_balance(&mut test, &renter, &renter_badge_resource.unwrap()),
+        Amount(1)
+    );
+    let is_rented: bool = test.call_method(rental_component, "is_rented", args![], vec![]);
+    assert!(is_rented);
+    let rental_ends_at: Option<u64> = test.call_method(rental_component, "rental_ends_at", args![], vec![]);
+    assert_eq!(rental_ends_at, Some(4));
+
+    // the NFT cannot be rented twice at the same time
+    let other_renter = create_account(&mut test);
+    let reason = test.execute_expect_failure(
+        rent_transaction(rental_component, &other_renter, PRICE_PER_EPOCH, 1),
+        vec![other_renter.owner_token.clone()],
+    );
+    assert_reject_reason(reason, "The NFT is already rented");
+}
+
+#[test]
+fn owner_reclaims_the_nft_after_the_rental() {
+    let RentalTest {
+        mut test,
+        rental_component,
+        owner,
+        nft,
+    } = setup();
+
+    let renter = create_account(&mut test);
+    test.execute_expect_success(
+        rent_transaction(rental_component, &renter, PRICE_PER_EPOCH * Amount(2), 2),
+        vec![renter.owner_token.clone()],
+    );
+
+    // the NFT cannot be reclaimed during the rental
+    set_epoch(&mut test, 1);
+    let reason = test.execute_expect_failure(
+        reclaim_transaction(rental_component, &owner),
+        vec![owner.owner_token.clone()],
+    );
+    assert_reject_reason(reason, "The rental period has not ended");
+
+    // once the rental period elapses, the NFT goes back to the owner
+    set_epoch(&mut test, 2);
+    let is_rented: bool = test.call_method(rental_component, "is_rented", args![], vec![]);
+    assert!(!is_rented);
+    test.execute_expect_success(
+        reclaim_transaction(rental_component, &owner),
+        vec![owner.owner_token.clone()],
+    );
+    assert_eq!(get_account_balance(&mut test, &owner, &nft.resource_address()), Amount(1));
+}
+
+#[test]
+fn it_rejects_invalid_rentals() {
+    let RentalTest {
+        mut test,
+        rental_component,
+        ..
+    } = setup();
+
+    let renter = create_account(&mut test);
+
+    let reason = test.execute_expect_failure(
+        rent_transaction(rental_component, &renter, PRICE_PER_EPOCH * Amount(6), MAX_EPOCHS + 1),
+        vec![renter.owner_token.clone()],
+    );
+    assert_reject_reason(reason, "The NFT can be rented for at most 5 epochs");
+
+    let reason = test.execute_expect_failure(
+        rent_transaction(rental_component, &renter, PRICE_PER_EPOCH, 2),
+        vec![renter.owner_token.clone()],
+    );
+    assert_reject_reason(reason, "Payment does not match the rental price");
+}
+
+#[derive(Clone, Debug)]
+struct Account {
+    pub component: ComponentAddress,
+    pub owner_token: NonFungibleAddress,
+    pub key: RistrettoSecretKey,
+}
+
+struct RentalTest {
+    test: TemplateTest,
+    rental_component: ComponentAddress,
+    owner: Account,
+    nft: NonFungibleAddress,
+}
+
+fn setup() -> RentalTest {
+    let mut test = TemplateTest::new(["."]);
+    let rental_template = test.get_template_address("Rental");
+
+    // create the NFT that the owner is going to rent
+    let owner = create_account(&mut test);
+    let nft = mint_account_nft(&mut test, &owner);
+
+    let result = test.execute_expect_success(
+        Transaction::builder()
+            .call_method(
+                owner.component,
+                "withdraw_non_fungible",
+                args![nft.resource_address(), nft.id()],
+            )
+            .put_last_instruction_output_on_workspace("nft_bucket")
+            .call_function(
+                rental_template,
+                "list",
+                args![Workspace("nft_bucket"), owner.component, PRICE_PER_EPOCH, MAX_EPOCHS],
+            )
+            .sign(&owner.key)
+            .build(),
+        vec![owner.owner_token.clone()],
+    );
+    let rental_component = result.finalize.execution_results[2]
+        .decode::<ComponentAddress>()
+        .unwrap();
+
+    RentalTest {
+        test,
+        rental_component,
+        owner,
+        nft,
+    }
+}
+
+fn create_account(test: &mut TemplateTest) -> Account {
+    let (component, owner_token, key) = test.create_funded_account();
+    Account {
+        component,
+        owner_token,
+        key,
+    }
+}
+
+fn get_account_balance(test: &mut TemplateTest, account: &Account, resource: &ResourceAddress) -> Amount {
+    let result = test.execute_expect_success(
+        Transaction::builder()
+            .call_method(account.component, "balance", args![resource])
+            .sign(&account.key)
+            .build(),
+        vec![account.owner_token.clone()],
+    );
+    result.finalize.execution_results[0]
+        .decode::<Amount>()
+        .unwrap()
+}
+
+fn mint_account_nft(test: &mut TemplateTest, account: &Account) -> NonFungibleAddress {
+    let account_nft_template = test.get_template_address("AccountNonFungible");
+    let result = test.execute_expect_success(
+        Transaction::builder()
+            .call_function(account_nft_template, "create", args![account.owner_token])
+            .sign(&account.key)
+            .build(),
+        vec![account.owner_token.clone()],
+    );
+    let account_nft_component = result.finalize.execution_results[0]
+        .decode::<ComponentAddress>()
+        .unwrap();
+
+    let mut nft_metadata = Metadata::new();
+    nft_metadata.insert("name".to_string(), "my_custom_nft".to_string());
+
+    test.execute_expect_success(
+        Transaction::builder()
+            .call_method(account_nft_component, "mint", args![nft_metadata])
+            .put_last_instruction_output_on_workspace("nft_bucket")
+            .call_method(account.component, "deposit", args![Workspace("nft_bucket")])
+            .sign(&account.key)
+            .build(),
+        vec![account.owner_token.clone()],
+    );
+    let output = test.get_previous_output_address(SubstateType::NonFungible);
+    output.as_non_fungible_address().unwrap().clone()
+}
+
+fn set_epoch(test: &mut TemplateTest, new_epoch: u64) {
+    test.set_virtual_substate(
+        VirtualSubstateId::CurrentEpoch,
+        VirtualSubstate::CurrentEpoch(new_epoch),
+    );
+}
+
+fn rent_transaction(rental_component: ComponentAddress, renter: &Account, payment: Amount, epochs: u64) -> Transaction {
+    Transaction::builder()
+        .call_method(renter.component, "withdraw", args![XTR, payment])
+        .put_last_instruction_output_on_workspace("payment")
+        .call_method(
+            rental_component,
+            "rent",
+            args![renter.component, Workspace("payment"), epochs],
+        )
+        .put_last_instruction_output_on_workspace("renter_badge")
+        .call_method(renter.component, "deposit", args![Workspace("renter_badge")])
+        .sign(&renter.key)
+        .build()
+}
+
+fn reclaim_transaction(rental_component: ComponentAddress, account: &Account) -> Transaction {
+    Transaction::builder()
+        .call_method(rental_component, "reclaim", args![])
+        .sign(&account.key)
+        .build()
+}
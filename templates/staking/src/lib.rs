@@ -0,0 +1,174 @@
+//   Copyright 2024. The Tari Project
+//
+//   Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//   following conditions are met:
+//
+//   1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//   disclaimer.
+//
+//   2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//   following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//   3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//   products derived from this software without specific prior written permission.
+//
+//   THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//   INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//   DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//   SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//   SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//   WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//   USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::collections::BTreeMap;
+use tari_template_lib::prelude::*;
+
+// Scale used in the reward per staked token accumulator, to avoid losing precision in the integer divisions
+pub const REWARD_SCALE: i64 = 1_000_000;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct StakeInfo {
+    amount: Amount,
+    // value of the reward accumulator when the stake was made, rewards are only accrued from that point
+    reward_per_token_paid: Amount,
+}
+
+#[template]
+mod staking {
+    use super::*;
+
+    /// Staking with per-epoch rewards
+    /// The stakers receive a receipt NFT, similar to how the tariswap pool mints LP tokens. The rewards of each epoch
+    /// are split between all the stakers proportionally to their stake.
+    pub struct Staking {
+        stake_vault: Vault,
+
+        // seeded at construction, the rewards are paid from it until it runs out
+        reward_vault: Vault,
+
+        // amount of reward tokens distributed in each epoch
+        reward_rate: Amount,
+
+        // receipts of each stake, burned on unstake
+        receipt_resource: ResourceAddress,
+        stakes: BTreeMap<NonFungibleId, StakeInfo>,
+        total_staked: Amount,
+
+        // accumulated rewards per staked token (scaled by `REWARD_SCALE`) and the epoch of its last update
+        reward_per_token: Amount,
+        last_update_epoch: u64,
+    }
+
+    impl Staking {
+        pub fn new(stake_resource: ResourceAddress, reward_bucket: Bucket, reward_rate: Amount) -> Component<Self> {
+            assert!(reward_rate.is_positive(), "Invalid reward rate");
+
+            // the address of the component is allocated beforehand, so the receipts can be restricted to it
+            let component_alloc = CallerContext::allocate_component_address(None);
+            let component_rule = AccessRule::Restricted(RestrictedAccessRule::Require(RequireRule::Require(
+                RuleRequirement::ScopedToComponent(*component_alloc.address()),
+            )));
+
+            // only this component can mint and burn the receipts, as each one is backed by a stake
+            let receipt_resource = ResourceBuilder::non_fungible()
+                .mintable(component_rule.clone())
+                .burnable(component_rule)
+                .build();
+
+            Component::new(Self {
+                stake_vault: Vault::new_empty(stake_resource),
+                reward_vault: Vault::from_bucket(reward_bucket),
+                reward_rate,
+                receipt_resource,
+                stakes: BTreeMap::new(),
+                total_staked: Amount::zero(),
+                reward_per_token: Amount::zero(),
+                last_update_epoch: Consensus::current_epoch(),
+            })
+            .with_access_rules(AccessRules::allow_all())
+            .with_address_allocation(component_alloc)
+            .create()
+        }
+
+        // returns a receipt NFT needed to unstake
+        pub fn stake(&mut self, bucket: Bucket) -> Bucket {
+            assert!(
+                bucket.resource_address() == self.stake_vault.resource_address(),
+                "Invalid stake resource"
+            );
+            assert!(bucket.amount().is_positive(), "Invalid stake amount");
+            self.update_rewards();
+
+            let stake_info = StakeInfo {
+                amount: bucket.amount(),
+                reward_per_token_paid: self.reward_per_token,
+            };
+            self.total_staked = self.total_staked + bucket.amount();
+            self.stake_vault.deposit(bucket);
+
+            let receipt_id = NonFungibleId::random();
+            self.stakes.insert(receipt_id.clone(), stake_info);
+            ResourceManager::get(self.receipt_resource).mint_non_fungible(receipt_id, &(), &())
+        }
+
+        // burns the receipt and returns the stake and the accrued rewards
+        // the rewards are limited by the remaining tokens in the reward vault
+        pub fn unstake(&mut self, receipt_bucket: Bucket) -> (Bucket, Bucket) {
+            assert!(
+                receipt_bucket.resource_address() == self.receipt_resource,
+                "Invalid receipt resource"
+            );
+            let receipt_ids = receipt_bucket.get_non_fungible_ids();
+            assert!(receipt_ids.len() == 1, "Can only unstake a single receipt");
+            self.update_rewards();
+
+            let stake_info = self
+                .stakes
+                .remove(&receipt_ids[0])
+                .expect("The stake does not exist");
+            let mut reward = self.calculate_reward(&stake_info);
+            let reward_balance = self.reward_vault.balance();
+            if reward > reward_balance {
+                reward = reward_balance;
+            }
+
+            self.total_staked = self.total_staked - stake_info.amount;
+            receipt_bucket.burn();
+
+            let stake_bucket = self.stake_vault.withdraw(stake_info.amount);
+            let reward_bucket = self.reward_vault.withdraw(reward);
+            (stake_bucket, reward_bucket)
+        }
+
+        pub fn receipt_resource(&self) -> ResourceAddress {
+            self.receipt_resource
+        }
+
+        pub fn get_total_staked(&self) -> Amount {
+            self.total_staked
+        }
+
+        pub fn get_reward_balance(&self) -> Amount {
+            self.reward_vault.balance()
+        }
+
+        // adds the rewards of all the epochs elapsed since the last update
+        fn update_rewards(&mut self) {
+            let current_epoch = Consensus::current_epoch();
+            let elapsed_epochs = current_epoch - self.last_update_epoch;
+
+            // there is nobody to distribute the rewards to while there are no stakes
+            if elapsed_epochs > 0 && self.total_staked.is_positive() {
+                let epoch_rewards = self.reward_rate * Amount::new(elapsed_epochs as i64);
+                self.reward_per_token =
+                    self.reward_per_token + epoch_rewards * Amount::new(REWARD_SCALE) / self.total_staked;
+            }
+
+            self.last_update_epoch = current_epoch;
+        }
+
+        fn calculate_reward(&self, stake_info: &StakeInfo) -> Amount {
+            stake_info.amount * (self.reward_per_token - stake_info.reward_per_token_paid) / Amount::new(REWARD_SCALE)
+        }
+    }
+}
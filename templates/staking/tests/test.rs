@@ -0,0 +1,180 @@
+//   Copyright 2024 The Tari Project
+//   SPDX-License-Identifier: BSD-3-Clause
+
+use tari_engine_types::virtual_substate::{VirtualSubstate, VirtualSubstateId};
+use tari_template_lib::args;
+use tari_template_lib::constants::XTR;
+use tari_template_lib::models::{Amount, ComponentAddress, NonFungibleAddress, ResourceAddress};
+use tari_template_test_tooling::crypto::RistrettoSecretKey;
+use tari_template_test_tooling::TemplateTest;
+use tari_transaction::Transaction;
+
+#[test]
+fn unstake_returns_stake_and_rewards() {
+    // 10 reward tokens per epoch
+    let StakingTest {
+        mut test,
+        staking_component,
+        ..
+    } = setup(Amount(1000), Amount(10));
+
+    let staker1 = create_account(&mut test);
+    let staker2 = create_account(&mut test);
+    let staker1_balance = get_account_tari_balance(&mut test, &staker1);
+    let staker2_balance = get_account_tari_balance(&mut test, &staker2);
+
+    // the first staker is alone during the first 4 epochs
+    set_epoch(&mut test, 1);
+    stake(&mut test, staking_component, &staker1, Amount(100));
+
+    // the second staker stakes 3 times more, so it gets 3/4 of the rewards from now on
+    set_epoch(&mut test, 5);
+    stake(&mut test, staking_component, &staker2, Amount(300));
+
+    // after 4 more epochs both stakers unstake
+    set_epoch(&mut test, 9);
+    unstake(&mut test, staking_component, &staker1);
+    unstake(&mut test, staking_component, &staker2);
+
+    // staker1: 4 epochs * 10 + 4 epochs * 10 * 1/4 = 50
+    // staker2: 4 epochs * 10 * 3/4 = 30
+    assert_eq!(get_account_tari_balance(&mut test, &staker1), staker1_balance + Amount(50));
+    assert_eq!(get_account_tari_balance(&mut test, &staker2), staker2_balance + Amount(30));
+
+    let total_staked: Amount = test.call_method(staking_component, "get_total_staked", args![], vec![]);
+    assert_eq!(total_staked, Amount::zero());
+    let reward_balance: Amount = test.call_method(staking_component, "get_reward_balance", args![], vec![]);
+    assert_eq!(reward_balance, Amount(1000 - 50 - 30));
+}
+
+#[test]
+fn rewards_are_limited_by_the_reward_vault() {
+    // the vault only has rewards for 2 epochs
+    let StakingTest {
+        mut test,
+        staking_component,
+        ..
+    } = setup(Amount(200), Amount(100));
+
+    let staker = create_account(&mut test);
+    let staker_balance = get_account_tari_balance(&mut test, &staker);
+
+    set_epoch(&mut test, 1);
+    stake(&mut test, staking_component, &staker, Amount(100));
+
+    // the accrued rewards (1000) exceed the reward vault
+    set_epoch(&mut test, 11);
+    unstake(&mut test, staking_component, &staker);
+
+    // the staker only gets what was left in the vault
+    assert_eq!(get_account_tari_balance(&mut test, &staker), staker_balance + Amount(200));
+    let reward_balance: Amount = test.call_method(staking_component, "get_reward_balance", args![], vec![]);
+    assert_eq!(reward_balance, Amount::zero());
+}
+
+#[derive(Clone, Debug)]
+struct Account {
+    pub component: ComponentAddress,
+    pub owner_token: NonFungibleAddress,
+    pub key: RistrettoSecretKey,
+}
+
+struct StakingTest {
+    test: TemplateTest,
+    staking_component: ComponentAddress,
+}
+
+fn setup(rewards: Amount, reward_rate: Amount) -> StakingTest {
+    let mut test = TemplateTest::new(["."]);
+    let staking_template = test.get_template_address("Staking");
+
+    // the rewards are seeded by the owner account
+    let owner = create_account(&mut test);
+    let result = test.execute_expect_success(
+        Transaction::builder()
+            .call_method(owner.component, "withdraw", args![XTR, rewards])
+            .put_last_instruction_output_on_workspace("rewards")
+            .call_function(
+                staking_template,
+                "new",
+                args![XTR, Workspace("rewards"), reward_rate],
+            )
+            .sign(&owner.key)
+            .build(),
+        vec![owner.owner_token.clone()],
+    );
+    let (substate_addr, _) = result
+        .expect_success()
+        .up_iter()
+        .find(|(address, substate)| {
+            address.is_component() && substate.substate_value().component().unwrap().module_name == "Staking"
+        })
+        .unwrap();
+    let staking_component = substate_addr.as_component_address().unwrap();
+
+    StakingTest {
+        test,
+        staking_component,
+    }
+}
+
+fn create_account(test: &mut TemplateTest) -> Account {
+    let (component, owner_token, key) = test.create_funded_account();
+    Account {
+        component,
+        owner_token,
+        key,
+    }
+}
+
+fn get_account_tari_balance(test: &mut TemplateTest, account: &Account) -> Amount {
+    let result = test.execute_expect_success(
+        Transaction::builder()
+            .call_method(account.component, "balance", args![XTR])
+            .sign(&account.key)
+            .build(),
+        vec![account.owner_token.clone()],
+    );
+    result.finalize.execution_results[0]
+        .decode::<Amount>()
+        .unwrap()
+}
+
+fn set_epoch(test: &mut TemplateTest, new_epoch: u64) {
+    test.set_virtual_substate(
+        VirtualSubstateId::CurrentEpoch,
+        VirtualSubstate::CurrentEpoch(new_epoch),
+    );
+}
+
+fn stake(test: &mut TemplateTest, staking_component: ComponentAddress, staker: &Account, amount: Amount) {
+    test.execute_expect_success(
+        Transaction::builder()
+            .call_method(staker.component, "withdraw", args![XTR, amount])
+            .put_last_instruction_output_on_workspace("stake")
+            .call_method(staking_component, "stake", args![Workspace("stake")])
+            .put_last_instruction_output_on_workspace("receipt")
+            .call_method(staker.component, "deposit", args![Workspace("receipt")])
+            .sign(&staker.key)
+            .build(),
+        vec![staker.owner_token.clone()],
+    );
+}
+
+// unstakes the (single) receipt held by the staker
+fn unstake(test: &mut TemplateTest, staking_component: ComponentAddress, staker: &Account) {
+    let receipt_resource: ResourceAddress = test.call_method(staking_component, "receipt_resource", args![], vec![]);
+
+    test.execute_expect_success(
+        Transaction::builder()
+            .call_method(staker.component, "withdraw", args![receipt_resource, Amount(1)])
+            .put_last_instruction_output_on_workspace("receipt")
+            .call_method(staking_component, "unstake", args![Workspace("receipt")])
+            .put_last_instruction_output_on_workspace("unstaked")
+            .call_method(staker.component, "deposit", args![Workspace("unstaked.0")])
+            .call_method(staker.component, "deposit", args![Workspace("unstaked.1")])
+            .sign(&staker.key)
+            .build(),
+        vec![staker.owner_token.clone()],
+    );
+}
@@ -0,0 +1,140 @@
+//   Copyright 2024. The Tari Project
+//
+//   Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//   following conditions are met:
+//
+//   1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//   disclaimer.
+//
+//   2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//   following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//   3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//   products derived from this software without specific prior written permission.
+//
+//   THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//   INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//   DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//   SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//   SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//   WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//   USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use account_utils::assert_component_is_account;
+use tari_template_lib::prelude::*;
+
+#[template]
+mod subscription {
+    use super::*;
+
+    /// Prepaid recurring payments
+    /// The subscriber locks funds upfront, and the provider collects the price of each elapsed epoch until the funds
+    /// are exhausted.
+    pub struct Subscription {
+        // address of the account component of the subscriber
+        subscriber: ComponentAddress,
+
+        // address of the account component that receives the payments
+        provider: ComponentAddress,
+
+        // badge that allows the provider to collect the payments
+        provider_badge_resource: ResourceAddress,
+
+        // holds the prepaid funds that have not been collected yet
+        vault: Vault,
+
+        price_per_epoch: Amount,
+
+        // epoch up to which the payments have been collected
+        last_collected_epoch: u64,
+    }
+
+    impl Subscription {
+        // the provider badge is deposited directly in the provider account
+        pub fn subscribe(
+            account: ComponentAddress,
+            provider: ComponentAddress,
+            prepaid_bucket: Bucket,
+            price_per_epoch: Amount,
+        ) -> Component<Self> {
+            assert_eq!(
+                prepaid_bucket.resource_address(),
+                XTR,
+                "Invalid payment resource, the subscription only accepts Tari (XTR) tokens"
+            );
+            assert!(price_per_epoch.is_positive(), "Invalid price per epoch");
+            assert!(
+                prepaid_bucket.amount() >= price_per_epoch,
+                "The prepaid funds must cover at least one epoch"
+            );
+
+            // needed to ensure that we can deposit the badge and the payments
+            assert_component_is_account(account);
+            assert_component_is_account(provider);
+
+            // we make sure that only the initial badge will be minted
+            let provider_badge_bucket = ResourceBuilder::non_fungible()
+                .mintable(AccessRule::DenyAll)
+                .initial_supply_with_data(Some((NonFungibleId::random(), (&(), &()))));
+            let provider_badge_resource = provider_badge_bucket.resource_address();
+            ComponentManager::get(provider).call::<_, ()>("deposit".to_string(), args![provider_badge_bucket]);
+
+            Component::new(Self {
+                subscriber: account,
+                provider,
+                provider_badge_resource,
+                vault: Vault::from_bucket(prepaid_bucket),
+                price_per_epoch,
+                last_collected_epoch: Consensus::current_epoch(),
+            })
+            .with_access_rules(AccessRules::allow_all())
+            .create()
+        }
+
+        // sends the price of all the epochs elapsed since the last collection to the provider
+        // the collected amount is limited by the remaining prepaid funds
+        // returns the badge back to the provider
+        pub fn collect(&mut self, provider_badge: Bucket) -> Bucket {
+            assert!(
+                provider_badge.resource_address() == self.provider_badge_resource,
+                "Invalid provider badge"
+            );
+
+            let amount = self.get_amount_due();
+            self.last_collected_epoch = Consensus::current_epoch();
+
+            if amount.is_positive() {
+                let payment = self.vault.withdraw(amount);
+                ComponentManager::get(self.provider).call::<_, ()>("deposit".to_string(), args![payment]);
+            }
+
+            provider_badge
+        }
+
+        // amount that the provider can collect right now
+        pub fn get_amount_due(&self) -> Amount {
+            let elapsed_epochs = Consensus::current_epoch() - self.last_collected_epoch;
+            let due = self.price_per_epoch * Amount::new(elapsed_epochs as i64);
+            let balance = self.vault.balance();
+            if due > balance {
+                balance
+            } else {
+                due
+            }
+        }
+
+        // amount of epochs, starting from the current one, that are still covered by the prepaid funds
+        pub fn epochs_remaining(&self) -> u64 {
+            let remaining = (self.vault.balance() - self.get_amount_due()) / self.price_per_epoch;
+            remaining.value() as u64
+        }
+
+        pub fn get_subscriber(&self) -> ComponentAddress {
+            self.subscriber
+        }
+
+        pub fn provider_badge_resource(&self) -> ResourceAddress {
+            self.provider_badge_resource
+        }
+    }
+}
@@ -0,0 +1,196 @@
+//   Copyright 2024 The Tari Project
+//   SPDX-License-Identifier: BSD-3-Clause
+
+use tari_engine_types::virtual_substate::{VirtualSubstate, VirtualSubstateId};
+use tari_template_lib::args;
+use tari_template_lib::constants::XTR;
+use tari_template_lib::models::{Amount, ComponentAddress, NonFungibleAddress, ResourceAddress};
+use tari_template_test_tooling::crypto::RistrettoSecretKey;
+use tari_template_test_tooling::support::assert_error::assert_reject_reason;
+use tari_template_test_tooling::TemplateTest;
+use tari_transaction::Transaction;
+
+const PREPAID: Amount = Amount(1000);
+const PRICE_PER_EPOCH: Amount = Amount(100);
+
+#[test]
+fn provider_collects_the_elapsed_epochs() {
+    let SubscriptionTest {
+        mut test,
+        subscription_component,
+        provider,
+        ..
+    } = setup();
+
+    let provider_balance = get_account_tari_balance(&mut test, &provider);
+
+    // the prepaid funds cover 10 epochs
+    let epochs_remaining: u64 = test.call_method(subscription_component, "epochs_remaining", args![], vec![]);
+    assert_eq!(epochs_remaining, 10);
+
+    // collect 3 epochs
+    set_epoch(&mut test, 3);
+    collect(&mut test, subscription_component, &provider);
+    assert_eq!(
+        get_account_tari_balance(&mut test, &provider),
+        provider_balance + PRICE_PER_EPOCH * Amount(3)
+    );
+    let epochs_remaining: u64 = test.call_method(subscription_component, "epochs_remaining", args![], vec![]);
+    assert_eq!(epochs_remaining, 7);
+
+    // collect 2 more epochs, the payments are cumulative
+    set_epoch(&mut test, 5);
+    collect(&mut test, subscription_component, &provider);
+    assert_eq!(
+        get_account_tari_balance(&mut test, &provider),
+        provider_balance + PRICE_PER_EPOCH * Amount(5)
+    );
+    let epochs_remaining: u64 = test.call_method(subscription_component, "epochs_remaining", args![], vec![]);
+    assert_eq!(epochs_remaining, 5);
+}
+
+#[test]
+fn provider_cannot_collect_more_than_prepaid() {
+    let SubscriptionTest {
+        mut test,
+        subscription_component,
+        provider,
+        ..
+    } = setup();
+
+    let provider_balance = get_account_tari_balance(&mut test, &provider);
+
+    // 20 epochs elapsed, but only 10 were prepaid
+    set_epoch(&mut test, 20);
+    collect(&mut test, subscription_component, &provider);
+    assert_eq!(get_account_tari_balance(&mut test, &provider), provider_balance + PREPAID);
+
+    // further collections do not transfer anything
+    set_epoch(&mut test, 25);
+    collect(&mut test, subscription_component, &provider);
+    assert_eq!(get_account_tari_balance(&mut test, &provider), provider_balance + PREPAID);
+    let epochs_remaining: u64 = test.call_method(subscription_component, "epochs_remaining", args![], vec![]);
+    assert_eq!(epochs_remaining, 0);
+}
+
+#[test]
+fn only_the_provider_can_collect() {
+    let SubscriptionTest {
+        mut test,
+        subscription_component,
+        subscriber,
+        ..
+    } = setup();
+
+    // the subscriber does not hold the provider badge, so it tries with an unrelated resource
+    set_epoch(&mut test, 3);
+    let reason = test.execute_expect_failure(
+        collect_transaction(subscription_component, &subscriber, XTR),
+        vec![subscriber.owner_token.clone()],
+    );
+    assert_reject_reason(reason, "Invalid provider badge");
+}
+
+#[derive(Clone, Debug)]
+struct Account {
+    pub component: ComponentAddress,
+    pub owner_token: NonFungibleAddress,
+    pub key: RistrettoSecretKey,
+}
+
+struct SubscriptionTest {
+    test: TemplateTest,
+    subscription_component: ComponentAddress,
+    subscriber: Account,
+    provider: Account,
+}
+
+fn setup() -> SubscriptionTest {
+    let mut test = TemplateTest::new(["."]);
+    let subscription_template = test.get_template_address("Subscription");
+
+    let subscriber = create_account(&mut test);
+    let provider = create_account(&mut test);
+
+    let result = test.execute_expect_success(
+        Transaction::builder()
+            .call_method(subscriber.component, "withdraw", args![XTR, PREPAID])
+            .put_last_instruction_output_on_workspace("prepaid")
+            .call_function(
+                subscription_template,
+                "subscribe",
+                args![subscriber.component, provider.component, Workspace("prepaid"), PRICE_PER_EPOCH],
+            )
+            .sign(&subscriber.key)
+            .build(),
+        vec![subscriber.owner_token.clone()],
+    );
+    let (substate_addr, _) = result
+        .expect_success()
+        .up_iter()
+        .find(|(address, substate)| {
+            address.is_component() && substate.substate_value().component().unwrap().module_name == "Subscription"
+        })
+        .unwrap();
+    let subscription_component = substate_addr.as_component_address().unwrap();
+
+    SubscriptionTest {
+        test,
+        subscription_component,
+        subscriber,
+        provider,
+    }
+}
+
+fn create_account(test: &mut TemplateTest) -> Account {
+    let (component, owner_token, key) = test.create_funded_account();
+    Account {
+        component,
+        owner_token,
+        key,
+    }
+}
+
+fn get_account_tari_balance(test: &mut TemplateTest, account: &Account) -> Amount {
+    let result = test.execute_expect_success(
+        Transaction::builder()
+            .call_method(account.component, "balance", args![XTR])
+            .sign(&account.key)
+            .build(),
+        vec![account.owner_token.clone()],
+    );
+    result.finalize.execution_results[0]
+        .decode::<Amount>()
+        .unwrap()
+}
+
+fn set_epoch(test: &mut TemplateTest, new_epoch: u64) {
+    test.set_virtual_substate(
+        VirtualSubstateId::CurrentEpoch,
+        VirtualSubstate::CurrentEpoch(new_epoch),
+    );
+}
+
+fn collect_transaction(
+    subscription_component: ComponentAddress,
+    account: &Account,
+    badge_resource: ResourceAddress,
+) -> Transaction {
+    Transaction::builder()
+        .call_method(account.component, "withdraw", args![badge_resource, Amount(1)])
+        .put_last_instruction_output_on_workspace("badge")
+        .call_method(subscription_component, "collect", args![Workspace("badge")])
+        .put_last_instruction_output_on_workspace("badge")
+        .call_method(account.component, "deposit", args![Workspace("badge")])
+        .sign(&account.key)
+        .build()
+}
+
+fn collect(test: &mut TemplateTest, subscription_component: ComponentAddress, provider: &Account) {
+    let badge_resource: ResourceAddress =
+        test.call_method(subscription_component, "provider_badge_resource", args![], vec![]);
+    test.execute_expect_success(
+        collect_transaction(subscription_component, provider, badge_resource),
+        vec![provider.owner_token.clone()],
+    );
+}
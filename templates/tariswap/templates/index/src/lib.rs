@@ -32,19 +32,40 @@ mod tariswap_index {
     pub struct TariswapIndex {
         pool_template: TemplateAddress,
         pools: BTreeMap<PoolKey, ComponentAddress>,
-        // All pools in the index will have the same swap market fee, which is represented as a per thousand amount
+        // Default swap market fee of the pools in the index, which is represented as a per thousand amount
         market_fee: u16,
+        // Part of the market fee that goes to the protocol treasury of each pool, also as a per thousand amount
+        protocol_fee: u16,
+        // the badge needed to remove pools from the index
+        admin_badge_resource: ResourceAddress,
     }
 
     impl TariswapIndex {
-        pub fn new(pool_template: TemplateAddress, market_fee: u16) -> Component<Self> {
-            Component::new(Self {
+        // returns the index component and the admin badge
+        pub fn new(pool_template: TemplateAddress, market_fee: u16, protocol_fee: u16) -> (Component<Self>, Bucket) {
+            // the fee is per-mil, a fee of 1000 or more would leave nothing for the swap output
+            assert!(market_fee < 1000, "Invalid fee {}", market_fee);
+
+            // the protocol fee is carved out of the market fee of each pool, so it can never exceed it
+            assert!(protocol_fee <= market_fee, "Invalid protocol fee {}", protocol_fee);
+
+            // we make sure that only the initial badge will be minted
+            let admin_badge_bucket = ResourceBuilder::non_fungible()
+                .mintable(AccessRule::DenyAll)
+                .initial_supply_with_data(Some((NonFungibleId::random(), (&(), &()))));
+            let admin_badge_resource = admin_badge_bucket.resource_address();
+
+            let component = Component::new(Self {
                 pool_template,
                 pools: BTreeMap::new(),
-                market_fee
+                market_fee,
+                protocol_fee,
+                admin_badge_resource,
             })
             .with_access_rules(AccessRules::allow_all())
-            .create()
+            .create();
+
+            (component, admin_badge_bucket)
         }
 
         // convenience method for external APIs and interfaces
@@ -52,31 +73,165 @@ mod tariswap_index {
             self.pools.clone()
         }
 
+        // the order of the resources does not matter, as the pool key is always sorted
+        pub fn find_pool(&self, a_addr: ResourceAddress, b_addr: ResourceAddress) -> Option<ComponentAddress> {
+            let pool_key = Self::build_pool_key(a_addr, b_addr);
+            self.pools.get(&pool_key).copied()
+        }
+
+        // canonical order of a resource pair, as used by the pools
+        pub fn get_pool_key(&self, a_addr: ResourceAddress, b_addr: ResourceAddress) -> PoolKey {
+            Self::build_pool_key(a_addr, b_addr)
+        }
+
+        pub fn pool_exists(&self, a_addr: ResourceAddress, b_addr: ResourceAddress) -> bool {
+            self.find_pool(a_addr, b_addr).is_some()
+        }
+
+        // swaps along the resources of "path" (e.g. [A, B, C] swaps A for B and then B for C), using the indexed pools
+        // the whole route is quoted upfront, so it fails before any swap if the final output would be below the minimum
+        // each hop is then bounded by its quote, a route that goes through the same pool twice gets rejected
+        pub fn swap_route_with_min(
+            &self,
+            input_bucket: Bucket,
+            path: Vec<ResourceAddress>,
+            min_final_output: Amount,
+        ) -> Bucket {
+            assert!(path.len() >= 2, "A swap route needs at least two resources");
+            assert!(
+                input_bucket.resource_address() == path[0],
+                "The input resource does not match the start of the route"
+            );
+
+            // quote all the hops of the route
+            let mut hops = vec![];
+            let mut amount = input_bucket.amount();
+            for pair in path.windows(2) {
+                let (input_resource, output_resource) = (pair[0], pair[1]);
+                let pool_component = self
+                    .find_pool(input_resource, output_resource)
+                    .unwrap_or_else(|| panic!("There is no pool for {} and {}", input_resource, output_resource));
+                amount = ComponentManager::get(pool_component)
+                    .call("get_amount_out".to_string(), args![input_resource, amount]);
+                hops.push((pool_component, output_resource, amount));
+            }
+            assert!(
+                amount >= min_final_output,
+                "The route output {} is below the minimum of {}",
+                amount,
+                min_final_output
+            );
+
+            // execute the swaps
+            let mut bucket = input_bucket;
+            for (pool_component, output_resource, min_output) in hops {
+                bucket = ComponentManager::get(pool_component).call(
+                    "swap_with_min_output".to_string(),
+                    args![bucket, output_resource, min_output],
+                );
+            }
+
+            bucket
+        }
+
+        // total value locked of a resource across all the indexed pools
+        // note that it does a cross-component call for each pool that contains the resource, so the cost is O(n)
+        pub fn get_total_reserves(&self, resource: ResourceAddress) -> Amount {
+            self.pools
+                .iter()
+                .filter(|(pool_key, _)| pool_key.0 == resource || pool_key.1 == resource)
+                .map(|(_, pool_component)| {
+                    let balance: Amount = ComponentManager::get(*pool_component)
+                        .call("get_pool_balance".to_string(), args![resource]);
+                    balance
+                })
+                .fold(Amount::zero(), |total, balance| total + balance)
+        }
+
+        // returns the new pool component, its LP resource and its treasury badge
+        // the resource symbols are optional and only used to name the LP token of the pool
+        // the default market fee can be overriden for pairs that need lower or higher fees
         pub fn create_pool(
             &mut self,
             a_addr: ResourceAddress,
             b_addr: ResourceAddress,
-        ) -> ComponentAddress {
+            a_symbol: Option<String>,
+            b_symbol: Option<String>,
+            fee_override: Option<u16>,
+            expected_price: Option<Amount>,
+        ) -> (ComponentAddress, ResourceAddress, Bucket) {
+            // a pool of a token against itself would break the swap math
+            assert!(a_addr != b_addr, "Cannot create a pool for identical resources");
+
             let pool_key = Self::build_pool_key(a_addr, b_addr);
 
+            // the symbols must follow the same order as the (sorted) pool key
+            let (a_symbol, b_symbol) = if pool_key.0 == a_addr {
+                (a_symbol, b_symbol)
+            } else {
+                (b_symbol, a_symbol)
+            };
+
             // check that the pool does not alredy exists
             assert!(
                 !self.pools.contains_key(&pool_key),
                 "A pool already exists for the input resources"
             );
 
+            // the fee is a per-mil amount, so it must be lower than 1000
+            let market_fee = fee_override.unwrap_or(self.market_fee);
+            assert!(market_fee < 1000, "Invalid fee override {}", market_fee);
+
+            // low fee pools would be rejected by the pool if the protocol fee was higher than the market fee
+            let protocol_fee = self.protocol_fee.min(market_fee);
+
+            // the expected price is the amount of "b" tokens paid for one "a" token, scaled by the pool spot price scale
+            // the base resource is passed along, so it does not matter if the pool key reorders the pair
+            let initial_price = expected_price.map(|price| (a_addr, price));
+
             // init the pool component
-            let pool_component: ComponentAddress = TemplateManager::get(self.pool_template)
-                .call("new".to_string(), args![
+            let (pool_component, lp_resource, treasury_badge): (ComponentAddress, ResourceAddress, Bucket) =
+                TemplateManager::get(self.pool_template).call("new".to_string(), args![
                     pool_key.0,
                     pool_key.1,
-                    self.market_fee
+                    market_fee,
+                    protocol_fee,
+                    a_symbol,
+                    b_symbol,
+                    initial_price
                 ]);
 
             // add the new pool component to the index
             self.pools.insert(pool_key, pool_component);
-            
-            pool_component
+
+            (pool_component, lp_resource, treasury_badge)
+        }
+
+        // takes an obsolete pool off the index, so a new pool can be created for the same pair
+        // pools with liquidity providers cannot be removed, to avoid stranding their liquidity
+        // returns the admin badge back to the caller
+        pub fn remove_pool(&mut self, admin_badge: Bucket, a_addr: ResourceAddress, b_addr: ResourceAddress) -> Bucket {
+            assert!(
+                admin_badge.resource_address() == self.admin_badge_resource,
+                "Invalid admin badge"
+            );
+
+            let pool_key = Self::build_pool_key(a_addr, b_addr);
+            let pool_component = *self
+                .pools
+                .get(&pool_key)
+                .expect("There is no pool for the input resources");
+
+            let is_empty: bool = ComponentManager::get(pool_component).call("is_empty".to_string(), args![]);
+            assert!(is_empty, "The pool still has liquidity");
+
+            self.pools.remove(&pool_key);
+
+            admin_badge
+        }
+
+        pub fn admin_badge_resource(&self) -> ResourceAddress {
+            self.admin_badge_resource
         }
 
         // create a consistent resource pair by sorting them
@@ -23,6 +23,19 @@
 use std::collections::HashMap;
 use tari_template_lib::prelude::*;
 
+// Spot prices are returned multiplied by this factor, so small price ratios don't get truncated to zero
+// e.g. a spot price of "1_500_000" represents a ratio of 1.5
+pub const SPOT_PRICE_SCALE: i64 = 1_000_000;
+
+// LP tokens locked forever on the first deposit, so the first liquidity provider cannot manipulate the LP share price
+pub const MINIMUM_LIQUIDITY: i64 = 10;
+
+// Maximum deviation (per-mil) of the first deposit from the expected initial price of the pool
+pub const INITIAL_PRICE_TOLERANCE: i64 = 50;
+
+// Fee charged on flash loans, represented as a per-mil quantity of the borrowed amount (rounded up)
+pub const FLASH_LOAN_FEE: i64 = 5;
+
 #[template]
 mod tariswap_pool {
     use super::*;
@@ -31,141 +44,314 @@ mod tariswap_pool {
     pub struct TariswapPool {
         pools: HashMap<ResourceAddress, Vault>,
         lp_resource: ResourceAddress,
+        // holds the MINIMUM_LIQUIDITY tokens, there is no way to withdraw them
+        locked_lp: Vault,
         fee: u16,
+        // part of the market fee (per-mil of the swap input) that goes to the protocol instead of the LP holders
+        protocol_fee: u16,
+        protocol_vaults: HashMap<ResourceAddress, Vault>,
+        // the treasury badge is also the admin badge of the pool, needed to pause it in emergencies
+        treasury_badge_resource: ResourceAddress,
+        // swaps and new liquidity are rejected while paused, but liquidity providers can always exit
+        paused: bool,
+        // the flash loan in progress (if any), as the borrowed resource and amount
+        flash_loan: Option<(ResourceAddress, Amount)>,
+        // receipts cannot be deposited anywhere, so the only way to get rid of them is repaying the flash loan
+        flash_loan_receipt_resource: ResourceAddress,
+        // sum of the spot prices of the "a" resource (scaled by `SPOT_PRICE_SCALE`) weighted by the epochs they lasted
+        // the "a" resource is always the lowest address of the pool, see `get_resource_order`
+        // consumers can sample it at two different epochs to derive a time-weighted average price (TWAP)
+        price_cumulative_a: Amount,
+        // unset until the first pool operation
+        last_update_epoch: Option<u64>,
+        // optional reference price for the first deposit, as the base resource and the amount of the other resource
+        // paid for one base token (scaled by `SPOT_PRICE_SCALE`)
+        initial_price: Option<(ResourceAddress, Amount)>,
     }
 
     impl TariswapPool {
         // Initialises a new pool component for for the pool A - B
         // the fees is represented as a per-mil quantity (e.g. "1" represents "0.1%")
-        pub fn new(a_addr: ResourceAddress, b_addr: ResourceAddress, fee: u16) -> Component<Self> {
+        // returns the pool component, its LP resource and the treasury badge, needed to withdraw the protocol fees
+        // the (optional) symbols of the pair are only used for the LP token metadata
+        // the (optional) initial price protects the pool against first deposits that set a skewed price
+        pub fn new(
+            a_addr: ResourceAddress,
+            b_addr: ResourceAddress,
+            fee: u16,
+            protocol_fee: u16,
+            a_symbol: Option<String>,
+            b_symbol: Option<String>,
+            initial_price: Option<(ResourceAddress, Amount)>,
+        ) -> (Component<Self>, ResourceAddress, Bucket) {
             // check that the the resource pair is correct
             assert!(a_addr != b_addr, "The resources of the pair must be different");
             Self::check_resource_is_fungible(a_addr);
             Self::check_resource_is_fungible(b_addr);
 
-            // the fee represents a percentage, so it must be between 0 and 100
-            let valid_fee_range = 0..100;
-            assert!(valid_fee_range.contains(&fee), "Invalid fee {}", fee);
+            // the fee is per-mil, a fee of 1000 or more would leave nothing for the swap output
+            assert!(fee < 1000, "Invalid fee {}", fee);
+
+            // the protocol fee is carved out of the market fee, so it can never exceed it
+            assert!(protocol_fee <= fee, "Invalid protocol fee {}", protocol_fee);
+
+            if let Some((base, price)) = initial_price {
+                assert!(base == a_addr || base == b_addr, "The resource {} is not in the pool", base);
+                assert!(price.is_positive(), "Invalid initial price {}", price);
+            }
 
             // create the vaults to store the funds
             let mut pools = HashMap::new();
             pools.insert(a_addr, Vault::new_empty(a_addr));
             pools.insert(b_addr, Vault::new_empty(b_addr));
 
-            // create the lp resource
-            // TODO: add lp resource minting/burning security, only this component should be allowed
-            let lp_resource = ResourceBuilder::fungible().with_token_symbol("LP").build();
+            // create the vaults to accumulate the protocol fees
+            let mut protocol_vaults = HashMap::new();
+            protocol_vaults.insert(a_addr, Vault::new_empty(a_addr));
+            protocol_vaults.insert(b_addr, Vault::new_empty(b_addr));
+
+            // create the badge that allows to withdraw the protocol fees
+            // we make sure that only the initial badge will be minted
+            let treasury_badge_bucket = ResourceBuilder::non_fungible()
+                .mintable(AccessRule::DenyAll)
+                .initial_supply_with_data(Some((NonFungibleId::random(), (&(), &()))));
+            let treasury_badge_resource = treasury_badge_bucket.resource_address();
 
-            Component::new(Self {
+            // create the lp resource, named after the pair (e.g. "LP-A-B") when the symbols are known
+            // TODO: add lp resource minting/burning security, only this component should be allowed
+            let (lp_symbol, lp_name) = match (a_symbol, b_symbol) {
+                (Some(a_symbol), Some(b_symbol)) => (
+                    format!("LP-{}-{}", a_symbol, b_symbol),
+                    format!("Tariswap {}/{} liquidity", a_symbol, b_symbol),
+                ),
+                _ => ("LP".to_string(), "Tariswap liquidity".to_string()),
+            };
+            let lp_resource = ResourceBuilder::fungible()
+                .with_token_symbol(lp_symbol)
+                .add_metadata("name", lp_name)
+                .build();
+
+            // the address of the component is allocated beforehand, so resources can be restricted to it
+            let component_alloc = CallerContext::allocate_component_address(None);
+            let component_rule = AccessRule::Restricted(RestrictedAccessRule::Require(RequireRule::Require(
+                RuleRequirement::ScopedToComponent(*component_alloc.address()),
+            )));
+
+            // create the flash loan receipt resource
+            // only this component can mint and burn the receipts, otherwise a borrower could burn the receipt instead
+            // of repaying the loan
+            let flash_loan_receipt_resource = ResourceBuilder::fungible()
+                .mintable(component_rule.clone())
+                .burnable(component_rule)
+                .depositable(AccessRule::DenyAll)
+                .build();
+
+            let component = Component::new(Self {
                 pools,
                 lp_resource,
+                locked_lp: Vault::new_empty(lp_resource),
                 fee,
+                protocol_fee,
+                protocol_vaults,
+                treasury_badge_resource,
+                paused: false,
+                flash_loan: None,
+                flash_loan_receipt_resource,
+                price_cumulative_a: Amount::zero(),
+                last_update_epoch: None,
+                initial_price,
             })
             // TODO: proper access rules
             .with_access_rules(AccessRules::allow_all())
-            .create()
+            .with_address_allocation(component_alloc)
+            .create();
+
+            (component, lp_resource, treasury_badge_bucket)
         }
 
         // swap A tokens for B tokens or viceversa
         pub fn swap(&mut self, input_bucket: Bucket, output_resource: ResourceAddress) -> Bucket {
             // check that the parameters are correct
             let input_resource = input_bucket.resource_address();
-            self.check_pool_resources(input_resource, output_resource);
+            self.check_swap_resources(input_resource, output_resource);
 
-            // get the data needed to calculate the pool rebalancing
-            let input_pool_balance = self.get_pool_balance(input_resource);
-            let output_pool_balance = self.get_pool_balance(output_resource);
+            // calculate the amount of output tokens to return to the user
+            let output_bucket_amount = self.calculate_amount_out(input_resource, input_bucket.amount());
 
-            // check that the pools are not empty, to prevent division by 0 errors later
-            assert!(
-                !input_pool_balance.is_zero(),
-                "The pool for resource '{}' is empty",
-                input_resource
-            );
+            self.execute_swap(input_bucket, output_resource, output_bucket_amount)
+        }
+
+        // same as "swap", but protects the trader against front-running by rejecting outputs below "min_output"
+        pub fn swap_with_min_output(
+            &mut self,
+            input_bucket: Bucket,
+            output_resource: ResourceAddress,
+            min_output: Amount,
+        ) -> Bucket {
+            // check that the parameters are correct
+            let input_resource = input_bucket.resource_address();
+            self.check_swap_resources(input_resource, output_resource);
+
+            // calculate the amount of output tokens and check it against the slippage bound
+            let output_bucket_amount = self.calculate_amount_out(input_resource, input_bucket.amount());
+            assert!(output_bucket_amount >= min_output, "Slippage exceeded");
+
+            self.execute_swap(input_bucket, output_resource, output_bucket_amount)
+        }
+
+        // swap for exactly "exact_output" tokens, taking only the needed input tokens
+        // returns the output bucket and the unused input tokens
+        pub fn swap_exact_output(
+            &mut self,
+            mut input_bucket: Bucket,
+            output_resource: ResourceAddress,
+            exact_output: Amount,
+        ) -> (Bucket, Bucket) {
+            // check that the parameters are correct
+            let input_resource = input_bucket.resource_address();
+            self.check_swap_resources(input_resource, output_resource);
+            assert!(exact_output.is_positive(), "Invalid output amount");
+
+            // calculate the input tokens needed for the requested output
+            let required_input = self.calculate_amount_in(input_resource, exact_output);
             assert!(
-                !output_pool_balance.is_zero(),
-                "The pool for resource '{}' is empty",
-                output_resource
+                input_bucket.amount() >= required_input,
+                "Insufficient input, {} tokens are required",
+                required_input
             );
 
-            // apply the fee to the input bucket
-            // so the user will get a lesser amout of tokens than the theoritical (for the gain of the LP holders)
-            let input_bucket_balance = input_bucket.amount().value();
-            let effective_input_balance = input_bucket_balance - (input_bucket_balance * (self.fee as i64)) / 1000;
-            let effective_input_balance = Amount::new(effective_input_balance);
+            let used_input_bucket = input_bucket.take(required_input);
+            let output_bucket = self.execute_swap(used_input_bucket, output_resource, exact_output);
 
-            // recalculate the new vault balances for the swap
-            // constant product AMM formula is "k = a * b"
-            // so the new output vault balance should be "b = k / a"
-            let k = input_pool_balance * output_pool_balance;
-            let new_input_pool_balance = input_pool_balance + effective_input_balance;
-            let new_output_pool_balance = k / new_input_pool_balance;
-
-            // calculate the amount of output tokens to return to the user
-            let output_bucket_amount = output_pool_balance - new_output_pool_balance;
+            (output_bucket, input_bucket)
+        }
 
-            // perform the swap
-            self.pools.get_mut(&input_resource).unwrap().deposit(input_bucket);
-            self.pools
-                .get_mut(&output_resource)
-                .unwrap()
-                .withdraw(output_bucket_amount)
+        // read-only quote of the output of a swap, so users can preview it before signing
+        pub fn get_amount_out(&self, input_resource: ResourceAddress, input_amount: Amount) -> Amount {
+            assert!(input_amount.is_positive(), "Invalid input amount");
+            self.calculate_amount_out(input_resource, input_amount)
         }
 
-        pub fn add_liquidity(&mut self, a_bucket: Bucket, b_bucket: Bucket) -> Bucket {
+        // only the amounts proportional to the pool reserves are taken, the unused parts are returned as change buckets
+        // along with the new lp tokens
+        pub fn add_liquidity(&mut self, mut a_bucket: Bucket, mut b_bucket: Bucket) -> (Bucket, Bucket, Bucket) {
             // check that the buckets are correct
             let a_resource = a_bucket.resource_address();
             let b_resource = b_bucket.resource_address();
             self.check_pool_resources(a_resource, b_resource);
+            self.check_not_paused();
+            self.check_no_flash_loan();
+            self.update_price_accumulator();
 
             // extract the bucket amounts for later
             let a_amount = a_bucket.amount();
             let b_amount = b_bucket.amount();
 
+            let lp_total_supply = self.lp_total_supply();
+            let (new_lp_amount, a_change, b_change) = if lp_total_supply.is_zero() {
+                // the first deposit mints "a + b" lp tokens
+                // but a minimum amount is locked forever to prevent the first depositor from inflating the LP price
+                let lp_amount = a_amount + b_amount;
+                assert!(
+                    lp_amount > Amount::new(MINIMUM_LIQUIDITY),
+                    "The initial liquidity must be greater than {}",
+                    MINIMUM_LIQUIDITY
+                );
+                self.check_initial_price(a_resource, a_amount, b_amount);
+
+                let locked_lp_bucket = ResourceManager::get(self.lp_resource).mint_fungible(Amount::new(MINIMUM_LIQUIDITY));
+                self.locked_lp.deposit(locked_lp_bucket);
+
+                // the first deposit sets the ratio of the pool, so there is never change
+                (lp_amount - Amount::new(MINIMUM_LIQUIDITY), Amount::zero(), Amount::zero())
+            } else {
+                // the amount of new lp tokens are proportional to the share of the pool that is being provided
+                let a_reserve = self.get_pool_balance(a_resource);
+                let b_reserve = self.get_pool_balance(b_resource);
+                let a_lp_amount = a_amount * lp_total_supply / a_reserve;
+                let b_lp_amount = b_amount * lp_total_supply / b_reserve;
+                if a_lp_amount < b_lp_amount {
+                    let b_used = Self::proportional_amount_rounded_up(a_amount, b_reserve, a_reserve).min(b_amount);
+                    (a_lp_amount, Amount::zero(), b_amount - b_used)
+                } else {
+                    let a_used = Self::proportional_amount_rounded_up(b_amount, a_reserve, b_reserve).min(a_amount);
+                    (b_lp_amount, a_amount - a_used, Amount::zero())
+                }
+            };
+            assert!(new_lp_amount.is_positive(), "Insufficient liquidity provided");
+
+            // the change buckets may be empty if the provided amounts match the pool ratio
+            let a_change_bucket = a_bucket.take(a_change);
+            let b_change_bucket = b_bucket.take(b_change);
+
             // add the liquidity to the pool
             self.pools.get_mut(&a_resource).unwrap().deposit(a_bucket);
             self.pools.get_mut(&b_resource).unwrap().deposit(b_bucket);
 
-            // get the bucket/pool ratios
-            let a_ratio = self.get_pool_ratio(a_resource, a_amount);
-            let b_ratio = self.get_pool_ratio(b_resource, b_amount);
-
-            // the amount of new lp tokens are proportional to the bucket-pool ratios
-            let new_lp_amount = a_ratio * a_amount + b_ratio * b_amount;
-
             // mint and return the new lp tokens
-            ResourceManager::get(self.lp_resource).mint_fungible(new_lp_amount)
+            let lp_bucket = ResourceManager::get(self.lp_resource).mint_fungible(new_lp_amount);
+
+            let mut event_payload = Metadata::new();
+            event_payload.insert("a_resource".to_string(), a_resource.to_string());
+            event_payload.insert("a_amount".to_string(), (a_amount - a_change).to_string());
+            event_payload.insert("b_resource".to_string(), b_resource.to_string());
+            event_payload.insert("b_amount".to_string(), (b_amount - b_change).to_string());
+            event_payload.insert("lp_amount".to_string(), new_lp_amount.to_string());
+            self.emit_event_with_reserves("add_liquidity", event_payload);
+            (lp_bucket, a_change_bucket, b_change_bucket)
         }
 
         pub fn remove_liquidity(&mut self, lp_bucket: Bucket) -> (Bucket, Bucket) {
             assert!(lp_bucket.resource_address() == self.lp_resource, "Invalid LP resource");
 
-            // get the pool information
-            let a_resource = self.get_a_resource();
-            let a_balance = self.get_pool_balance(a_resource).value() as f64;
-            let b_resource = self.get_b_resource();
-            let b_balance = self.get_pool_balance(b_resource).value() as f64;
+            let (a_amount, b_amount) = self.calculate_removal_amounts(lp_bucket.amount());
+            self.execute_removal(lp_bucket, a_amount, b_amount)
+        }
 
-            // calculate the amount of tokens to take from each pool
-            let lp_ratio = lp_bucket.amount().value() as f64 / self.lp_total_supply().value() as f64;
-            let a_amount = Amount::new((lp_ratio * a_balance).ceil() as i64);
-            let b_amount = Amount::new((lp_ratio * b_balance).ceil() as i64);
+        // same as "remove_liquidity", but rejects the removal if the pool ratio moved and the outputs are too low
+        pub fn remove_liquidity_with_min(&mut self, lp_bucket: Bucket, min_a: Amount, min_b: Amount) -> (Bucket, Bucket) {
+            assert!(lp_bucket.resource_address() == self.lp_resource, "Invalid LP resource");
 
-            // burn the LP tokens
-            lp_bucket.burn();
+            let (a_amount, b_amount) = self.calculate_removal_amounts(lp_bucket.amount());
+            assert!(a_amount >= min_a && b_amount >= min_b, "Insufficient output");
 
-            // return the pool tokens
-            let a_bucket = self.pools.get_mut(&a_resource).unwrap().withdraw(a_amount);
-            let b_bucket = self.pools.get_mut(&b_resource).unwrap().withdraw(b_amount);
-            (a_bucket, b_bucket)
+            self.execute_removal(lp_bucket, a_amount, b_amount)
+        }
+
+        // only burns "percent" (in basis points) of the provided LP tokens, the rest are returned along with the reserves
+        pub fn remove_liquidity_percent(&mut self, mut lp_bucket: Bucket, percent: u16) -> (Bucket, Bucket, Bucket) {
+            assert!(lp_bucket.resource_address() == self.lp_resource, "Invalid LP resource");
+            assert!(percent <= 10000, "Invalid percent {}", percent);
+
+            let lp_amount_to_remove = lp_bucket.amount() * Amount::new(percent as i64) / Amount::new(10000);
+            let lp_bucket_to_remove = lp_bucket.take(lp_amount_to_remove);
+
+            let (a_amount, b_amount) = self.calculate_removal_amounts(lp_amount_to_remove);
+            let (a_bucket, b_bucket) = self.execute_removal(lp_bucket_to_remove, a_amount, b_amount);
+
+            (a_bucket, b_bucket, lp_bucket)
+        }
+
+        // adds reserves to the pool without minting LP tokens, increasing the value of all outstanding LP tokens
+        pub fn donate(&mut self, bucket: Bucket) {
+            self.check_no_flash_loan();
+            assert!(bucket.amount().is_positive(), "Invalid donation amount");
+
+            let resource = bucket.resource_address();
+            let vault = self
+                .pools
+                .get_mut(&resource)
+                .unwrap_or_else(|| panic!("The resource {} is not in the pool", resource));
+            vault.deposit(bucket);
         }
 
+        // the iteration order of the pools map is not stable, so the resources are sorted by address
         pub fn get_a_resource(&self) -> ResourceAddress {
-            *self.pools.keys().nth(0).unwrap()
+            self.get_resource_order().0
         }
 
         pub fn get_b_resource(&self) -> ResourceAddress {
-            *self.pools.keys().nth(1).unwrap()
+            self.get_resource_order().1
         }
 
         pub fn get_pool_balances(&self) -> HashMap<ResourceAddress, Amount> {
@@ -186,6 +372,25 @@ mod tariswap_pool {
             vault.balance()
         }
 
+        // the resources sorted by address, matching the pool key of the index
+        pub fn get_resource_order(&self) -> (ResourceAddress, ResourceAddress) {
+            let mut resources: Vec<ResourceAddress> = self.pools.keys().copied().collect();
+            resources.sort();
+            (resources[0], resources[1])
+        }
+
+        // both reserves in a single call, the resources are sorted by address
+        pub fn get_reserves(&self) -> (ResourceAddress, Amount, ResourceAddress, Amount) {
+            let (a_resource, b_resource) = self.get_resource_order();
+
+            (
+                a_resource,
+                self.get_pool_balance(a_resource),
+                b_resource,
+                self.get_pool_balance(b_resource),
+            )
+        }
+
         pub fn get_pool_ratio(&self, resource: ResourceAddress, amount: Amount) -> Amount {
             let balance = self.get_pool_balance(resource);
 
@@ -196,6 +401,113 @@ mod tariswap_pool {
             }
         }
 
+        // returns how many tokens of the opposite resource are paid for one `base` token, scaled by `SPOT_PRICE_SCALE`
+        pub fn get_spot_price(&self, base: ResourceAddress) -> Amount {
+            let quote = self.get_other_resource(base);
+            let base_balance = self.get_pool_balance(base);
+            let quote_balance = self.get_pool_balance(quote);
+            assert!(!base_balance.is_zero(), "The pool for resource '{}' is empty", base);
+
+            quote_balance * Amount::new(SPOT_PRICE_SCALE) / base_balance
+        }
+
+        // borrows pool reserves, the loan must be repaid (plus the FLASH_LOAN_FEE) in the same transaction
+        // returns the borrowed tokens and a receipt that can only be consumed by "repay_flash_loan"
+        // as unconsumed buckets make the transaction fail, an unpaid loan will never be committed
+        pub fn flash_loan(&mut self, resource: ResourceAddress, amount: Amount) -> (Bucket, Bucket) {
+            self.check_not_paused();
+            self.check_no_flash_loan();
+            assert!(amount.is_positive(), "Invalid flash loan amount");
+            assert!(
+                amount < self.get_pool_balance(resource),
+                "Not enough liquidity for resource '{}'",
+                resource
+            );
+
+            self.flash_loan = Some((resource, amount));
+
+            let loan_bucket = self.pools.get_mut(&resource).unwrap().withdraw(amount);
+            let receipt_bucket = ResourceManager::get(self.flash_loan_receipt_resource).mint_fungible(Amount::new(1));
+            (loan_bucket, receipt_bucket)
+        }
+
+        pub fn repay_flash_loan(&mut self, repayment: Bucket, receipt: Bucket) {
+            let (resource, amount) = self.flash_loan.take().expect("There is no flash loan in progress");
+            assert!(
+                receipt.resource_address() == self.flash_loan_receipt_resource,
+                "Invalid flash loan receipt"
+            );
+            assert!(
+                repayment.resource_address() == resource,
+                "Invalid repayment resource, expected {}",
+                resource
+            );
+
+            // the pool reserves must be equal or greater after the repayment
+            let fee = Amount::new((amount.value() * FLASH_LOAN_FEE + 999) / 1000);
+            assert!(repayment.amount() >= amount + fee, "Insufficient flash loan repayment");
+
+            receipt.burn();
+            self.pools.get_mut(&resource).unwrap().deposit(repayment);
+        }
+
+        // returns the price accumulator of the "a" resource (the lowest address of the pool) and the epoch of its last
+        // update
+        pub fn get_price_cumulative(&self) -> (Amount, u64) {
+            (self.price_cumulative_a, self.last_update_epoch.unwrap_or_default())
+        }
+
+        pub fn get_protocol_fees(&self, resource: ResourceAddress) -> Amount {
+            let vault = self
+                .protocol_vaults
+                .get(&resource)
+                .unwrap_or_else(|| panic!("Resource {} is not in the pool", resource));
+            vault.balance()
+        }
+
+        // the treasury badge is returned to the caller, so it can be used again in the future
+        pub fn withdraw_protocol_fees(&mut self, treasury_badge: Bucket, resource: ResourceAddress) -> (Bucket, Bucket) {
+            assert!(
+                treasury_badge.resource_address() == self.treasury_badge_resource,
+                "Invalid treasury badge"
+            );
+
+            let fees = self
+                .protocol_vaults
+                .get_mut(&resource)
+                .unwrap_or_else(|| panic!("Resource {} is not in the pool", resource))
+                .withdraw_all();
+
+            (fees, treasury_badge)
+        }
+
+        // emergency stop, returns the badge back to the caller
+        pub fn pause(&mut self, treasury_badge: Bucket) -> Bucket {
+            assert!(
+                treasury_badge.resource_address() == self.treasury_badge_resource,
+                "Invalid treasury badge"
+            );
+            self.paused = true;
+            treasury_badge
+        }
+
+        pub fn unpause(&mut self, treasury_badge: Bucket) -> Bucket {
+            assert!(
+                treasury_badge.resource_address() == self.treasury_badge_resource,
+                "Invalid treasury badge"
+            );
+            self.paused = false;
+            treasury_badge
+        }
+
+        pub fn is_paused(&self) -> bool {
+            self.paused
+        }
+
+        pub fn treasury_badge_resource(&self) -> ResourceAddress {
+            self.treasury_badge_resource
+        }
+
         pub fn lp_resource(&self) -> ResourceAddress {
             self.lp_resource
         }
@@ -204,10 +516,29 @@ mod tariswap_pool {
             ResourceManager::get(self.lp_resource).total_supply()
         }
 
+        // a pool is considered empty when the only LP tokens left are the locked minimum liquidity
+        pub fn is_empty(&self) -> bool {
+            self.lp_total_supply() == self.locked_lp.balance()
+        }
+
         pub fn fee(&self) -> u16 {
             self.fee
         }
 
+        // part of the fee (also per-mil) that goes to the protocol treasury
+        pub fn protocol_fee(&self) -> u16 {
+            self.protocol_fee
+        }
+
+        // the output must be the opposite resource of the input in this pool
+        fn check_swap_resources(&self, input_resource: ResourceAddress, output_resource: ResourceAddress) {
+            assert!(input_resource != output_resource, "Cannot swap a resource for itself");
+            assert!(
+                self.pools.contains_key(&input_resource) && self.pools.contains_key(&output_resource),
+                "Resource not in pool"
+            );
+        }
+
         fn check_pool_resources(&self, a_resource: ResourceAddress, b_resource: ResourceAddress) {
             assert!(a_resource != b_resource, "The resource addresses are the same");
             assert!(
@@ -222,6 +553,217 @@ mod tariswap_pool {
             );
         }
 
+        // returns the amount of tokens to take from each pool for the given amount of LP tokens
+        fn calculate_removal_amounts(&self, lp_amount: Amount) -> (Amount, Amount) {
+            // get the pool information
+            let a_balance = self.get_pool_balance(self.get_a_resource()).value() as f64;
+            let b_balance = self.get_pool_balance(self.get_b_resource()).value() as f64;
+
+            // calculate the amount of tokens to take from each pool
+            // we round down, so tiny LP positions cannot drain the pool by rounding errors
+            let lp_ratio = lp_amount.value() as f64 / self.lp_total_supply().value() as f64;
+            let a_amount = Amount::new((lp_ratio * a_balance).floor() as i64);
+            let b_amount = Amount::new((lp_ratio * b_balance).floor() as i64);
+
+            (a_amount, b_amount)
+        }
+
+        fn execute_removal(&mut self, lp_bucket: Bucket, a_amount: Amount, b_amount: Amount) -> (Bucket, Bucket) {
+            self.check_no_flash_loan();
+            self.update_price_accumulator();
+
+            // burn the LP tokens
+            let lp_amount = lp_bucket.amount();
+            lp_bucket.burn();
+
+            // return the pool tokens
+            let a_resource = self.get_a_resource();
+            let b_resource = self.get_b_resource();
+            let a_bucket = self.pools.get_mut(&a_resource).unwrap().withdraw(a_amount);
+            let b_bucket = self.pools.get_mut(&b_resource).unwrap().withdraw(b_amount);
+
+            let mut event_payload = Metadata::new();
+            event_payload.insert("a_resource".to_string(), a_resource.to_string());
+            event_payload.insert("a_amount".to_string(), a_amount.to_string());
+            event_payload.insert("b_resource".to_string(), b_resource.to_string());
+            event_payload.insert("b_amount".to_string(), b_amount.to_string());
+            event_payload.insert("lp_amount".to_string(), lp_amount.to_string());
+            self.emit_event_with_reserves("remove_liquidity", event_payload);
+
+            (a_bucket, b_bucket)
+        }
+
+        fn execute_swap(&mut self, mut input_bucket: Bucket, output_resource: ResourceAddress, output_amount: Amount) -> Bucket {
+            self.check_not_paused();
+            self.check_no_flash_loan();
+            // the integer math of the swap formulas can leave dust inputs without any output
+            assert!(output_amount.is_positive(), "Output rounds to zero, increase input");
+            self.update_price_accumulator();
+            let input_resource = input_bucket.resource_address();
+
+            // the protocol share of the market fee is kept apart, the rest stays in the pool for the LP holders
+            let input_amount = input_bucket.amount().value();
+            let protocol_fee_amount = Amount::new((input_amount * (self.protocol_fee as i64)) / 1000);
+            if protocol_fee_amount.is_positive() {
+                let protocol_fee_bucket = input_bucket.take(protocol_fee_amount);
+                self.protocol_vaults.get_mut(&input_resource).unwrap().deposit(protocol_fee_bucket);
+            }
+
+            self.pools.get_mut(&input_resource).unwrap().deposit(input_bucket);
+            let output_bucket = self.pools
+                .get_mut(&output_resource)
+                .unwrap()
+                .withdraw(output_amount);
+
+            // notify off-chain indexers, e.g. to track the trading volume
+            let mut event_payload = Metadata::new();
+            event_payload.insert("input_resource".to_string(), input_resource.to_string());
+            event_payload.insert("input_amount".to_string(), Amount::new(input_amount).to_string());
+            event_payload.insert("output_resource".to_string(), output_resource.to_string());
+            event_payload.insert("output_amount".to_string(), output_amount.to_string());
+            self.emit_event_with_reserves("swap", event_payload);
+
+            output_bucket
+        }
+
+        fn calculate_amount_out(&self, input_resource: ResourceAddress, input_amount: Amount) -> Amount {
+            let output_resource = self.get_other_resource(input_resource);
+
+            // check that the pools are not empty, to prevent division by 0 errors later
+            self.check_has_liquidity();
+
+            // get the data needed to calculate the pool rebalancing
+            let input_pool_balance = self.get_pool_balance(input_resource);
+            let output_pool_balance = self.get_pool_balance(output_resource);
+
+            // apply the fee to the input amount
+            // so the user will get a lesser amout of tokens than the theoritical (for the gain of the LP holders)
+            let input_amount = input_amount.value();
+            let effective_input_amount = input_amount - (input_amount * (self.fee as i64)) / 1000;
+            let effective_input_amount = Amount::new(effective_input_amount);
+
+            // recalculate the new vault balances for the swap
+            // constant product AMM formula is "k = a * b"
+            // so the new output vault balance should be "b = k / a"
+            let k = input_pool_balance * output_pool_balance;
+            let new_input_pool_balance = input_pool_balance + effective_input_amount;
+            let new_output_pool_balance = k / new_input_pool_balance;
+
+            output_pool_balance - new_output_pool_balance
+        }
+
+        // inverse of "calculate_amount_out", returns the minimum input needed to get "output_amount" tokens
+        fn calculate_amount_in(&self, input_resource: ResourceAddress, output_amount: Amount) -> Amount {
+            let output_resource = self.get_other_resource(input_resource);
+            self.check_has_liquidity();
+
+            let input_pool_balance = self.get_pool_balance(input_resource).value();
+            let output_pool_balance = self.get_pool_balance(output_resource).value();
+            assert!(
+                output_amount.value() < output_pool_balance,
+                "Not enough liquidity for resource '{}'",
+                output_resource
+            );
+
+            // constant product AMM formula is "k = a * b", so the new input vault balance should be "a = k / b"
+            // we round up so the pool never gives away more than the formula allows
+            let k = input_pool_balance * output_pool_balance;
+            let new_output_pool_balance = output_pool_balance - output_amount.value();
+            let new_input_pool_balance = (k + new_output_pool_balance - 1) / new_output_pool_balance;
+            let effective_input_amount = new_input_pool_balance - input_pool_balance;
+
+            // undo the market fee, the fee is rounded down in swaps so we look for the smallest valid input
+            let fee = self.fee as i64;
+            let mut input_amount = effective_input_amount * 1000 / (1000 - fee);
+            while input_amount - (input_amount * fee) / 1000 < effective_input_amount {
+                input_amount += 1;
+            }
+
+            Amount::new(input_amount)
+        }
+
+        // adds the current spot price for all the epochs elapsed since the last update
+        // must be called before the pool reserves change, so the price is the one that lasted during those epochs
+        fn update_price_accumulator(&mut self) {
+            let current_epoch = Consensus::current_epoch();
+
+            if let Some(last_update_epoch) = self.last_update_epoch {
+                let elapsed_epochs = current_epoch - last_update_epoch;
+                let (a_resource, _) = self.get_resource_order();
+                // there is no price to accumulate while the pool is empty
+                if elapsed_epochs > 0 && !self.get_pool_balance(a_resource).is_zero() {
+                    let spot_price = self.get_spot_price(a_resource);
+                    self.price_cumulative_a = self.price_cumulative_a + spot_price * Amount::new(elapsed_epochs as i64);
+                }
+            }
+
+            self.last_update_epoch = Some(current_epoch);
+        }
+
+        // "amount * numerator / denominator", rounded up so that rounding always favours the pool
+        fn proportional_amount_rounded_up(amount: Amount, numerator: Amount, denominator: Amount) -> Amount {
+            (amount * numerator + denominator - Amount::new(1)) / denominator
+        }
+
+        // the first deposit sets the price of the pool, so it must be close to the expected initial price (if any)
+        fn check_initial_price(&self, a_resource: ResourceAddress, a_amount: Amount, b_amount: Amount) {
+            if let Some((base, expected_price)) = self.initial_price {
+                let (base_amount, quote_amount) = if base == a_resource {
+                    (a_amount, b_amount)
+                } else {
+                    (b_amount, a_amount)
+                };
+                assert!(base_amount.is_positive(), "The initial deposit deviates from the expected price");
+                let price = quote_amount * Amount::new(SPOT_PRICE_SCALE) / base_amount;
+
+                let deviation = if price > expected_price {
+                    price - expected_price
+                } else {
+                    expected_price - price
+                };
+                assert!(
+                    deviation * Amount::new(1000) <= expected_price * Amount::new(INITIAL_PRICE_TOLERANCE),
+                    "The initial deposit deviates from the expected price"
+                );
+            }
+        }
+
+        // all the pool events include the resulting reserves, so indexers can track the liquidity of the pool
+        fn emit_event_with_reserves(&self, topic: &str, mut payload: Metadata) {
+            let (a_resource, a_reserve, b_resource, b_reserve) = self.get_reserves();
+            payload.insert("reserve_a_resource".to_string(), a_resource.to_string());
+            payload.insert("reserve_a".to_string(), a_reserve.to_string());
+            payload.insert("reserve_b_resource".to_string(), b_resource.to_string());
+            payload.insert("reserve_b".to_string(), b_reserve.to_string());
+            emit_event(topic, payload);
+        }
+
+        fn check_not_paused(&self) {
+            assert!(!self.paused, "The pool is paused");
+        }
+
+        // swaps against an empty reserve would divide by zero in the constant product formula
+        fn check_has_liquidity(&self) {
+            assert!(
+                self.pools.values().all(|vault| !vault.balance().is_zero()),
+                "Pool has no liquidity"
+            );
+        }
+
+        // the pool reserves must not be used while a flash loan is in progress
+        fn check_no_flash_loan(&self) {
+            assert!(self.flash_loan.is_none(), "A flash loan is in progress");
+        }
+
+        fn get_other_resource(&self, resource: ResourceAddress) -> ResourceAddress {
+            assert!(
+                self.pools.contains_key(&resource),
+                "The resource {} is not in the pool",
+                resource
+            );
+            *self.pools.keys().find(|r| **r != resource).unwrap()
+        }
+
         fn check_resource_is_fungible(resource: ResourceAddress) {
             let resource_type = ResourceManager::get(resource).resource_type();
             assert!(
@@ -4,7 +4,7 @@
 use tari_engine_types::instruction::Instruction;
 use tari_template_lib::{
     args,
-    models::{Amount, ComponentAddress},
+    models::{Amount, Bucket, ComponentAddress},
     prelude::{NonFungibleAddress, ResourceAddress},
 };
 use tari_template_test_tooling::{SubstateType, TemplateTest};
@@ -13,6 +13,11 @@ use tari_transaction::Transaction;
 use tari_template_test_tooling::crypto::RistrettoSecretKey;
 use tari_engine_types::commit_result::ExecuteResult;
 use tari_dan_engine::runtime::AssertError;
+use tari_engine_types::virtual_substate::{VirtualSubstate, VirtualSubstateId};
+
+// must match the scale used by the pool template
+const SPOT_PRICE_SCALE: i64 = 1_000_000;
+const MINIMUM_LIQUIDITY: i64 = 10;
 
 struct TariswapTest {
     template_test: TemplateTest,
@@ -26,18 +31,30 @@ struct TariswapTest {
 }
 
 fn setup(fee: u16) -> TariswapTest {
+    setup_with_protocol_fee(fee, 0)
+}
+
+fn setup_with_protocol_fee(fee: u16, protocol_fee: u16) -> TariswapTest {
     let mut template_test = TemplateTest::new(["./templates/index", "./templates/pool"]);
 
+    // the account will also hold the admin badge of the index and the treasury badge of the pool
+    let (account_address, account_proof, _) = template_test.create_funded_account();
+
     // create the pool index
-    let index_component = create_index_component(&mut template_test, fee);
+    let index_component = create_index_component(&mut template_test, fee, protocol_fee, account_address);
 
     // create the token pair for the swap pool
     let (a_faucet, a_resource) = create_faucet_component(&mut template_test, "A".to_string());
     let (b_faucet, b_resource) = create_faucet_component(&mut template_test, "B".to_string());
 
-    let (pool_component, lp_resource) = create_pool_component(&mut template_test, a_resource, b_resource, index_component);
+    let (pool_component, lp_resource) = create_pool_component(
+        &mut template_test,
+        (a_resource, "A".to_string()),
+        (b_resource, "B".to_string()),
+        index_component,
+        account_address,
+    );
 
-    let (account_address, account_proof, _) = template_test.create_funded_account();
     fund_account(&mut template_test, account_address, a_faucet);
     fund_account(&mut template_test, account_address, b_faucet);
 
@@ -66,30 +83,33 @@ fn create_faucet_component(template_test: &mut TemplateTest, symbol: String) ->
     (component_address, resource_address)
 }
 
-fn create_index_component(template_test: &mut TemplateTest, fee: u16) -> ComponentAddress {
-    let pool_template = template_test.get_template_address("TariswapPool");
-
-    let component_address: ComponentAddress =
-        template_test.call_function("TariswapIndex", "new", args![pool_template, fee], vec![]);
-
-    component_address
-}
-
-fn create_pool_component(
+fn create_index_component(
     template_test: &mut TemplateTest,
-    a_resource: ResourceAddress,
-    b_resource: ResourceAddress,
-    index_component: ComponentAddress
-) -> (ComponentAddress, ResourceAddress) {
-    let pool_module_name = "TariswapPool";
+    fee: u16,
+    protocol_fee: u16,
+    admin_account: ComponentAddress,
+) -> ComponentAddress {
+    let index_module_name = "TariswapIndex";
+    let index_template = template_test.get_template_address(index_module_name);
+    let pool_template = template_test.get_template_address("TariswapPool");
 
     let res = template_test
         .execute_and_commit(
-            vec![Instruction::CallMethod {
-                component_address: index_component,
-                method: "create_pool".to_string(),
-                args: args![a_resource, b_resource],
-            }],
+            vec![
+                Instruction::CallFunction {
+                    template_address: index_template,
+                    function: "new".to_string(),
+                    args: args![pool_template, fee, protocol_fee],
+                },
+                Instruction::PutLastInstructionOutputOnWorkspace {
+                    key: b"index".to_vec(),
+                },
+                Instruction::CallMethod {
+                    component_address: admin_account,
+                    method: "deposit".to_string(),
+                    args: args![Variable("index.1")],
+                },
+            ],
             vec![],
         )
         .unwrap();
@@ -99,18 +119,50 @@ fn create_pool_component(
         .expect_success()
         .up_iter()
         .find(|(address, substate)| {
-            address.is_component() && substate.substate_value().component().unwrap().module_name == pool_module_name
+            address.is_component() && substate.substate_value().component().unwrap().module_name == index_module_name
         })
         .unwrap();
-    let component_address = substate_addr.as_component_address().unwrap();
+    substate_addr.as_component_address().unwrap()
+}
 
-    // extract the LP token resource address
-    let (substate_addr, _) = res
-        .expect_success()
-        .up_iter()
-        .find(|(address, _)| address.is_resource())
+fn create_pool_component(
+    template_test: &mut TemplateTest,
+    (a_resource, a_symbol): (ResourceAddress, String),
+    (b_resource, b_symbol): (ResourceAddress, String),
+    index_component: ComponentAddress,
+    treasury_account: ComponentAddress,
+) -> (ComponentAddress, ResourceAddress) {
+    let res = template_test
+        .execute_and_commit(
+            vec![
+                Instruction::CallMethod {
+                    component_address: index_component,
+                    method: "create_pool".to_string(),
+                    args: args![
+                        a_resource,
+                        b_resource,
+                        Some(a_symbol),
+                        Some(b_symbol),
+                        None::<u16>,
+                        None::<Amount>
+                    ],
+                },
+                Instruction::PutLastInstructionOutputOnWorkspace {
+                    key: b"pool".to_vec(),
+                },
+                Instruction::CallMethod {
+                    component_address: treasury_account,
+                    method: "deposit".to_string(),
+                    args: args![Variable("pool.2")],
+                },
+            ],
+            vec![],
+        )
+        .unwrap();
+
+    let (component_address, lp_resource, _) = res.finalize.execution_results[0]
+        .decode::<(ComponentAddress, ResourceAddress, Bucket)>()
         .unwrap();
-    let lp_resource = substate_addr.as_resource_address().unwrap();
 
     (component_address, lp_resource)
 }
@@ -178,7 +230,126 @@ fn swap(test: &mut TariswapTest, input_resource: &ResourceAddress, output_resour
         )
 }
 
+fn swap_route_with_min(
+    test: &mut TariswapTest,
+    path: Vec<ResourceAddress>,
+    amount: Amount,
+    min_final_output: Amount,
+) -> anyhow::Result<ExecuteResult> {
+    test.template_test
+        .execute_and_commit(
+            vec![
+                Instruction::CallMethod {
+                    component_address: test.account_address,
+                    method: "withdraw".to_string(),
+                    args: args![path[0], amount],
+                },
+                Instruction::PutLastInstructionOutputOnWorkspace {
+                    key: b"input_bucket".to_vec(),
+                },
+                Instruction::CallMethod {
+                    component_address: test.index_component,
+                    method: "swap_route_with_min".to_string(),
+                    args: args![Variable("input_bucket"), path, min_final_output],
+                },
+                Instruction::PutLastInstructionOutputOnWorkspace {
+                    key: b"output_bucket".to_vec(),
+                },
+                Instruction::CallMethod {
+                    component_address: test.account_address,
+                    method: "deposit".to_string(),
+                    args: args![Variable("output_bucket")],
+                },
+            ],
+            // proof needed to withdraw
+            vec![test.account_proof.clone()],
+        )
+}
+
+fn swap_with_min_output(
+    test: &mut TariswapTest,
+    input_resource: &ResourceAddress,
+    output_resource: &ResourceAddress,
+    amount: Amount,
+    min_output: Amount,
+) -> anyhow::Result<ExecuteResult> {
+    test.template_test
+        .execute_and_commit(
+            vec![
+                Instruction::CallMethod {
+                    component_address: test.account_address,
+                    method: "withdraw".to_string(),
+                    args: args![input_resource, amount],
+                },
+                Instruction::PutLastInstructionOutputOnWorkspace {
+                    key: b"input_bucket".to_vec(),
+                },
+                Instruction::CallMethod {
+                    component_address: test.pool_component,
+                    method: "swap_with_min_output".to_string(),
+                    args: args![Variable("input_bucket"), output_resource, min_output],
+                },
+                Instruction::PutLastInstructionOutputOnWorkspace {
+                    key: b"output_bucket".to_vec(),
+                },
+                Instruction::CallMethod {
+                    component_address: test.account_address,
+                    method: "deposit".to_string(),
+                    args: args![Variable("output_bucket"),],
+                },
+            ],
+            // proof needed to withdraw
+            vec![test.account_proof.clone()],
+        )
+}
+
+fn swap_exact_output(
+    test: &mut TariswapTest,
+    input_resource: &ResourceAddress,
+    output_resource: &ResourceAddress,
+    max_input_amount: Amount,
+    exact_output: Amount,
+) -> anyhow::Result<ExecuteResult> {
+    test.template_test
+        .execute_and_commit(
+            vec![
+                Instruction::CallMethod {
+                    component_address: test.account_address,
+                    method: "withdraw".to_string(),
+                    args: args![input_resource, max_input_amount],
+                },
+                Instruction::PutLastInstructionOutputOnWorkspace {
+                    key: b"input_bucket".to_vec(),
+                },
+                Instruction::CallMethod {
+                    component_address: test.pool_component,
+                    method: "swap_exact_output".to_string(),
+                    args: args![Variable("input_bucket"), output_resource, exact_output],
+                },
+                Instruction::PutLastInstructionOutputOnWorkspace {
+                    key: b"swap_buckets".to_vec(),
+                },
+                Instruction::CallMethod {
+                    component_address: test.account_address,
+                    method: "deposit".to_string(),
+                    args: args![Variable("swap_buckets.0"),],
+                },
+                Instruction::CallMethod {
+                    component_address: test.account_address,
+                    method: "deposit".to_string(),
+                    args: args![Variable("swap_buckets.1"),],
+                },
+            ],
+            // proof needed to withdraw
+            vec![test.account_proof.clone()],
+        )
+}
+
 fn add_liquidity(test: &mut TariswapTest, a_amount: Amount, b_amount: Amount) {
+    try_add_liquidity(test, a_amount, b_amount).unwrap();
+}
+
+fn try_add_liquidity(test: &mut TariswapTest, a_amount: Amount, b_amount: Amount) -> anyhow::Result<ExecuteResult> {
     test.template_test
         .execute_and_commit(
             vec![
@@ -204,19 +375,29 @@ fn add_liquidity(test: &mut TariswapTest, a_amount: Amount, b_amount: Amount) {
                     args: args![Variable("a_bucket"), Variable("b_bucket")],
                 },
                 Instruction::PutLastInstructionOutputOnWorkspace {
-                    key: b"lp_bucket".to_vec(),
+                    key: b"add_liquidity_result".to_vec(),
                 },
+                // deposit the lp tokens and the unused change
                 Instruction::CallMethod {
                     component_address: test.account_address,
                     method: "deposit".to_string(),
-                    args: args![Variable("lp_bucket")],
+                    args: args![Variable("add_liquidity_result.0")],
+                },
+                Instruction::CallMethod {
+                    component_address: test.account_address,
+                    method: "deposit".to_string(),
+                    args: args![Variable("add_liquidity_result.1")],
+                },
+                Instruction::CallMethod {
+                    component_address: test.account_address,
+                    method: "deposit".to_string(),
+                    args: args![Variable("add_liquidity_result.2")],
                 },
             ],
             // proof needed to withdraw (from account) and mint (the lp_resource owned by the test identity)
             // respectively
             vec![test.account_proof.clone(), test.template_test.get_test_proof()],
         )
-        .unwrap();
 }
 
 fn remove_liquidity(test: &mut TariswapTest, lp_amount: Amount) {
@@ -257,48 +438,362 @@ fn remove_liquidity(test: &mut TariswapTest, lp_amount: Amount) {
         .unwrap();
 }
 
-fn get_pool_balance(test: &mut TariswapTest, resource_address: ResourceAddress) -> Amount {
+fn remove_liquidity_percent(test: &mut TariswapTest, lp_amount: Amount, percent: u16) {
     test.template_test
-        .call_method(test.pool_component, "get_pool_balance", args![resource_address], vec![])
+        .execute_and_commit(
+            vec![
+                Instruction::CallMethod {
+                    component_address: test.account_address,
+                    method: "withdraw".to_string(),
+                    args: args![test.lp_resource, lp_amount],
+                },
+                Instruction::PutLastInstructionOutputOnWorkspace {
+                    key: b"lp_bucket".to_vec(),
+                },
+                Instruction::CallMethod {
+                    component_address: test.pool_component,
+                    method: "remove_liquidity_percent".to_string(),
+                    args: args![Variable("lp_bucket"), percent],
+                },
+                Instruction::PutLastInstructionOutputOnWorkspace {
+                    key: b"pool_buckets".to_vec(),
+                },
+                Instruction::CallMethod {
+                    component_address: test.account_address,
+                    method: "deposit".to_string(),
+                    args: args![Variable("pool_buckets.0"),],
+                },
+                Instruction::CallMethod {
+                    component_address: test.account_address,
+                    method: "deposit".to_string(),
+                    args: args![Variable("pool_buckets.1"),],
+                },
+                // the LP tokens that were not burned
+                Instruction::CallMethod {
+                    component_address: test.account_address,
+                    method: "deposit".to_string(),
+                    args: args![Variable("pool_buckets.2"),],
+                },
+            ],
+            // proof needed to withdraw (from account) and burn (the lp_resource owned by the test identity)
+            // respectively
+            vec![test.account_proof.clone(), test.template_test.get_test_proof()],
+        )
+        .unwrap();
 }
 
-fn get_account_balance(test: &mut TariswapTest, resource_address: ResourceAddress) -> Amount {
+fn remove_liquidity_with_min(
+    test: &mut TariswapTest,
+    lp_amount: Amount,
+    min_a: Amount,
+    min_b: Amount,
+) -> anyhow::Result<ExecuteResult> {
     test.template_test
-        .call_method(test.account_address, "balance", args![resource_address], vec![])
+        .execute_and_commit(
+            vec![
+                Instruction::CallMethod {
+                    component_address: test.account_address,
+                    method: "withdraw".to_string(),
+                    args: args![test.lp_resource, lp_amount],
+                },
+                Instruction::PutLastInstructionOutputOnWorkspace {
+                    key: b"lp_bucket".to_vec(),
+                },
+                Instruction::CallMethod {
+                    component_address: test.pool_component,
+                    method: "remove_liquidity_with_min".to_string(),
+                    args: args![Variable("lp_bucket"), min_a, min_b],
+                },
+                Instruction::PutLastInstructionOutputOnWorkspace {
+                    key: b"pool_buckets".to_vec(),
+                },
+                Instruction::CallMethod {
+                    component_address: test.account_address,
+                    method: "deposit".to_string(),
+                    args: args![Variable("pool_buckets.0"),],
+                },
+                Instruction::CallMethod {
+                    component_address: test.account_address,
+                    method: "deposit".to_string(),
+                    args: args![Variable("pool_buckets.1"),],
+                },
+            ],
+            // proof needed to withdraw (from account) and burn (the lp_resource owned by the test identity)
+            // respectively
+            vec![test.account_proof.clone(), test.template_test.get_test_proof()],
+        )
 }
 
-fn assert_swap(
+fn flash_loan(
     test: &mut TariswapTest,
-    input_resource: &ResourceAddress,
-    input_amount: i64,
-    output_resource: &ResourceAddress,
-    expected_output_amount: i64,
-) {
-    // create the amount objects
-    let input_amount = Amount::new(input_amount);
-    let expected_output_amount = Amount::new(expected_output_amount);
-
-    // save the current pool balances for later comparison
-    let input_pool_balance = get_pool_balance(test, *input_resource);
-    let output_pool_balance = get_pool_balance(test, *output_resource);
-
-    // call the component
-    swap(test, input_resource, output_resource, input_amount, expected_output_amount).unwrap();
-
-    // check that the new pool balances are expected
-    let new_input_pool_balance = get_pool_balance(test, *input_resource);
-    let new_output_pool_balance = get_pool_balance(test, *output_resource);
-    assert_eq!(new_input_pool_balance, input_pool_balance + input_amount);
-    assert_eq!(new_output_pool_balance, output_pool_balance - expected_output_amount);
-}
-
-fn assert_add_liquidity(test: &mut TariswapTest, a_amount: i64, b_amount: i64, expected_lp_amount: i64) {
-    // create the amount objects
-    let a_amount = Amount::new(a_amount);
-    let b_amount = Amount::new(b_amount);
-    let expected_lp_amount = Amount::new(expected_lp_amount);
-
-    // save the resource addreses to keep the compiler happy
+    resource_address: ResourceAddress,
+    loan_amount: Amount,
+    repayment_amount: Amount,
+) -> anyhow::Result<ExecuteResult> {
+    test.template_test
+        .execute_and_commit(
+            vec![
+                Instruction::CallMethod {
+                    component_address: test.pool_component,
+                    method: "flash_loan".to_string(),
+                    args: args![resource_address, loan_amount],
+                },
+                Instruction::PutLastInstructionOutputOnWorkspace {
+                    key: b"loan".to_vec(),
+                },
+                // the borrowed tokens would be used here, we just join them with the fee in the account
+                Instruction::CallMethod {
+                    component_address: test.account_address,
+                    method: "deposit".to_string(),
+                    args: args![Variable("loan.0")],
+                },
+                Instruction::CallMethod {
+                    component_address: test.account_address,
+                    method: "withdraw".to_string(),
+                    args: args![resource_address, repayment_amount],
+                },
+                Instruction::PutLastInstructionOutputOnWorkspace {
+                    key: b"repayment".to_vec(),
+                },
+                Instruction::CallMethod {
+                    component_address: test.pool_component,
+                    method: "repay_flash_loan".to_string(),
+                    args: args![Variable("repayment"), Variable("loan.1")],
+                },
+            ],
+            // proof needed to withdraw
+            vec![test.account_proof.clone()],
+        )
+}
+
+fn donate(test: &mut TariswapTest, resource_address: ResourceAddress, amount: Amount) -> anyhow::Result<ExecuteResult> {
+    test.template_test
+        .execute_and_commit(
+            vec![
+                Instruction::CallMethod {
+                    component_address: test.account_address,
+                    method: "withdraw".to_string(),
+                    args: args![resource_address, amount],
+                },
+                Instruction::PutLastInstructionOutputOnWorkspace {
+                    key: b"donation".to_vec(),
+                },
+                Instruction::CallMethod {
+                    component_address: test.pool_component,
+                    method: "donate".to_string(),
+                    args: args![Variable("donation")],
+                },
+            ],
+            // proof needed to withdraw
+            vec![test.account_proof.clone()],
+        )
+}
+
+fn remove_pool(
+    test: &mut TariswapTest,
+    a_resource: ResourceAddress,
+    b_resource: ResourceAddress,
+) -> anyhow::Result<ExecuteResult> {
+    let admin_badge_resource: ResourceAddress =
+        test.template_test
+            .call_method(test.index_component, "admin_badge_resource", args![], vec![]);
+
+    test.template_test
+        .execute_and_commit(
+            vec![
+                Instruction::CallMethod {
+                    component_address: test.account_address,
+                    method: "withdraw".to_string(),
+                    args: args![admin_badge_resource, Amount::new(1)],
+                },
+                Instruction::PutLastInstructionOutputOnWorkspace {
+                    key: b"admin_badge".to_vec(),
+                },
+                Instruction::CallMethod {
+                    component_address: test.index_component,
+                    method: "remove_pool".to_string(),
+                    args: args![Variable("admin_badge"), a_resource, b_resource],
+                },
+                Instruction::PutLastInstructionOutputOnWorkspace {
+                    key: b"admin_badge".to_vec(),
+                },
+                Instruction::CallMethod {
+                    component_address: test.account_address,
+                    method: "deposit".to_string(),
+                    args: args![Variable("admin_badge")],
+                },
+            ],
+            // proof needed to withdraw
+            vec![test.account_proof.clone()],
+        )
+}
+
+fn get_pool_balance(test: &mut TariswapTest, resource_address: ResourceAddress) -> Amount {
+    test.template_test
+        .call_method(test.pool_component, "get_pool_balance", args![resource_address], vec![])
+}
+
+fn get_lp_total_supply(test: &mut TariswapTest) -> Amount {
+    test.template_test
+        .call_method(test.pool_component, "lp_total_supply", args![], vec![])
+}
+
+fn get_spot_price(test: &mut TariswapTest, base: ResourceAddress) -> Amount {
+    test.template_test
+        .call_method(test.pool_component, "get_spot_price", args![base], vec![])
+}
+
+fn get_price_cumulative(test: &mut TariswapTest) -> (Amount, u64) {
+    test.template_test
+        .call_method(test.pool_component, "get_price_cumulative", args![], vec![])
+}
+
+fn set_epoch(test: &mut TariswapTest, new_epoch: u64) {
+    test.template_test.set_virtual_substate(
+        VirtualSubstateId::CurrentEpoch,
+        VirtualSubstate::CurrentEpoch(new_epoch),
+    );
+}
+
+fn get_amount_out(test: &mut TariswapTest, input_resource: ResourceAddress, input_amount: Amount) -> Amount {
+    test.template_test
+        .call_method(test.pool_component, "get_amount_out", args![input_resource, input_amount], vec![])
+}
+
+fn get_protocol_fees(test: &mut TariswapTest, resource_address: ResourceAddress) -> Amount {
+    test.template_test
+        .call_method(test.pool_component, "get_protocol_fees", args![resource_address], vec![])
+}
+
+fn withdraw_protocol_fees(test: &mut TariswapTest, resource_address: ResourceAddress) {
+    let treasury_badge_resource: ResourceAddress =
+        test.template_test
+            .call_method(test.pool_component, "treasury_badge_resource", args![], vec![]);
+
+    test.template_test
+        .execute_and_commit(
+            vec![
+                Instruction::CallMethod {
+                    component_address: test.account_address,
+                    method: "withdraw".to_string(),
+                    args: args![treasury_badge_resource, Amount::new(1)],
+                },
+                Instruction::PutLastInstructionOutputOnWorkspace {
+                    key: b"treasury_badge".to_vec(),
+                },
+                Instruction::CallMethod {
+                    component_address: test.pool_component,
+                    method: "withdraw_protocol_fees".to_string(),
+                    args: args![Variable("treasury_badge"), resource_address],
+                },
+                Instruction::PutLastInstructionOutputOnWorkspace {
+                    key: b"ret".to_vec(),
+                },
+                Instruction::CallMethod {
+                    component_address: test.account_address,
+                    method: "deposit".to_string(),
+                    args: args![Variable("ret.0")],
+                },
+                Instruction::CallMethod {
+                    component_address: test.account_address,
+                    method: "deposit".to_string(),
+                    args: args![Variable("ret.1")],
+                },
+            ],
+            // proof needed to withdraw
+            vec![test.account_proof.clone()],
+        )
+        .unwrap();
+}
+
+// calls "pause" or "unpause" on the pool with the treasury badge
+fn set_paused(test: &mut TariswapTest, method: &str) {
+    let treasury_badge_resource: ResourceAddress =
+        test.template_test
+            .call_method(test.pool_component, "treasury_badge_resource", args![], vec![]);
+
+    test.template_test
+        .execute_and_commit(
+            vec![
+                Instruction::CallMethod {
+                    component_address: test.account_address,
+                    method: "withdraw".to_string(),
+                    args: args![treasury_badge_resource, Amount::new(1)],
+                },
+                Instruction::PutLastInstructionOutputOnWorkspace {
+                    key: b"treasury_badge".to_vec(),
+                },
+                Instruction::CallMethod {
+                    component_address: test.pool_component,
+                    method: method.to_string(),
+                    args: args![Variable("treasury_badge")],
+                },
+                Instruction::PutLastInstructionOutputOnWorkspace {
+                    key: b"treasury_badge_returned".to_vec(),
+                },
+                Instruction::CallMethod {
+                    component_address: test.account_address,
+                    method: "deposit".to_string(),
+                    args: args![Variable("treasury_badge_returned")],
+                },
+            ],
+            // proof needed to withdraw
+            vec![test.account_proof.clone()],
+        )
+        .unwrap();
+}
+
+fn get_account_balance(test: &mut TariswapTest, resource_address: ResourceAddress) -> Amount {
+    test.template_test
+        .call_method(test.account_address, "balance", args![resource_address], vec![])
+}
+
+fn assert_swap(
+    test: &mut TariswapTest,
+    input_resource: &ResourceAddress,
+    input_amount: i64,
+    output_resource: &ResourceAddress,
+    expected_output_amount: i64,
+) {
+    // create the amount objects
+    let input_amount = Amount::new(input_amount);
+    let expected_output_amount = Amount::new(expected_output_amount);
+
+    // save the current pool balances for later comparison
+    let input_pool_balance = get_pool_balance(test, *input_resource);
+    let output_pool_balance = get_pool_balance(test, *output_resource);
+
+    // call the component
+    swap(test, input_resource, output_resource, input_amount, expected_output_amount).unwrap();
+
+    // check that the new pool balances are expected
+    let new_input_pool_balance = get_pool_balance(test, *input_resource);
+    let new_output_pool_balance = get_pool_balance(test, *output_resource);
+    assert_eq!(new_input_pool_balance, input_pool_balance + input_amount);
+    assert_eq!(new_output_pool_balance, output_pool_balance - expected_output_amount);
+}
+
+fn assert_add_liquidity(test: &mut TariswapTest, a_amount: i64, b_amount: i64, expected_lp_amount: i64) {
+    // liquidity that matches the pool ratio does not get any change
+    assert_add_liquidity_with_change(test, a_amount, b_amount, expected_lp_amount, 0, 0);
+}
+
+fn assert_add_liquidity_with_change(
+    test: &mut TariswapTest,
+    a_amount: i64,
+    b_amount: i64,
+    expected_lp_amount: i64,
+    expected_a_change: i64,
+    expected_b_change: i64,
+) {
+    // create the amount objects
+    let a_amount = Amount::new(a_amount);
+    let b_amount = Amount::new(b_amount);
+    let expected_lp_amount = Amount::new(expected_lp_amount);
+    let expected_a_change = Amount::new(expected_a_change);
+    let expected_b_change = Amount::new(expected_b_change);
+
+    // save the resource addreses to keep the compiler happy
     let a_resource = test.a_resource;
     let b_resource = test.b_resource;
     let lp_resource = test.lp_resource;
@@ -307,6 +802,8 @@ fn assert_add_liquidity(test: &mut TariswapTest, a_amount: i64, b_amount: i64, e
     let pool_a_balance = get_pool_balance(test, a_resource);
     let pool_b_balance = get_pool_balance(test, b_resource);
     let account_lp_balance = get_account_balance(test, lp_resource);
+    let account_a_balance = get_account_balance(test, a_resource);
+    let account_b_balance = get_account_balance(test, b_resource);
 
     // call the component
     add_liquidity(test, a_amount, b_amount);
@@ -315,11 +812,17 @@ fn assert_add_liquidity(test: &mut TariswapTest, a_amount: i64, b_amount: i64, e
     let new_account_lp_balance = get_account_balance(test, lp_resource);
     assert_eq!(new_account_lp_balance, account_lp_balance + expected_lp_amount);
 
-    // check pool balances
+    // the change came back to the account
+    let new_account_a_balance = get_account_balance(test, a_resource);
+    let new_account_b_balance = get_account_balance(test, b_resource);
+    assert_eq!(new_account_a_balance, account_a_balance - a_amount + expected_a_change);
+    assert_eq!(new_account_b_balance, account_b_balance - b_amount + expected_b_change);
+
+    // check pool balances, the pool only keeps the amounts that were not returned as change
     let new_pool_a_balance = get_pool_balance(test, a_resource);
     let new_pool_b_balance = get_pool_balance(test, b_resource);
-    assert_eq!(new_pool_a_balance, pool_a_balance + a_amount);
-    assert_eq!(new_pool_b_balance, pool_b_balance + b_amount);
+    assert_eq!(new_pool_a_balance, pool_a_balance + a_amount - expected_a_change);
+    assert_eq!(new_pool_b_balance, pool_b_balance + b_amount - expected_b_change);
 }
 
 fn assert_remove_liquidity(
@@ -368,7 +871,8 @@ fn it_swaps_fungible_tokens() {
 
     // add some liquidity
     let liquidity_amount = 500;
-    let expected_lp_amount = liquidity_amount * 2; // we provided both "a" and "b" tokens
+    // we provided both "a" and "b" tokens, minus the minimum liquidity locked in the first deposit
+    let expected_lp_amount = liquidity_amount * 2 - MINIMUM_LIQUIDITY;
     assert_add_liquidity(&mut test, liquidity_amount, liquidity_amount, expected_lp_amount);
 
     // let's do a swap, giving "A" tokens for "B" tokens
@@ -384,8 +888,8 @@ fn it_swaps_fungible_tokens() {
 
     // remove liquidity
     let lp_amount_to_remove = 100;
-    let expected_a_amount = 50;
-    let expected_b_amount = 51;
+    let expected_a_amount = 49;
+    let expected_b_amount = 50;
     assert_remove_liquidity(&mut test, lp_amount_to_remove, expected_a_amount, expected_b_amount);
 }
 
@@ -412,59 +916,1036 @@ fn it_detects_existing_pools() {
 }
 
 #[test]
-fn it_fails_when_max_slippage_exceeded() {
+fn it_swaps_along_a_route() {
+    let mut test = setup(0);
+    let a_resource = test.a_resource;
+    let b_resource = test.b_resource;
+    add_liquidity(&mut test, Amount::new(1000), Amount::new(1000));
+
+    // create and fund a second pool for the B-C pair
+    let (c_faucet, c_resource) = create_faucet_component(&mut test.template_test, "C".to_string());
+    fund_account(&mut test.template_test, test.account_address, c_faucet);
+    let (bc_pool_component, _) = create_pool_component(
+        &mut test.template_test,
+        (b_resource, "B".to_string()),
+        (c_resource, "C".to_string()),
+        test.index_component,
+        test.account_address,
+    );
+    let ab_pool_component = test.pool_component;
+    test.pool_component = bc_pool_component;
+    test.a_resource = b_resource;
+    test.b_resource = c_resource;
+    add_liquidity(&mut test, Amount::new(1000), Amount::new(1000));
+    test.pool_component = ab_pool_component;
+    test.a_resource = a_resource;
+    test.b_resource = b_resource;
+
+    // 100 A are swapped for 91 B, that are swapped for 84 C
+    let path = vec![a_resource, b_resource, c_resource];
+    let res = swap_route_with_min(&mut test, path.clone(), Amount::new(100), Amount::new(85));
+    assert!(res
+        .unwrap_err()
+        .to_string()
+        .contains("The route output 84 is below the minimum of 85"));
+
+    let c_balance = get_account_balance(&mut test, c_resource);
+    swap_route_with_min(&mut test, path, Amount::new(100), Amount::new(84)).unwrap();
+    assert_eq!(get_account_balance(&mut test, c_resource), c_balance + Amount::new(84));
+}
+
+#[test]
+fn it_rejects_pools_for_identical_resources() {
+    let mut test = setup(0);
+    let (_, c_resource) = create_faucet_component(&mut test.template_test, "C".to_string());
+
+    let transaction = new_pool_transaction(&mut test, c_resource, c_resource);
+    let reason = test.template_test.execute_expect_failure(transaction, vec![]);
+    assert_reject_reason(reason, "Cannot create a pool for identical resources");
+}
+
+#[test]
+fn it_finds_pools_by_pair() {
     // init the test
     let fee = 50; // 5% market fee
     let mut test = setup(fee);
 
-    // copy the resource addresses to keep the borrow checker happy
+    // copy values to keep the borrow checker happy
     let a_resource = test.a_resource;
     let b_resource = test.b_resource;
+    let index_component = test.index_component;
 
-    // add some liquidity
-    let liquidity_amount = 500;
-    let expected_lp_amount = liquidity_amount * 2; // we provided both "a" and "b" tokens
-    assert_add_liquidity(&mut test, liquidity_amount, liquidity_amount, expected_lp_amount);
-
-    // let's do a swap, giving "A" tokens for "B" tokens
-    // create the amount objects
-    let a_amount = Amount::new(50);
-    let expected_b_amount = Amount::new(44);
+    // the pool can be found in both directions
+    let pool: Option<ComponentAddress> =
+        test.template_test
+            .call_method(index_component, "find_pool", args![a_resource, b_resource], vec![]);
+    assert_eq!(pool, Some(test.pool_component));
+    let pool: Option<ComponentAddress> =
+        test.template_test
+            .call_method(index_component, "find_pool", args![b_resource, a_resource], vec![]);
+    assert_eq!(pool, Some(test.pool_component));
+    let exists: bool = test
+        .template_test
+        .call_method(index_component, "pool_exists", args![b_resource, a_resource], vec![]);
+    assert!(exists);
 
-    // we are going to use a max slippage that is not going to be satisfied
-    let excessive_b_amount = expected_b_amount + 1;
- 
-    // the swap should fail with a assertion error
-    let res = swap(&mut test, &a_resource, &b_resource, a_amount, excessive_b_amount);
-    assert!(res.is_err());
-    let expected_error_message = AssertError::InvalidAmount { expected: excessive_b_amount, got: expected_b_amount};
-    assert!(res.err().unwrap().to_string().contains(&expected_error_message.to_string()));
+    // there is no pool for a new pair
+    let (_, c_resource) = create_faucet_component(&mut test.template_test, "C".to_string());
+    let pool: Option<ComponentAddress> =
+        test.template_test
+            .call_method(index_component, "find_pool", args![a_resource, c_resource], vec![]);
+    assert_eq!(pool, None);
+    let exists: bool = test
+        .template_test
+        .call_method(index_component, "pool_exists", args![a_resource, c_resource], vec![]);
+    assert!(!exists);
 }
 
-fn assert_duplicated_pool_error(
-    test: &mut TariswapTest,
-    a_resource: ResourceAddress,
-    b_resource: ResourceAddress
-) {
-    let transaction = new_pool_transaction(test, a_resource, b_resource);
-    let reason = test.template_test.execute_expect_failure(
-        transaction,
-        vec![],
-    );
-    assert_reject_reason(reason, "A pool already exists for the input resources");
+#[test]
+fn it_removes_empty_pools_from_the_index() {
+    // init the test
+    let fee = 50; // 5% market fee
+    let mut test = setup(fee);
+
+    // copy values to keep the borrow checker happy
+    let a_resource = test.a_resource;
+    let b_resource = test.b_resource;
+
+    // the pool has no liquidity yet, so it can be removed
+    remove_pool(&mut test, b_resource, a_resource).unwrap();
+
+    // a new pool can now be created for the same pair
+    let transaction = new_pool_transaction(&mut test, a_resource, b_resource);
+    let result = test.template_test.execute_expect_success(transaction, vec![]);
+    let (new_pool_component, new_lp_resource, _) = result.finalize.execution_results[0]
+        .decode::<(ComponentAddress, ResourceAddress, Bucket)>()
+        .unwrap();
+    test.pool_component = new_pool_component;
+    test.lp_resource = new_lp_resource;
+
+    // pools with liquidity cannot be removed
+    assert_add_liquidity(&mut test, 500, 500, 1000 - MINIMUM_LIQUIDITY);
+    let res = remove_pool(&mut test, a_resource, b_resource);
+    assert!(res.unwrap_err().to_string().contains("The pool still has liquidity"));
 }
 
-fn new_pool_transaction(
-    test: &mut TariswapTest,
+#[test]
+fn it_overrides_the_fee_of_a_pool() {
+    // init the test
+    let fee = 50; // 5% market fee
+    let mut test = setup(fee);
+
+    // copy values to keep the borrow checker happy
+    let a_resource = test.a_resource;
+    let index_component = test.index_component;
+
+    // the pool created in the setup uses the default fee of the index
+    let pool_fee: u16 = test
+        .template_test
+        .call_method(test.pool_component, "fee", args![], vec![]);
+    assert_eq!(pool_fee, fee);
+
+    // create a new pool with a lower fee
+    let (_, c_resource) = create_faucet_component(&mut test.template_test, "C".to_string());
+    let transaction = new_pool_transaction_with_fee(&mut test, a_resource, c_resource, Some(1));
+    test.template_test.execute_expect_success(transaction, vec![]);
+
+    let c_pool: Option<ComponentAddress> =
+        test.template_test
+            .call_method(index_component, "find_pool", args![a_resource, c_resource], vec![]);
+    let c_pool_fee: u16 = test
+        .template_test
+        .call_method(c_pool.unwrap(), "fee", args![], vec![]);
+    assert_eq!(c_pool_fee, 1);
+
+    // out of bounds fees are rejected
+    let (_, d_resource) = create_faucet_component(&mut test.template_test, "D".to_string());
+    let transaction = new_pool_transaction_with_fee(&mut test, a_resource, d_resource, Some(1000));
+    let reason = test.template_test.execute_expect_failure(transaction, vec![]);
+    assert_reject_reason(reason, "Invalid fee override 1000");
+}
+
+#[test]
+fn it_caps_the_protocol_fee_of_low_fee_pools() {
+    let fee = 50; // 5% market fee
+    let protocol_fee = 10; // 1% protocol fee
+    let mut test = setup_with_protocol_fee(fee, protocol_fee);
+    let a_resource = test.a_resource;
+    let index_component = test.index_component;
+
+    // the fee override is lower than the protocol fee of the index
+    let (_, c_resource) = create_faucet_component(&mut test.template_test, "C".to_string());
+    let transaction = new_pool_transaction_with_fee(&mut test, a_resource, c_resource, Some(5));
+    test.template_test.execute_expect_success(transaction, vec![]);
+
+    // so the whole fee of the pool goes to the protocol
+    let c_pool: Option<ComponentAddress> =
+        test.template_test
+            .call_method(index_component, "find_pool", args![a_resource, c_resource], vec![]);
+    let c_pool_protocol_fee: u16 = test
+        .template_test
+        .call_method(c_pool.unwrap(), "protocol_fee", args![], vec![]);
+    assert_eq!(c_pool_protocol_fee, 5);
+
+    // the regular pools keep the protocol fee of the index
+    let pool_protocol_fee: u16 = test
+        .template_test
+        .call_method(test.pool_component, "protocol_fee", args![], vec![]);
+    assert_eq!(pool_protocol_fee, protocol_fee);
+
+    // an index cannot be created with a protocol fee higher than the market fee
+    let index_template = test.template_test.get_template_address("TariswapIndex");
+    let pool_template = test.template_test.get_template_address("TariswapPool");
+    let res = test.template_test.execute_and_commit(
+        vec![Instruction::CallFunction {
+            template_address: index_template,
+            function: "new".to_string(),
+            args: args![pool_template, 5u16, 10u16],
+        }],
+        vec![],
+    );
+    assert!(res.unwrap_err().to_string().contains("Invalid protocol fee 10"));
+}
+
+#[test]
+fn it_rejects_out_of_bounds_fees() {
+    let mut test = setup(50);
+    let index_template = test.template_test.get_template_address("TariswapIndex");
+    let pool_template = test.template_test.get_template_address("TariswapPool");
+    let a_resource = test.a_resource;
+    let b_resource = test.b_resource;
+
+    // the index cannot be created with a fee of 100% or more
+    let reason = test.template_test.execute_expect_failure(
+        Transaction::builder()
+            .call_function(index_template, "new", args![pool_template, 1000u16, 0u16])
+            .put_last_instruction_output_on_workspace("index")
+            .call_method(test.account_address, "deposit", args![Workspace("index.1")])
+            .sign(&RistrettoSecretKey::default())
+            .build(),
+        vec![],
+    );
+    assert_reject_reason(reason, "Invalid fee 1000");
+
+    // neither can a pool
+    let reason = test.template_test.execute_expect_failure(
+        Transaction::builder()
+            .call_function(
+                pool_template,
+                "new",
+                args![
+                    a_resource,
+                    b_resource,
+                    1000u16,
+                    0u16,
+                    None::<String>,
+                    None::<String>,
+                    None::<(ResourceAddress, Amount)>
+                ],
+            )
+            .put_last_instruction_output_on_workspace("pool")
+            .call_method(test.account_address, "deposit", args![Workspace("pool.2")])
+            .sign(&RistrettoSecretKey::default())
+            .build(),
+        vec![],
+    );
+    assert_reject_reason(reason, "Invalid fee 1000");
+
+    // but high fees under 100% are valid pool fees
+    let (_, c_resource) = create_faucet_component(&mut test.template_test, "C".to_string());
+    let transaction = new_pool_transaction_with_fee(&mut test, a_resource, c_resource, Some(999));
+    test.template_test.execute_expect_success(transaction, vec![]);
+}
+
+#[test]
+fn it_guards_the_initial_price_of_a_pool() {
+    let mut test = setup(0);
+    let a_resource = test.a_resource;
+    let index_component = test.index_component;
+    let account_address = test.account_address;
+
+    // create a pool that is expected to start at 2 "C" tokens per "A" token
+    let (c_faucet, c_resource) = create_faucet_component(&mut test.template_test, "C".to_string());
+    fund_account(&mut test.template_test, account_address, c_faucet);
+    let expected_price = Amount::new(2 * SPOT_PRICE_SCALE);
+    let transaction = new_pool_transaction_with_options(&mut test, a_resource, c_resource, None, Some(expected_price));
+    test.template_test.execute_expect_success(transaction, vec![]);
+    let c_pool: Option<ComponentAddress> =
+        test.template_test
+            .call_method(index_component, "find_pool", args![a_resource, c_resource], vec![]);
+
+    // use the new pool for the liquidity helpers
+    test.pool_component = c_pool.unwrap();
+    test.b_resource = c_resource;
+
+    // a first deposit at a price of 3 is rejected
+    let res = try_add_liquidity(&mut test, Amount::new(100), Amount::new(300));
+    assert!(res
+        .unwrap_err()
+        .to_string()
+        .contains("The initial deposit deviates from the expected price"));
+
+    // a first deposit inside the tolerance seeds the pool
+    add_liquidity(&mut test, Amount::new(100), Amount::new(204));
+    assert_eq!(get_spot_price(&mut test, a_resource), Amount::new(2_040_000));
+}
+
+#[test]
+fn it_aggregates_reserves_across_pools() {
+    // init the test
+    let fee = 50; // 5% market fee
+    let mut test = setup(fee);
+
+    // copy values to keep the borrow checker happy
+    let a_resource = test.a_resource;
+    let index_component = test.index_component;
+    let account_address = test.account_address;
+
+    // add liquidity to the A-B pool
+    assert_add_liquidity(&mut test, 500, 500, 1000 - MINIMUM_LIQUIDITY);
+    let ab_pool_a_balance = get_pool_balance(&mut test, a_resource);
+
+    // create and add liquidity to an A-C pool
+    let (c_faucet, c_resource) = create_faucet_component(&mut test.template_test, "C".to_string());
+    fund_account(&mut test.template_test, account_address, c_faucet);
+    let transaction = new_pool_transaction(&mut test, a_resource, c_resource);
+    test.template_test.execute_expect_success(transaction, vec![]);
+    let c_pool: Option<ComponentAddress> =
+        test.template_test
+            .call_method(index_component, "find_pool", args![a_resource, c_resource], vec![]);
+    test.pool_component = c_pool.unwrap();
+    test.b_resource = c_resource;
+    test.lp_resource = test
+        .template_test
+        .call_method(test.pool_component, "lp_resource", args![], vec![]);
+    assert_add_liquidity(&mut test, 300, 200, 500 - MINIMUM_LIQUIDITY);
+    let ac_pool_a_balance = get_pool_balance(&mut test, a_resource);
+
+    // the aggregate is the sum of the "a" balances of both pools
+    let total_reserves: Amount = test
+        .template_test
+        .call_method(index_component, "get_total_reserves", args![a_resource], vec![]);
+    assert_eq!(total_reserves, ab_pool_a_balance + ac_pool_a_balance);
+    assert_eq!(total_reserves, Amount::new(800));
+}
+
+#[test]
+fn it_fails_when_max_slippage_exceeded() {
+    // init the test
+    let fee = 50; // 5% market fee
+    let mut test = setup(fee);
+
+    // copy the resource addresses to keep the borrow checker happy
+    let a_resource = test.a_resource;
+    let b_resource = test.b_resource;
+
+    // add some liquidity
+    let liquidity_amount = 500;
+    // we provided both "a" and "b" tokens, minus the minimum liquidity locked in the first deposit
+    let expected_lp_amount = liquidity_amount * 2 - MINIMUM_LIQUIDITY;
+    assert_add_liquidity(&mut test, liquidity_amount, liquidity_amount, expected_lp_amount);
+
+    // let's do a swap, giving "A" tokens for "B" tokens
+    // create the amount objects
+    let a_amount = Amount::new(50);
+    let expected_b_amount = Amount::new(44);
+
+    // we are going to use a max slippage that is not going to be satisfied
+    let excessive_b_amount = expected_b_amount + 1;
+ 
+    // the swap should fail with a assertion error
+    let res = swap(&mut test, &a_resource, &b_resource, a_amount, excessive_b_amount);
+    assert!(res.is_err());
+    let expected_error_message = AssertError::InvalidAmount { expected: excessive_b_amount, got: expected_b_amount};
+    assert!(res.err().unwrap().to_string().contains(&expected_error_message.to_string()));
+}
+
+#[test]
+fn it_returns_the_spot_price() {
+    // init the test
+    let fee = 50; // 5% market fee
+    let mut test = setup(fee);
+
+    // copy the resource addresses to keep the borrow checker happy
+    let a_resource = test.a_resource;
+    let b_resource = test.b_resource;
+
+    // add asymmetric liquidity, 1 "A" token is worth 4 "B" tokens
+    assert_add_liquidity(&mut test, 200, 800, 1000 - MINIMUM_LIQUIDITY);
+
+    // the spot price matches the reserve ratio
+    let a_price = get_spot_price(&mut test, a_resource);
+    assert_eq!(a_price, Amount::new(4 * SPOT_PRICE_SCALE));
+
+    // small ratios are not truncated to zero
+    let b_price = get_spot_price(&mut test, b_resource);
+    assert_eq!(b_price, Amount::new(SPOT_PRICE_SCALE / 4));
+}
+
+#[test]
+fn it_quotes_swaps() {
+    // init the test
+    let fee = 50; // 5% market fee
+    let mut test = setup(fee);
+
+    // copy the resource addresses to keep the borrow checker happy
+    let a_resource = test.a_resource;
+    let b_resource = test.b_resource;
+
+    // add some liquidity
+    assert_add_liquidity(&mut test, 500, 500, 1000 - MINIMUM_LIQUIDITY);
+
+    // the quote must match the output of the swap
+    let a_amount = 50;
+    let quoted_b_amount = get_amount_out(&mut test, a_resource, Amount::new(a_amount));
+    assert_eq!(quoted_b_amount, Amount::new(44));
+    assert_swap(&mut test, &a_resource, a_amount, &b_resource, quoted_b_amount.value());
+
+    // quotes for zero amounts are rejected
+    let transaction = Transaction::builder()
+        .call_method(test.pool_component, "get_amount_out", args![a_resource, Amount::zero()])
+        .sign(&RistrettoSecretKey::default())
+        .build();
+    let reason = test.template_test.execute_expect_failure(transaction, vec![]);
+    assert_reject_reason(reason, "Invalid input amount");
+
+    // quotes for resources outside the pool are rejected
+    let (_, c_resource) = create_faucet_component(&mut test.template_test, "C".to_string());
+    let transaction = Transaction::builder()
+        .call_method(test.pool_component, "get_amount_out", args![c_resource, Amount::new(a_amount)])
+        .sign(&RistrettoSecretKey::default())
+        .build();
+    let reason = test.template_test.execute_expect_failure(transaction, vec![]);
+    assert_reject_reason(reason, "is not in the pool");
+}
+
+#[test]
+fn it_rejects_swaps_exceeding_min_output() {
+    // init the test
+    let fee = 50; // 5% market fee
+    let mut test = setup(fee);
+
+    // copy the resource addresses to keep the borrow checker happy
+    let a_resource = test.a_resource;
+    let b_resource = test.b_resource;
+
+    // add some liquidity
+    assert_add_liquidity(&mut test, 500, 500, 1000 - MINIMUM_LIQUIDITY);
+
+    // the achievable output is 44 "B" tokens, so a higher minimum must be rejected
+    let a_amount = Amount::new(50);
+    let res = swap_with_min_output(&mut test, &a_resource, &b_resource, a_amount, Amount::new(45));
+    assert!(res.unwrap_err().to_string().contains("Slippage exceeded"));
+
+    // the input was not consumed
+    assert_eq!(get_pool_balance(&mut test, a_resource), Amount::new(500));
+
+    // a satisfiable bound succeeds
+    let b_balance = get_account_balance(&mut test, b_resource);
+    swap_with_min_output(&mut test, &a_resource, &b_resource, a_amount, Amount::new(44)).unwrap();
+    assert_eq!(get_account_balance(&mut test, b_resource), b_balance + Amount::new(44));
+}
+
+#[test]
+fn it_swaps_for_an_exact_output() {
+    // init the test
+    let fee = 50; // 5% market fee
+    let mut test = setup(fee);
+
+    // copy the resource addresses to keep the borrow checker happy
+    let a_resource = test.a_resource;
+    let b_resource = test.b_resource;
+
+    // add some liquidity
+    assert_add_liquidity(&mut test, 500, 500, 1000 - MINIMUM_LIQUIDITY);
+
+    // save the current balances for later comparison
+    let account_a_balance = get_account_balance(&mut test, a_resource);
+    let account_b_balance = get_account_balance(&mut test, b_resource);
+    let pool_a_balance = get_pool_balance(&mut test, a_resource);
+
+    // we want exactly 44 "B" tokens and are willing to pay up to 100 "A" tokens for them
+    let exact_output = Amount::new(44);
+    swap_exact_output(&mut test, &a_resource, &b_resource, Amount::new(100), exact_output).unwrap();
+
+    // we received exactly the requested output
+    assert_eq!(get_account_balance(&mut test, b_resource), account_b_balance + exact_output);
+
+    // only 51 "A" tokens were needed, applying market fees and the constant product formula: a = k / b
+    // the rest of the input was returned as change
+    let required_input = Amount::new(51);
+    assert_eq!(get_account_balance(&mut test, a_resource), account_a_balance - required_input);
+    assert_eq!(get_pool_balance(&mut test, a_resource), pool_a_balance + required_input);
+
+    // an insufficient input bucket is rejected
+    let res = swap_exact_output(&mut test, &a_resource, &b_resource, Amount::new(10), exact_output);
+    assert!(res.unwrap_err().to_string().contains("Insufficient input"));
+}
+
+#[test]
+fn it_rejects_liquidity_removals_below_min() {
+    // init the test
+    let fee = 50; // 5% market fee
+    let mut test = setup(fee);
+
+    // copy the resource addresses to keep the borrow checker happy
+    let a_resource = test.a_resource;
+    let b_resource = test.b_resource;
+    let lp_resource = test.lp_resource;
+
+    // add some liquidity
+    assert_add_liquidity(&mut test, 500, 500, 1000 - MINIMUM_LIQUIDITY);
+    let lp_balance = get_account_balance(&mut test, lp_resource);
+
+    // removing 10% of the LP tokens returns 50 of each token, so higher minimums must be rejected
+    let lp_amount = Amount::new(100);
+    let res = remove_liquidity_with_min(&mut test, lp_amount, Amount::new(51), Amount::new(50));
+    assert!(res.unwrap_err().to_string().contains("Insufficient output"));
+    let res = remove_liquidity_with_min(&mut test, lp_amount, Amount::new(50), Amount::new(51));
+    assert!(res.unwrap_err().to_string().contains("Insufficient output"));
+
+    // the LP tokens were not burned
+    assert_eq!(get_account_balance(&mut test, lp_resource), lp_balance);
+
+    // a satisfiable bound succeeds
+    let a_balance = get_account_balance(&mut test, a_resource);
+    let b_balance = get_account_balance(&mut test, b_resource);
+    remove_liquidity_with_min(&mut test, lp_amount, Amount::new(50), Amount::new(50)).unwrap();
+    assert_eq!(get_account_balance(&mut test, lp_resource), lp_balance - lp_amount);
+    assert_eq!(get_account_balance(&mut test, a_resource), a_balance + Amount::new(50));
+    assert_eq!(get_account_balance(&mut test, b_resource), b_balance + Amount::new(50));
+}
+
+#[test]
+fn it_collects_protocol_fees() {
+    // init the test
+    let fee = 50; // 5% market fee
+    let protocol_fee = 10; // 1% of each swap goes to the protocol, the remaining 4% to the LP holders
+    let mut test = setup_with_protocol_fee(fee, protocol_fee);
+
+    // copy the resource addresses to keep the borrow checker happy
+    let a_resource = test.a_resource;
+    let b_resource = test.b_resource;
+
+    // add some liquidity
+    assert_add_liquidity(&mut test, 5000, 5000, 10000 - MINIMUM_LIQUIDITY);
+
+    // do several swaps in both directions
+    for (input_resource, output_resource, amount) in [
+        (a_resource, b_resource, 1000),
+        (b_resource, a_resource, 1000),
+        (a_resource, b_resource, 500),
+    ] {
+        let amount = Amount::new(amount);
+        let expected_output = get_amount_out(&mut test, input_resource, amount);
+        swap(&mut test, &input_resource, &output_resource, amount, expected_output).unwrap();
+    }
+
+    // the protocol vaults grow by the protocol share of each swap input
+    assert_eq!(get_protocol_fees(&mut test, a_resource), Amount::new(15));
+    assert_eq!(get_protocol_fees(&mut test, b_resource), Amount::new(10));
+
+    // the treasury badge holder can withdraw the protocol fees
+    let account_a_balance = get_account_balance(&mut test, a_resource);
+    withdraw_protocol_fees(&mut test, a_resource);
+    assert_eq!(get_account_balance(&mut test, a_resource), account_a_balance + Amount::new(15));
+    assert_eq!(get_protocol_fees(&mut test, a_resource), Amount::zero());
+}
+
+#[test]
+fn it_locks_the_minimum_liquidity() {
+    // init the test
+    let fee = 50; // 5% market fee
+    let mut test = setup(fee);
+
+    // copy the resource addresses to keep the borrow checker happy
+    let a_resource = test.a_resource;
+    let b_resource = test.b_resource;
+    let lp_resource = test.lp_resource;
+
+    // the first provider does not receive the locked minimum liquidity
+    let liquidity_amount = 500;
+    let expected_lp_amount = liquidity_amount * 2 - MINIMUM_LIQUIDITY;
+    assert_add_liquidity(&mut test, liquidity_amount, liquidity_amount, expected_lp_amount);
+
+    // but the locked LP tokens still count for the total supply
+    let lp_total_supply: Amount = test
+        .template_test
+        .call_method(test.pool_component, "lp_total_supply", args![], vec![]);
+    assert_eq!(lp_total_supply, Amount::new(liquidity_amount * 2));
+
+    // a tiny deposit gets a proportional amount of LP tokens
+    let lp_balance = get_account_balance(&mut test, lp_resource);
+    add_liquidity(&mut test, Amount::new(1), Amount::new(1));
+    let minted_lp_amount = get_account_balance(&mut test, lp_resource) - lp_balance;
+    assert_eq!(minted_lp_amount, Amount::new(2));
+
+    // the tiny position cannot redeem more than what was deposited
+    let lp_total_supply: Amount = test
+        .template_test
+        .call_method(test.pool_component, "lp_total_supply", args![], vec![]);
+    for resource in [a_resource, b_resource] {
+        let redeemable = minted_lp_amount * get_pool_balance(&mut test, resource) / lp_total_supply;
+        assert!(redeemable <= Amount::new(1));
+    }
+}
+
+#[test]
+fn it_tracks_the_lp_total_supply() {
+    let mut test = setup(0);
+    let lp_resource = test.lp_resource;
+
+    // nothing is minted before the first deposit
+    assert_eq!(get_lp_total_supply(&mut test), Amount::zero());
+
+    // two asymmetric deposits
+    add_liquidity(&mut test, Amount::new(500), Amount::new(300));
+    let first_minted = get_account_balance(&mut test, lp_resource);
+    add_liquidity(&mut test, Amount::new(100), Amount::new(400));
+    let second_minted = get_account_balance(&mut test, lp_resource) - first_minted;
+
+    // the LP resource returned on the pool creation is the one minted on deposits
+    let minted_lp_resource: ResourceAddress =
+        test.template_test
+            .call_method(test.pool_component, "lp_resource", args![], vec![]);
+    assert_eq!(minted_lp_resource, lp_resource);
+    assert!(first_minted.is_positive());
+
+    // the second deposit only gets LP tokens for its proportional share of the smaller side
+    assert_eq!(first_minted, Amount::new(800 - MINIMUM_LIQUIDITY));
+    assert_eq!(second_minted, Amount::new(100 * 800 / 500));
+
+    // the total supply includes all the minted LP tokens, including the locked minimum liquidity
+    assert_eq!(
+        get_lp_total_supply(&mut test),
+        first_minted + second_minted + Amount::new(MINIMUM_LIQUIDITY)
+    );
+}
+
+#[test]
+fn it_mints_proportional_lp_for_unbalanced_liquidity() {
+    let mut test = setup(0);
+    let a_resource = test.a_resource;
+    let b_resource = test.b_resource;
+    let lp_resource = test.lp_resource;
+
+    assert_add_liquidity(&mut test, 500, 500, 1000 - MINIMUM_LIQUIDITY);
+
+    let lp_balance = get_account_balance(&mut test, lp_resource);
+    let account_b_balance = get_account_balance(&mut test, b_resource);
+    let pool_a_balance = get_pool_balance(&mut test, a_resource);
+    let pool_b_balance = get_pool_balance(&mut test, b_resource);
+    let lp_total_supply = get_lp_total_supply(&mut test);
+
+    // provide way more "B" tokens than the pool ratio requires
+    let (a_amount, b_amount) = (Amount::new(100), Amount::new(300));
+    add_liquidity(&mut test, a_amount, b_amount);
+
+    // the mint is the minimum of the proportional shares, so "A" is the limiting side
+    let expected_lp = (a_amount * lp_total_supply / pool_a_balance).min(b_amount * lp_total_supply / pool_b_balance);
+    assert_eq!(expected_lp, Amount::new(200));
+    assert_eq!(get_account_balance(&mut test, lp_resource), lp_balance + expected_lp);
+
+    // only the proportional amount of "B" was taken, the rest came back as change
+    let b_used = a_amount * pool_b_balance / pool_a_balance;
+    let expected_change = b_amount - b_used;
+    assert_eq!(expected_change, Amount::new(200));
+    assert_eq!(get_account_balance(&mut test, b_resource), account_b_balance - b_used);
+    assert_eq!(get_pool_balance(&mut test, a_resource), pool_a_balance + a_amount);
+    assert_eq!(get_pool_balance(&mut test, b_resource), pool_b_balance + b_used);
+}
+
+#[test]
+fn it_returns_change_for_mismatched_liquidity_ratios() {
+    let mut test = setup(0);
+
+    // the pool ratio is 1:2
+    assert_add_liquidity(&mut test, 500, 1000, 1500 - MINIMUM_LIQUIDITY);
+
+    // too many "A" tokens: 300 "B" only require 150 "A"
+    // the lp amount is proportional to the "B" share of the pool: 300 * 1500 / 1000
+    assert_add_liquidity_with_change(&mut test, 200, 300, 450, 50, 0);
+
+    // too many "B" tokens: 100 "A" only require 200 "B"
+    // the lp amount is proportional to the "A" share of the pool: 100 * 1950 / 650
+    assert_add_liquidity_with_change(&mut test, 100, 500, 300, 0, 300);
+}
+
+#[test]
+fn it_removes_a_percentage_of_the_liquidity() {
+    let mut test = setup(0);
+    let a_resource = test.a_resource;
+    let b_resource = test.b_resource;
+    let lp_resource = test.lp_resource;
+
+    assert_add_liquidity(&mut test, 1000, 1000, 2000 - MINIMUM_LIQUIDITY);
+    let lp_balance = get_account_balance(&mut test, lp_resource);
+    let account_a_balance = get_account_balance(&mut test, a_resource);
+    let account_b_balance = get_account_balance(&mut test, b_resource);
+
+    // remove 50% of the position, the whole LP position is sent but only half is burned
+    remove_liquidity_percent(&mut test, lp_balance, 5000);
+
+    // the position is 1990 of the 2000 LP tokens, so half of it is worth 995 * 1000 / 2000 of each reserve
+    assert_eq!(get_account_balance(&mut test, lp_resource), Amount::new(995));
+    assert_eq!(get_account_balance(&mut test, a_resource), account_a_balance + Amount::new(497));
+    assert_eq!(get_account_balance(&mut test, b_resource), account_b_balance + Amount::new(497));
+
+    // the percent is capped to 100%
+    let result = test.template_test.execute_and_commit(
+        vec![
+            Instruction::CallMethod {
+                component_address: test.account_address,
+                method: "withdraw".to_string(),
+                args: args![lp_resource, Amount::new(10)],
+            },
+            Instruction::PutLastInstructionOutputOnWorkspace {
+                key: b"lp_bucket".to_vec(),
+            },
+            Instruction::CallMethod {
+                component_address: test.pool_component,
+                method: "remove_liquidity_percent".to_string(),
+                args: args![Variable("lp_bucket"), 10001u16],
+            },
+        ],
+        vec![test.account_proof.clone(), test.template_test.get_test_proof()],
+    );
+    assert!(result.unwrap_err().to_string().contains("Invalid percent 10001"));
+}
+
+#[test]
+fn it_returns_both_reserves() {
+    let mut test = setup(0);
+    assert_add_liquidity(&mut test, 300, 700, 1000 - MINIMUM_LIQUIDITY);
+
+    let (first_resource, first_reserve, second_resource, second_reserve): (
+        ResourceAddress,
+        Amount,
+        ResourceAddress,
+        Amount,
+    ) = test
+        .template_test
+        .call_method(test.pool_component, "get_reserves", args![], vec![]);
+
+    // the resources are in canonical order
+    assert!(first_resource < second_resource);
+    let mut expected_resources = vec![test.a_resource, test.b_resource];
+    expected_resources.sort();
+    assert_eq!(vec![first_resource, second_resource], expected_resources);
+
+    // and match the individual balances
+    assert_eq!(first_reserve, get_pool_balance(&mut test, first_resource));
+    assert_eq!(second_reserve, get_pool_balance(&mut test, second_resource));
+}
+
+#[test]
+fn it_reports_the_canonical_resource_order() {
+    let mut test = setup(0);
+
+    let resource_order: (ResourceAddress, ResourceAddress) = test
+        .template_test
+        .call_method(test.pool_component, "get_resource_order", args![], vec![]);
+
+    // the pool and the index agree on the order, whatever the order of the inputs
+    for (first, second) in [(test.a_resource, test.b_resource), (test.b_resource, test.a_resource)] {
+        let pool_key: (ResourceAddress, ResourceAddress) = test
+            .template_test
+            .call_method(test.index_component, "get_pool_key", args![first, second], vec![]);
+        assert_eq!(resource_order, pool_key);
+    }
+}
+
+#[test]
+fn it_emits_swap_events() {
+    let fee = 50; // 5% market fee
+    let mut test = setup(fee);
+    let a_resource = test.a_resource;
+    let b_resource = test.b_resource;
+
+    assert_add_liquidity(&mut test, 500, 500, 1000 - MINIMUM_LIQUIDITY);
+
+    let result = swap(&mut test, &a_resource, &b_resource, Amount::new(50), Amount::new(44)).unwrap();
+    let event = result
+        .finalize
+        .events
+        .iter()
+        .find(|e| e.topic() == "swap")
+        .expect("swap event not found");
+    assert_eq!(event.get_payload("input_resource"), Some(a_resource.to_string()));
+    assert_eq!(event.get_payload("input_amount"), Some("50".to_string()));
+    assert_eq!(event.get_payload("output_resource"), Some(b_resource.to_string()));
+    assert_eq!(event.get_payload("output_amount"), Some("44".to_string()));
+
+    // the event includes the resulting reserves
+    let reserves = [
+        (event.get_payload("reserve_a_resource"), event.get_payload("reserve_a")),
+        (event.get_payload("reserve_b_resource"), event.get_payload("reserve_b")),
+    ];
+    assert!(reserves.contains(&(Some(a_resource.to_string()), Some("550".to_string()))));
+    assert!(reserves.contains(&(Some(b_resource.to_string()), Some("456".to_string()))));
+}
+
+#[test]
+fn it_rejects_swaps_with_invalid_resources() {
+    let mut test = setup(0);
+    let a_resource = test.a_resource;
+    assert_add_liquidity(&mut test, 500, 500, 1000 - MINIMUM_LIQUIDITY);
+
+    // the output resource does not belong to the pool
+    let (_, c_resource) = create_faucet_component(&mut test.template_test, "C".to_string());
+    let res = swap(&mut test, &a_resource, &c_resource, Amount::new(50), Amount::zero());
+    assert!(res.unwrap_err().to_string().contains("Resource not in pool"));
+
+    // a resource cannot be swapped for itself
+    let res = swap(&mut test, &a_resource, &a_resource, Amount::new(50), Amount::zero());
+    assert!(res.unwrap_err().to_string().contains("Cannot swap a resource for itself"));
+}
+
+#[test]
+fn it_rejects_swaps_without_output() {
+    let mut test = setup(0);
+    let a_resource = test.a_resource;
+    let b_resource = test.b_resource;
+    assert_add_liquidity(&mut test, 500, 500, 1000 - MINIMUM_LIQUIDITY);
+
+    // the input is kept by the trader, as the whole transaction is rejected
+    let a_balance = get_account_balance(&mut test, a_resource);
+    let res = swap(&mut test, &a_resource, &b_resource, Amount::zero(), Amount::zero());
+    assert!(res.unwrap_err().to_string().contains("Output rounds to zero, increase input"));
+    assert_eq!(get_account_balance(&mut test, a_resource), a_balance);
+
+    // the smallest possible input still gets some output
+    assert_swap(&mut test, &a_resource, 1, &b_resource, 1);
+}
+
+#[test]
+fn it_rejects_swaps_without_liquidity() {
+    let mut test = setup(0);
+    let a_resource = test.a_resource;
+    let b_resource = test.b_resource;
+
+    // nobody added liquidity to the new pool
+    let res = swap(&mut test, &a_resource, &b_resource, Amount::new(50), Amount::zero());
+    assert!(res.unwrap_err().to_string().contains("Pool has no liquidity"));
+    let res = swap_exact_output(&mut test, &a_resource, &b_resource, Amount::new(50), Amount::new(10));
+    assert!(res.unwrap_err().to_string().contains("Pool has no liquidity"));
+}
+
+#[test]
+fn it_can_be_paused_by_the_admin() {
+    let fee = 50; // 5% market fee
+    let mut test = setup(fee);
+    let a_resource = test.a_resource;
+    let b_resource = test.b_resource;
+    assert_add_liquidity(&mut test, 500, 500, 1000 - MINIMUM_LIQUIDITY);
+
+    set_paused(&mut test, "pause");
+    let is_paused: bool = test
+        .template_test
+        .call_method(test.pool_component, "is_paused", args![], vec![]);
+    assert!(is_paused);
+
+    // swaps and new liquidity are rejected
+    let res = swap(&mut test, &a_resource, &b_resource, Amount::new(50), Amount::new(44));
+    assert!(res.unwrap_err().to_string().contains("The pool is paused"));
+    let res = try_add_liquidity(&mut test, Amount::new(100), Amount::new(100));
+    assert!(res.unwrap_err().to_string().contains("The pool is paused"));
+    let res = flash_loan(&mut test, a_resource, Amount::new(100), Amount::new(101));
+    assert!(res.unwrap_err().to_string().contains("The pool is paused"));
+
+    // but liquidity providers can still exit
+    assert_remove_liquidity(&mut test, 100, 50, 50);
+
+    // everything works again after unpausing
+    set_paused(&mut test, "unpause");
+    assert_swap(&mut test, &a_resource, 50, &b_resource, 44);
+}
+
+#[test]
+fn it_supports_flash_loans() {
+    // init the test
+    let fee = 50; // 5% market fee
+    let mut test = setup(fee);
+
+    // copy the resource addresses to keep the borrow checker happy
+    let a_resource = test.a_resource;
+
+    // add some liquidity
+    assert_add_liquidity(&mut test, 500, 500, 1000 - MINIMUM_LIQUIDITY);
+
+    // borrow and repay with the fee (0.5% of 100, rounded up)
+    let loan_amount = Amount::new(100);
+    let flash_fee = Amount::new(1);
+    flash_loan(&mut test, a_resource, loan_amount, loan_amount + flash_fee).unwrap();
+
+    // the pool keeps the fee
+    assert_eq!(get_pool_balance(&mut test, a_resource), Amount::new(500) + flash_fee);
+
+    // under-repaying the loan is rejected
+    let res = flash_loan(&mut test, a_resource, loan_amount, loan_amount);
+    assert!(res
+        .unwrap_err()
+        .to_string()
+        .contains("Insufficient flash loan repayment"));
+    assert_eq!(get_pool_balance(&mut test, a_resource), Amount::new(500) + flash_fee);
+}
+
+#[test]
+fn it_names_the_lp_token_after_the_pair() {
+    // init the test
+    let fee = 50; // 5% market fee
+    let test = setup(fee);
+
+    // the index sorts the pair resources, so the symbol order follows the resource addresses
+    let expected_symbol = if test.a_resource < test.b_resource {
+        "LP-A-B"
+    } else {
+        "LP-B-A"
+    };
+
+    let lp_resource = test
+        .template_test
+        .read_only_state_store()
+        .get_resource(&test.lp_resource)
+        .unwrap();
+    assert_eq!(lp_resource.metadata().get("symbol"), Some(expected_symbol));
+}
+
+#[test]
+fn it_accepts_donations() {
+    // init the test
+    let fee = 50; // 5% market fee
+    let mut test = setup(fee);
+
+    // copy the resource addresses to keep the borrow checker happy
+    let a_resource = test.a_resource;
+
+    // add some liquidity
+    assert_add_liquidity(&mut test, 500, 500, 1000 - MINIMUM_LIQUIDITY);
+
+    // before the donation, 100 LP tokens (10% of the supply) are worth 50 of each resource
+    let lp_total_supply: Amount = test
+        .template_test
+        .call_method(test.pool_component, "lp_total_supply", args![], vec![]);
+    let lp_amount = Amount::new(100);
+    let redeemable_a = lp_amount * get_pool_balance(&mut test, a_resource) / lp_total_supply;
+    assert_eq!(redeemable_a, Amount::new(50));
+
+    // donating does not mint any LP tokens
+    let lp_resource = test.lp_resource;
+    let lp_balance = get_account_balance(&mut test, lp_resource);
+    donate(&mut test, a_resource, Amount::new(100)).unwrap();
+    assert_eq!(get_account_balance(&mut test, lp_resource), lp_balance);
+    assert_eq!(get_pool_balance(&mut test, a_resource), Amount::new(600));
+
+    // the same LP tokens are now worth more "a" tokens
+    assert_remove_liquidity(&mut test, 100, 60, 50);
+
+    // resources that are not in the pool are rejected
+    let (c_faucet, c_resource) = create_faucet_component(&mut test.template_test, "C".to_string());
+    let account_address = test.account_address;
+    fund_account(&mut test.template_test, account_address, c_faucet);
+    let res = donate(&mut test, c_resource, Amount::new(100));
+    assert!(res.unwrap_err().to_string().contains("is not in the pool"));
+}
+
+#[test]
+fn it_accumulates_the_price_over_time() {
+    // init the test
+    let fee = 50; // 5% market fee
+    let mut test = setup(fee);
+
+    // copy the resource addresses to keep the borrow checker happy
+    let a_resource = test.a_resource;
+    let b_resource = test.b_resource;
+
+    // the accumulator tracks the price of the resource with the lowest address
+    let (pool_a_resource, pool_b_resource): (ResourceAddress, ResourceAddress) =
+        test.template_test
+            .call_method(test.pool_component, "get_resource_order", args![], vec![]);
+    assert_eq!(pool_a_resource, a_resource.min(b_resource));
+    assert_eq!(pool_b_resource, a_resource.max(b_resource));
+    let resource: ResourceAddress =
+        test.template_test
+            .call_method(test.pool_component, "get_a_resource", args![], vec![]);
+    assert_eq!(resource, pool_a_resource);
+
+    // add some liquidity, the pool was empty so nothing is accumulated yet
+    set_epoch(&mut test, 1);
+    assert_add_liquidity(&mut test, 500, 500, 1000 - MINIMUM_LIQUIDITY);
+    assert_eq!(get_price_cumulative(&mut test), (Amount::zero(), 1));
+
+    // the swap happens in the same epoch, so nothing is accumulated either
+    swap(&mut test, &pool_a_resource, &pool_b_resource, Amount::new(100), Amount::zero()).unwrap();
+    assert_eq!(get_price_cumulative(&mut test), (Amount::zero(), 1));
+    let first_price = get_spot_price(&mut test, pool_a_resource);
+
+    // the price after the first swap lasted for 10 epochs
+    set_epoch(&mut test, 11);
+    swap(&mut test, &pool_b_resource, &pool_a_resource, Amount::new(50), Amount::zero()).unwrap();
+    let expected_cumulative = first_price * Amount::new(10);
+    assert_eq!(get_price_cumulative(&mut test), (expected_cumulative, 11));
+    let second_price = get_spot_price(&mut test, pool_a_resource);
+
+    // the price after the second swap lasted for 5 epochs
+    set_epoch(&mut test, 16);
+    swap(&mut test, &pool_a_resource, &pool_b_resource, Amount::new(10), Amount::zero()).unwrap();
+    let expected_cumulative = expected_cumulative + second_price * Amount::new(5);
+    assert_eq!(get_price_cumulative(&mut test), (expected_cumulative, 16));
+}
+
+fn assert_duplicated_pool_error(
+    test: &mut TariswapTest,
+    a_resource: ResourceAddress,
+    b_resource: ResourceAddress
+) {
+    let transaction = new_pool_transaction(test, a_resource, b_resource);
+    let reason = test.template_test.execute_expect_failure(
+        transaction,
+        vec![],
+    );
+    assert_reject_reason(reason, "A pool already exists for the input resources");
+}
+
+fn new_pool_transaction(
+    test: &mut TariswapTest,
     a_resource: ResourceAddress,
     b_resource: ResourceAddress
+) -> Transaction {
+    new_pool_transaction_with_fee(test, a_resource, b_resource, None)
+}
+
+fn new_pool_transaction_with_fee(
+    test: &mut TariswapTest,
+    a_resource: ResourceAddress,
+    b_resource: ResourceAddress,
+    fee_override: Option<u16>,
+) -> Transaction {
+    new_pool_transaction_with_options(test, a_resource, b_resource, fee_override, None)
+}
+
+fn new_pool_transaction_with_options(
+    test: &mut TariswapTest,
+    a_resource: ResourceAddress,
+    b_resource: ResourceAddress,
+    fee_override: Option<u16>,
+    expected_price: Option<Amount>,
 ) -> Transaction {
     Transaction::builder()
         .call_method(
             test.index_component,
             "create_pool",
-            args![a_resource, b_resource],
+            args![
+                a_resource,
+                b_resource,
+                None::<String>,
+                None::<String>,
+                fee_override,
+                expected_price
+            ],
         )
+        .put_last_instruction_output_on_workspace("pool")
+        .call_method(test.account_address, "deposit", args![Workspace("pool.2")])
         // there are no user-protected methods in the tariswap
         .sign(&RistrettoSecretKey::default())
         .build()
@@ -0,0 +1,166 @@
+//   Copyright 2024. The Tari Project
+//
+//   Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//   following conditions are met:
+//
+//   1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//   disclaimer.
+//
+//   2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//   following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//   3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//   products derived from this software without specific prior written permission.
+//
+//   THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//   INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//   DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//   SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//   SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//   WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//   USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+
+use account_utils::assert_component_is_account;
+use tari_template_lib::prelude::*;
+
+#[template]
+mod ticket_sale {
+    use super::*;
+
+    /// Fixed-price sale of a bounded supply of NFT tickets for an event
+    /// Tickets can be returned for a full refund before the event starts, and are put on sale again
+    pub struct TicketSale {
+        event_name: String,
+
+        // holds all the tickets that are still for sale, the whole supply is minted upfront
+        tickets: Vault,
+        total_tickets: u64,
+        tickets_sold: u64,
+
+        // price (in XTR) of each ticket
+        price: Amount,
+
+        // refunds are only allowed before this epoch, and the organizer can withdraw the proceeds afterwards
+        event_epoch: u64,
+
+        // holds the payments of the sold tickets
+        proceeds: Vault,
+
+        organizer_badge_resource: ResourceAddress,
+    }
+
+    impl TicketSale {
+        // returns the badge that allows the organizer to withdraw the proceeds after the event
+        pub fn new(
+            event_name: String,
+            total_tickets: u64,
+            price: Amount,
+            event_epoch: u64,
+        ) -> (Component<Self>, Bucket) {
+            assert!(total_tickets > 0, "Invalid amount of tickets");
+            assert!(price.is_positive(), "Invalid price");
+            assert!(
+                event_epoch > Consensus::current_epoch(),
+                "The event must be in the future"
+            );
+
+            let tickets_bucket = ResourceBuilder::non_fungible()
+                .add_metadata("name", event_name.clone())
+                .mintable(AccessRule::DenyAll)
+                .initial_supply((1..=total_tickets).map(NonFungibleId::from_u64));
+
+            // we make sure that only the initial badge will be minted
+            let organizer_badge_bucket = ResourceBuilder::non_fungible()
+                .mintable(AccessRule::DenyAll)
+                .initial_supply_with_data(Some((NonFungibleId::random(), (&(), &()))));
+            let organizer_badge_resource = organizer_badge_bucket.resource_address();
+
+            let component = Component::new(Self {
+                event_name,
+                tickets: Vault::from_bucket(tickets_bucket),
+                total_tickets,
+                tickets_sold: 0,
+                price,
+                event_epoch,
+                proceeds: Vault::new_empty(XTR),
+                organizer_badge_resource,
+            })
+            .with_access_rules(AccessRules::allow_all())
+            .create();
+
+            (component, organizer_badge_bucket)
+        }
+
+        // sends one ticket to the buyer account
+        pub fn buy(&mut self, buyer_account_address: ComponentAddress, payment: Bucket) {
+            assert!(self.tickets_sold < self.total_tickets, "The tickets are sold out");
+
+            assert_eq!(
+                payment.resource_address(),
+                XTR,
+                "Invalid payment resource, the sale only accepts Tari (XTR) tokens"
+            );
+            assert!(payment.amount() == self.price, "Payment does not match the price");
+
+            // validate that the buyer account is really an account, so we can deposit the ticket
+            assert_component_is_account(buyer_account_address);
+
+            self.proceeds.deposit(payment);
+            let ticket_bucket = self.tickets.withdraw(Amount(1));
+            ComponentManager::get(buyer_account_address).call::<_, ()>("deposit".to_string(), args![ticket_bucket]);
+
+            self.tickets_sold += 1;
+        }
+
+        // returns the price of the tickets, which are put on sale again
+        pub fn refund(&mut self, ticket_bucket: Bucket) -> Bucket {
+            assert!(
+                Consensus::current_epoch() < self.event_epoch,
+                "Refunds are only allowed before the event"
+            );
+            assert!(
+                ticket_bucket.resource_address() == self.tickets.resource_address(),
+                "Invalid ticket resource"
+            );
+
+            let ticket_count = ticket_bucket.amount();
+            assert!(ticket_count.is_positive(), "The bucket is empty");
+
+            self.tickets.deposit(ticket_bucket);
+            self.tickets_sold -= ticket_count.value() as u64;
+
+            self.proceeds.withdraw(self.price * ticket_count)
+        }
+
+        // returns the badge back to the organizer
+        pub fn withdraw_proceeds(&mut self, organizer_badge: Bucket) -> (Bucket, Bucket) {
+            assert!(
+                organizer_badge.resource_address() == self.organizer_badge_resource,
+                "Invalid organizer badge"
+            );
+            assert!(
+                Consensus::current_epoch() >= self.event_epoch,
+                "The proceeds can only be withdrawn after the event"
+            );
+
+            (self.proceeds.withdraw_all(), organizer_badge)
+        }
+
+        pub fn get_event_name(&self) -> String {
+            self.event_name.clone()
+        }
+
+        pub fn get_tickets_sold(&self) -> u64 {
+            self.tickets_sold
+        }
+
+        pub fn get_ticket_resource(&self) -> ResourceAddress {
+            self.tickets.resource_address()
+        }
+
+        pub fn organizer_badge_resource(&self) -> ResourceAddress {
+            self.organizer_badge_resource
+        }
+    }
+}
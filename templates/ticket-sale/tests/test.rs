@@ -0,0 +1,207 @@
+//   Copyright 2024 The Tari Project
+//   SPDX-License-Identifier: BSD-3-Clause
+
+use tari_engine_types::virtual_substate::{VirtualSubstate, VirtualSubstateId};
+use tari_template_lib::args;
+use tari_template_lib::constants::XTR;
+use tari_template_lib::models::{Amount, Bucket, ComponentAddress, NonFungibleAddress, ResourceAddress};
+use tari_template_test_tooling::crypto::RistrettoSecretKey;
+use tari_template_test_tooling::support::assert_error::assert_reject_reason;
+use tari_template_test_tooling::TemplateTest;
+use tari_transaction::Transaction;
+
+const TOTAL_TICKETS: u64 = 3;
+const PRICE: Amount = Amount(50);
+const EVENT_EPOCH: u64 = 10;
+
+#[test]
+fn it_sells_tickets_up_to_the_cap() {
+    let TicketSaleTest {
+        mut test,
+        ticket_sale_component,
+        ticket_resource,
+        ..
+    } = setup();
+
+    let buyer = create_account(&mut test);
+    for _ in 0..TOTAL_TICKETS {
+        test.execute_expect_success(
+            buy_transaction(ticket_sale_component, &buyer, PRICE),
+            vec![buyer.owner_token.clone()],
+        );
+    }
+    assert_eq!(
+        get_account_balance(&mut test, &buyer, &ticket_resource),
+        Amount(TOTAL_TICKETS as i64)
+    );
+    let tickets_sold: u64 = test.call_method(ticket_sale_component, "get_tickets_sold", args![], vec![]);
+    assert_eq!(tickets_sold, TOTAL_TICKETS);
+
+    // there are no more tickets to sell
+    let reason = test.execute_expect_failure(
+        buy_transaction(ticket_sale_component, &buyer, PRICE),
+        vec![buyer.owner_token.clone()],
+    );
+    assert_reject_reason(reason, "The tickets are sold out");
+}
+
+#[test]
+fn it_refunds_tickets_before_the_event() {
+    let TicketSaleTest {
+        mut test,
+        ticket_sale_component,
+        ticket_resource,
+        organizer,
+    } = setup();
+
+    let buyer = create_account(&mut test);
+    let buyer_balance = get_account_balance(&mut test, &buyer, &XTR);
+    test.execute_expect_success(
+        buy_transaction(ticket_sale_component, &buyer, PRICE),
+        vec![buyer.owner_token.clone()],
+    );
+    test.execute_expect_success(
+        buy_transaction(ticket_sale_component, &buyer, PRICE),
+        vec![buyer.owner_token.clone()],
+    );
+
+    // a ticket is returned before the event
+    set_epoch(&mut test, EVENT_EPOCH - 1);
+    test.execute_expect_success(
+        refund_transaction(ticket_sale_component, &buyer, ticket_resource),
+        vec![buyer.owner_token.clone()],
+    );
+    assert_eq!(get_account_balance(&mut test, &buyer, &ticket_resource), Amount(1));
+    assert_eq!(get_account_balance(&mut test, &buyer, &XTR), buyer_balance - PRICE);
+    let tickets_sold: u64 = test.call_method(ticket_sale_component, "get_tickets_sold", args![], vec![]);
+    assert_eq!(tickets_sold, 1);
+
+    // refunds are not allowed once the event starts
+    set_epoch(&mut test, EVENT_EPOCH);
+    let reason = test.execute_expect_failure(
+        refund_transaction(ticket_sale_component, &buyer, ticket_resource),
+        vec![buyer.owner_token.clone()],
+    );
+    assert_reject_reason(reason, "Refunds are only allowed before the event");
+
+    // the organizer gets the payment of the remaining ticket
+    let organizer_balance = get_account_balance(&mut test, &organizer, &XTR);
+    let organizer_badge_resource: ResourceAddress =
+        test.call_method(ticket_sale_component, "organizer_badge_resource", args![], vec![]);
+    test.execute_expect_success(
+        Transaction::builder()
+            .call_method(organizer.component, "withdraw", args![organizer_badge_resource, Amount(1)])
+            .put_last_instruction_output_on_workspace("badge")
+            .call_method(ticket_sale_component, "withdraw_proceeds", args![Workspace("badge")])
+            .put_last_instruction_output_on_workspace("ret")
+            .call_method(organizer.component, "deposit", args![Workspace("ret.0")])
+            .call_method(organizer.component, "deposit", args![Workspace("ret.1")])
+            .sign(&organizer.key)
+            .build(),
+        vec![organizer.owner_token.clone()],
+    );
+    assert_eq!(get_account_balance(&mut test, &organizer, &XTR), organizer_balance + PRICE);
+}
+
+#[derive(Clone, Debug)]
+struct Account {
+    pub component: ComponentAddress,
+    pub owner_token: NonFungibleAddress,
+    pub key: RistrettoSecretKey,
+}
+
+struct TicketSaleTest {
+    test: TemplateTest,
+    ticket_sale_component: ComponentAddress,
+    ticket_resource: ResourceAddress,
+    organizer: Account,
+}
+
+fn setup() -> TicketSaleTest {
+    let mut test = TemplateTest::new(["."]);
+    let ticket_sale_template = test.get_template_address("TicketSale");
+
+    let organizer = create_account(&mut test);
+    let result = test.execute_expect_success(
+        Transaction::builder()
+            .call_function(
+                ticket_sale_template,
+                "new",
+                args!["Tari conference".to_string(), TOTAL_TICKETS, PRICE, EVENT_EPOCH],
+            )
+            .put_last_instruction_output_on_workspace("ret")
+            .call_method(organizer.component, "deposit", args![Workspace("ret.1")])
+            .sign(&organizer.key)
+            .build(),
+        vec![organizer.owner_token.clone()],
+    );
+    let (ticket_sale_component, _) = result.finalize.execution_results[0]
+        .decode::<(ComponentAddress, Bucket)>()
+        .unwrap();
+    let ticket_resource: ResourceAddress =
+        test.call_method(ticket_sale_component, "get_ticket_resource", args![], vec![]);
+
+    TicketSaleTest {
+        test,
+        ticket_sale_component,
+        ticket_resource,
+        organizer,
+    }
+}
+
+fn create_account(test: &mut TemplateTest) -> Account {
+    let (component, owner_token, key) = test.create_funded_account();
+    Account {
+        component,
+        owner_token,
+        key,
+    }
+}
+
+fn get_account_balance(test: &mut TemplateTest, account: &Account, resource: &ResourceAddress) -> Amount {
+    let result = test.execute_expect_success(
+        Transaction::builder()
+            .call_method(account.component, "balance", args![resource])
+            .sign(&account.key)
+            .build(),
+        vec![account.owner_token.clone()],
+    );
+    result.finalize.execution_results[0]
+        .decode::<Amount>()
+        .unwrap()
+}
+
+fn set_epoch(test: &mut TemplateTest, new_epoch: u64) {
+    test.set_virtual_substate(
+        VirtualSubstateId::CurrentEpoch,
+        VirtualSubstate::CurrentEpoch(new_epoch),
+    );
+}
+
+fn buy_transaction(ticket_sale_component: ComponentAddress, buyer: &Account, payment: Amount) -> Transaction {
+    Transaction::builder()
+        .call_method(buyer.component, "withdraw", args![XTR, payment])
+        .put_last_instruction_output_on_workspace("payment")
+        .call_method(
+            ticket_sale_component,
+            "buy",
+            args![buyer.component, Workspace("payment")],
+        )
+        .sign(&buyer.key)
+        .build()
+}
+
+fn refund_transaction(
+    ticket_sale_component: ComponentAddress,
+    account: &Account,
+    ticket_resource: ResourceAddress,
+) -> Transaction {
+    Transaction::builder()
+        .call_method(account.component, "withdraw", args![ticket_resource, Amount(1)])
+        .put_last_instruction_output_on_workspace("ticket")
+        .call_method(ticket_sale_component, "refund", args![Workspace("ticket")])
+        .put_last_instruction_output_on_workspace("refund")
+        .call_method(account.component, "deposit", args![Workspace("refund")])
+        .sign(&account.key)
+        .build()
+}
@@ -0,0 +1,119 @@
+//   Copyright 2024. The Tari Project
+//
+//   Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//   following conditions are met:
+//
+//   1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//   disclaimer.
+//
+//   2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//   following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//   3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//   products derived from this software without specific prior written permission.
+//
+//   THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//   INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//   DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//   SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//   SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//   WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//   USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use account_utils::assert_component_is_account;
+use tari_template_lib::prelude::*;
+
+// Maximum amount of tips kept in the log, the oldest ones are dropped to bound the substate size
+pub const MAX_TIP_LOG: usize = 20;
+
+// Maximum length (in bytes) of the message of a tip, otherwise a single tip could still grow the substate without bound
+pub const MAX_MESSAGE_LEN: usize = 140;
+
+#[template]
+mod tipjar {
+    use super::*;
+
+    pub struct TipJar {
+        // address of the account component that receives the tips
+        owner: ComponentAddress,
+
+        // badge that allows the owner to withdraw the tips
+        owner_badge_resource: ResourceAddress,
+
+        vault: Vault,
+
+        // most recent tips, as (amount, message, epoch)
+        tips: Vec<(Amount, String, u64)>,
+    }
+
+    impl TipJar {
+        // the owner badge is deposited directly in the owner account
+        pub fn new(owner: ComponentAddress) -> Component<Self> {
+            // needed to ensure that we can deposit the badge and the tips
+            assert_component_is_account(owner);
+
+            // we make sure that only the initial badge will be minted
+            let owner_badge_bucket = ResourceBuilder::non_fungible()
+                .mintable(AccessRule::DenyAll)
+                .initial_supply_with_data(Some((NonFungibleId::random(), (&(), &()))));
+            let owner_badge_resource = owner_badge_bucket.resource_address();
+            ComponentManager::get(owner).call::<_, ()>("deposit".to_string(), args![owner_badge_bucket]);
+
+            Component::new(Self {
+                owner,
+                owner_badge_resource,
+                vault: Vault::new_empty(XTR),
+                tips: vec![],
+            })
+            .with_access_rules(AccessRules::allow_all())
+            .create()
+        }
+
+        pub fn tip(&mut self, payment: Bucket, message: String) {
+            assert_eq!(
+                payment.resource_address(),
+                XTR,
+                "Invalid tip resource, the tip jar only accepts Tari (XTR) tokens"
+            );
+            assert!(payment.amount().is_positive(), "Invalid tip amount");
+            assert!(
+                message.len() <= MAX_MESSAGE_LEN,
+                "The message cannot be longer than {} bytes",
+                MAX_MESSAGE_LEN
+            );
+
+            self.tips.push((payment.amount(), message, Consensus::current_epoch()));
+            while self.tips.len() > MAX_TIP_LOG {
+                self.tips.remove(0);
+            }
+
+            self.vault.deposit(payment);
+        }
+
+        // sends all the tips to the owner account
+        // returns the badge back to the owner
+        pub fn withdraw(&mut self, owner_badge: Bucket) -> Bucket {
+            assert!(
+                owner_badge.resource_address() == self.owner_badge_resource,
+                "Invalid owner badge"
+            );
+
+            let tips = self.vault.withdraw_all();
+            ComponentManager::get(self.owner).call::<_, ()>("deposit".to_string(), args![tips]);
+
+            owner_badge
+        }
+
+        pub fn get_balance(&self) -> Amount {
+            self.vault.balance()
+        }
+
+        pub fn get_tips(&self) -> Vec<(Amount, String, u64)> {
+            self.tips.clone()
+        }
+
+        pub fn owner_badge_resource(&self) -> ResourceAddress {
+            self.owner_badge_resource
+        }
+    }
+}
@@ -0,0 +1,124 @@
+//   Copyright 2024 The Tari Project
+//   SPDX-License-Identifier: BSD-3-Clause
+
+use tari_template_lib::args;
+use tari_template_lib::constants::XTR;
+use tari_template_lib::models::{Amount, ComponentAddress, NonFungibleAddress, ResourceAddress};
+use tari_template_test_tooling::crypto::RistrettoSecretKey;
+use tari_template_test_tooling::support::assert_error::assert_reject_reason;
+use tari_template_test_tooling::TemplateTest;
+use tari_transaction::Transaction;
+
+// mirrors the "MAX_MESSAGE_LEN" constant of the template
+const MAX_MESSAGE_LEN: usize = 140;
+
+#[test]
+fn owner_withdraws_the_tips() {
+    let mut test = TemplateTest::new(["."]);
+
+    let owner = create_account(&mut test);
+    let tipjar_component: ComponentAddress = test.call_function("TipJar", "new", args![owner.component], vec![]);
+
+    // two different accounts leave a tip
+    let tipper1 = create_account(&mut test);
+    let tipper2 = create_account(&mut test);
+    tip(&mut test, tipjar_component, &tipper1, Amount(100), "Great work!");
+    tip(&mut test, tipjar_component, &tipper2, Amount(50), "Thanks");
+
+    let balance: Amount = test.call_method(tipjar_component, "get_balance", args![], vec![]);
+    assert_eq!(balance, Amount(150));
+    let tips: Vec<(Amount, String, u64)> = test.call_method(tipjar_component, "get_tips", args![], vec![]);
+    assert_eq!(tips.len(), 2);
+    assert_eq!(tips[0].0, Amount(100));
+    assert_eq!(tips[1].1, "Thanks");
+
+    // the owner gets all the tips
+    let owner_balance = get_account_tari_balance(&mut test, &owner);
+    let owner_badge_resource: ResourceAddress =
+        test.call_method(tipjar_component, "owner_badge_resource", args![], vec![]);
+    test.execute_expect_success(
+        Transaction::builder()
+            .call_method(owner.component, "withdraw", args![owner_badge_resource, Amount(1)])
+            .put_last_instruction_output_on_workspace("badge")
+            .call_method(tipjar_component, "withdraw", args![Workspace("badge")])
+            .put_last_instruction_output_on_workspace("badge")
+            .call_method(owner.component, "deposit", args![Workspace("badge")])
+            .sign(&owner.key)
+            .build(),
+        vec![owner.owner_token.clone()],
+    );
+    assert_eq!(get_account_tari_balance(&mut test, &owner), owner_balance + Amount(150));
+
+    // the tip jar is empty again
+    let balance: Amount = test.call_method(tipjar_component, "get_balance", args![], vec![]);
+    assert_eq!(balance, Amount::zero());
+}
+
+#[test]
+fn it_rejects_long_messages() {
+    let mut test = TemplateTest::new(["."]);
+
+    let owner = create_account(&mut test);
+    let tipjar_component: ComponentAddress = test.call_function("TipJar", "new", args![owner.component], vec![]);
+    let tipper = create_account(&mut test);
+
+    // messages up to the maximum length are accepted
+    let message = "a".repeat(MAX_MESSAGE_LEN);
+    tip(&mut test, tipjar_component, &tipper, Amount(10), &message);
+
+    let message = "a".repeat(MAX_MESSAGE_LEN + 1);
+    let reason = test.execute_expect_failure(
+        tip_transaction(tipjar_component, &tipper, Amount(10), &message),
+        vec![tipper.owner_token.clone()],
+    );
+    assert_reject_reason(reason, "The message cannot be longer than 140 bytes");
+
+    // the rejected tip is not recorded
+    let tips: Vec<(Amount, String, u64)> = test.call_method(tipjar_component, "get_tips", args![], vec![]);
+    assert_eq!(tips.len(), 1);
+}
+
+#[derive(Clone, Debug)]
+struct Account {
+    pub component: ComponentAddress,
+    pub owner_token: NonFungibleAddress,
+    pub key: RistrettoSecretKey,
+}
+
+fn create_account(test: &mut TemplateTest) -> Account {
+    let (component, owner_token, key) = test.create_funded_account();
+    Account {
+        component,
+        owner_token,
+        key,
+    }
+}
+
+fn get_account_tari_balance(test: &mut TemplateTest, account: &Account) -> Amount {
+    let result = test.execute_expect_success(
+        Transaction::builder()
+            .call_method(account.component, "balance", args![XTR])
+            .sign(&account.key)
+            .build(),
+        vec![account.owner_token.clone()],
+    );
+    result.finalize.execution_results[0]
+        .decode::<Amount>()
+        .unwrap()
+}
+
+fn tip(test: &mut TemplateTest, tipjar_component: ComponentAddress, tipper: &Account, amount: Amount, message: &str) {
+    test.execute_expect_success(
+        tip_transaction(tipjar_component, tipper, amount, message),
+        vec![tipper.owner_token.clone()],
+    );
+}
+
+fn tip_transaction(tipjar_component: ComponentAddress, tipper: &Account, amount: Amount, message: &str) -> Transaction {
+    Transaction::builder()
+        .call_method(tipper.component, "withdraw", args![XTR, amount])
+        .put_last_instruction_output_on_workspace("tip")
+        .call_method(tipjar_component, "tip", args![Workspace("tip"), message.to_string()])
+        .sign(&tipper.key)
+        .build()
+}
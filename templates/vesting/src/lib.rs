@@ -0,0 +1,117 @@
+//   Copyright 2024. The Tari Project
+//
+//   Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//   following conditions are met:
+//
+//   1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//   disclaimer.
+//
+//   2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//   following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//   3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//   products derived from this software without specific prior written permission.
+//
+//   THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//   INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//   DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//   SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//   SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//   WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//   USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use account_utils::assert_component_is_account;
+use tari_template_lib::prelude::*;
+
+#[template]
+mod vesting {
+    use super::*;
+
+    /// Linear token vesting
+    /// The locked tokens are released linearly to the beneficiary during the vesting period
+    pub struct Vesting {
+        // address of the account component that receives the vested tokens
+        beneficiary: ComponentAddress,
+
+        // holds the tokens that have not been claimed yet
+        vault: Vault,
+
+        // total amount of tokens locked at creation
+        total_amount: Amount,
+
+        // amount of tokens already sent to the beneficiary, to prevent double-claims
+        claimed_amount: Amount,
+
+        // epoch from which the tokens start to vest
+        start_epoch: u64,
+
+        // amount of epochs that takes to vest all the tokens
+        duration_epochs: u64,
+    }
+
+    impl Vesting {
+        pub fn new(
+            beneficiary: ComponentAddress,
+            total_bucket: Bucket,
+            start_epoch: u64,
+            duration_epochs: u64,
+        ) -> Component<Self> {
+            assert!(
+                total_bucket.resource_type() == ResourceType::Fungible,
+                "The resource is not fungible"
+            );
+            assert!(total_bucket.amount().is_positive(), "Invalid vesting amount");
+            assert!(duration_epochs > 0, "Invalid vesting duration");
+
+            // needed to ensure that we can deposit the vested tokens, otherwise they would be locked forever
+            assert_component_is_account(beneficiary);
+
+            Component::new(Self {
+                beneficiary,
+                total_amount: total_bucket.amount(),
+                vault: Vault::from_bucket(total_bucket),
+                claimed_amount: Amount::zero(),
+                start_epoch,
+                duration_epochs,
+            })
+            .with_access_rules(AccessRules::allow_all())
+            .create()
+        }
+
+        // sends all the vested but unclaimed tokens to the beneficiary
+        pub fn claim(&mut self) {
+            let claimable = self.get_claimable_amount();
+            if claimable.is_zero() {
+                return;
+            }
+
+            let bucket = self.vault.withdraw(claimable);
+            ComponentManager::get(self.beneficiary).call::<_, ()>("deposit".to_string(), args![bucket]);
+
+            self.claimed_amount = self.claimed_amount + claimable;
+        }
+
+        pub fn get_claimable_amount(&self) -> Amount {
+            self.get_vested_amount() - self.claimed_amount
+        }
+
+        pub fn get_claimed_amount(&self) -> Amount {
+            self.claimed_amount
+        }
+
+        // nothing is vested before the start epoch, and everything is vested after the vesting period
+        pub fn get_vested_amount(&self) -> Amount {
+            let current_epoch = Consensus::current_epoch();
+            if current_epoch <= self.start_epoch {
+                return Amount::zero();
+            }
+
+            let elapsed_epochs = current_epoch - self.start_epoch;
+            if elapsed_epochs >= self.duration_epochs {
+                return self.total_amount;
+            }
+
+            self.total_amount * Amount::new(elapsed_epochs as i64) / Amount::new(self.duration_epochs as i64)
+        }
+    }
+}
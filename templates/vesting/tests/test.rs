@@ -0,0 +1,172 @@
+//   Copyright 2024 The Tari Project
+//   SPDX-License-Identifier: BSD-3-Clause
+
+use tari_engine_types::virtual_substate::{VirtualSubstate, VirtualSubstateId};
+use tari_template_lib::args;
+use tari_template_lib::constants::XTR;
+use tari_template_lib::models::{Amount, ComponentAddress, NonFungibleAddress};
+use tari_template_test_tooling::crypto::RistrettoSecretKey;
+use tari_template_test_tooling::support::assert_error::assert_reject_reason;
+use tari_template_test_tooling::TemplateTest;
+use tari_transaction::Transaction;
+
+const START_EPOCH: u64 = 10;
+const DURATION_EPOCHS: u64 = 100;
+
+#[test]
+fn tokens_vest_linearly() {
+    let total_amount = Amount(1000);
+    let VestingTest {
+        mut test,
+        vesting_component,
+        beneficiary,
+    } = setup(total_amount);
+
+    let beneficiary_balance = get_account_tari_balance(&mut test, &beneficiary);
+
+    // claiming before the start epoch releases nothing
+    set_epoch(&mut test, START_EPOCH - 5);
+    claim(&mut test, vesting_component, &beneficiary);
+    assert_eq!(get_account_tari_balance(&mut test, &beneficiary), beneficiary_balance);
+
+    // half of the tokens are vested at the midpoint
+    set_epoch(&mut test, START_EPOCH + DURATION_EPOCHS / 2);
+    let claimable: Amount = test.call_method(vesting_component, "get_claimable_amount", args![], vec![]);
+    assert_eq!(claimable, Amount(500));
+    claim(&mut test, vesting_component, &beneficiary);
+    assert_eq!(
+        get_account_tari_balance(&mut test, &beneficiary),
+        beneficiary_balance + Amount(500)
+    );
+
+    // claiming again in the same epoch does not release anything else
+    claim(&mut test, vesting_component, &beneficiary);
+    let claimed: Amount = test.call_method(vesting_component, "get_claimed_amount", args![], vec![]);
+    assert_eq!(claimed, Amount(500));
+
+    // after the vesting period ends the full remainder is released
+    set_epoch(&mut test, START_EPOCH + DURATION_EPOCHS + 10);
+    claim(&mut test, vesting_component, &beneficiary);
+    assert_eq!(
+        get_account_tari_balance(&mut test, &beneficiary),
+        beneficiary_balance + total_amount
+    );
+    let claimable: Amount = test.call_method(vesting_component, "get_claimable_amount", args![], vec![]);
+    assert_eq!(claimable, Amount::zero());
+}
+
+#[test]
+fn it_rejects_beneficiaries_that_are_not_accounts() {
+    let VestingTest {
+        mut test,
+        vesting_component,
+        ..
+    } = setup(Amount(1000));
+    let vesting_template = test.get_template_address("Vesting");
+    let funder = create_account(&mut test);
+
+    // using the address of a vesting component instead of an account
+    let reason = test.execute_expect_failure(
+        Transaction::builder()
+            .call_method(funder.component, "withdraw", args![XTR, Amount(1000)])
+            .put_last_instruction_output_on_workspace("tokens")
+            .call_function(
+                vesting_template,
+                "new",
+                args![vesting_component, Workspace("tokens"), START_EPOCH, DURATION_EPOCHS],
+            )
+            .sign(&funder.key)
+            .build(),
+        vec![funder.owner_token.clone()],
+    );
+    assert_reject_reason(reason, "Invalid account");
+}
+
+#[derive(Clone, Debug)]
+struct Account {
+    pub component: ComponentAddress,
+    pub owner_token: NonFungibleAddress,
+    pub key: RistrettoSecretKey,
+}
+
+struct VestingTest {
+    test: TemplateTest,
+    vesting_component: ComponentAddress,
+    beneficiary: Account,
+}
+
+fn setup(total_amount: Amount) -> VestingTest {
+    let mut test = TemplateTest::new(["."]);
+    let vesting_template = test.get_template_address("Vesting");
+
+    let funder = create_account(&mut test);
+    let beneficiary = create_account(&mut test);
+
+    // lock the tokens of the funder in the vesting component
+    let result = test.execute_expect_success(
+        Transaction::builder()
+            .call_method(funder.component, "withdraw", args![XTR, total_amount])
+            .put_last_instruction_output_on_workspace("tokens")
+            .call_function(
+                vesting_template,
+                "new",
+                args![beneficiary.component, Workspace("tokens"), START_EPOCH, DURATION_EPOCHS],
+            )
+            .sign(&funder.key)
+            .build(),
+        vec![funder.owner_token.clone()],
+    );
+    let (substate_addr, _) = result
+        .expect_success()
+        .up_iter()
+        .find(|(address, substate)| {
+            address.is_component() && substate.substate_value().component().unwrap().module_name == "Vesting"
+        })
+        .unwrap();
+    let vesting_component = substate_addr.as_component_address().unwrap();
+
+    VestingTest {
+        test,
+        vesting_component,
+        beneficiary,
+    }
+}
+
+fn create_account(test: &mut TemplateTest) -> Account {
+    let (component, owner_token, key) = test.create_funded_account();
+    Account {
+        component,
+        owner_token,
+        key,
+    }
+}
+
+fn get_account_tari_balance(test: &mut TemplateTest, account: &Account) -> Amount {
+    let result = test.execute_expect_success(
+        Transaction::builder()
+            .call_method(account.component, "balance", args![XTR])
+            .sign(&account.key)
+            .build(),
+        vec![account.owner_token.clone()],
+    );
+    result.finalize.execution_results[0]
+        .decode::<Amount>()
+        .unwrap()
+}
+
+fn set_epoch(test: &mut TemplateTest, new_epoch: u64) {
+    test.set_virtual_substate(
+        VirtualSubstateId::CurrentEpoch,
+        VirtualSubstate::CurrentEpoch(new_epoch),
+    );
+}
+
+fn claim(test: &mut TemplateTest, vesting_component: ComponentAddress, signer: &Account) {
+    test.execute_expect_success(
+        Transaction::builder()
+            .call_method(vesting_component, "claim", args![])
+            .sign(&signer.key)
+            .build(),
+        vec![],
+    );
+}
@@ -0,0 +1,16 @@
+//   Copyright 2024 The Tari Project
+//   SPDX-License-Identifier: BSD-3-Clause
+
+// The build script exposes the path of every compiled template through a "TARI_TEMPLATE_<NAME>_WASM" env var,
+// which is available here as the integration tests are a target of the same package
+const FAUCET_WASM: &[u8] = include_bytes!(env!("TARI_TEMPLATE_FAUCET_WASM"));
+const TARISWAP_POOL_WASM: &[u8] = include_bytes!(env!("TARI_TEMPLATE_TARISWAP_POOL_WASM"));
+
+// all wasm binaries start with the "\0asm" magic number
+const WASM_MAGIC: &[u8] = b"\0asm";
+
+#[test]
+fn it_loads_template_wasms_from_the_emitted_env_vars() {
+    assert!(FAUCET_WASM.starts_with(WASM_MAGIC));
+    assert!(TARISWAP_POOL_WASM.starts_with(WASM_MAGIC));
+}